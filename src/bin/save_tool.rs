@@ -0,0 +1,74 @@
+//! Inspect and repair save files without starting the game.
+//!
+//! `cargo run --bin save_tool -- list <save dir>/slots/slot_1/save.ron`
+//!
+//! Object defaults are read from the LDtk project the game builds the world from, the asset
+//! directory is found the same way as the game does, `BEVY_ASSET_ROOT` overrides it.
+
+use bevy::prelude::*;
+use bevy_rpg::plugins::{
+    ldtk::{entities::read_ldtk_objects, LDTK_PROJECT},
+    save::{
+        paths::DataPaths,
+        saveable::Saveables,
+        tool::{clean, convert, diff, list, reset},
+        SaveablePlugin,
+    },
+    scene_editor::scene_loader::SceneRes,
+};
+use std::path::Path;
+
+const USAGE: &str = "Usage: save_tool <command>
+
+Commands:
+    list <save> [type]          List objects, optionally only one GameObjectType
+    diff <save> <other>         Show components that differ between two saves
+    clean <save> [out]          Drop orphaned entries
+    reset <save> <id> [out]     Reset an object to its LDtk defaults
+    convert <save> <out>        Re-encode a save, the format follows the extension (.ron / .bin)
+
+Without [out] the save is overwritten after a backup is made next to it.";
+
+fn main() {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .init();
+    // Fills the saveable registry that `Ecs` is deserialized with
+    let mut app = App::new();
+    app.add_plugin(SaveablePlugin);
+    let saveables = app.world.resource::<Saveables>();
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+    let stdout = &mut std::io::stdout();
+    let result = match args.as_slice() {
+        ["list", save] => list(stdout, saveables, Path::new(save), None),
+        ["list", save, ty] => list(stdout, saveables, Path::new(save), Some(*ty)),
+        ["diff", save, other] => diff(stdout, saveables, Path::new(save), Path::new(other)),
+        ["clean", save, out @ ..] if out.len() <= 1 => load_editor(saveables).and_then(|editor| {
+            let out = out.first().map(Path::new);
+            clean(stdout, saveables, &editor, Path::new(save), out)
+        }),
+        ["reset", save, id, out @ ..] if out.len() <= 1 => {
+            load_editor(saveables).and_then(|editor| {
+                let out = out.first().map(Path::new);
+                reset(stdout, saveables, &editor, Path::new(save), id, out)
+            })
+        }
+        ["convert", save, out] => convert(stdout, saveables, Path::new(save), Path::new(out)),
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {e:?}");
+        std::process::exit(1);
+    }
+}
+
+fn load_editor(saveables: &Saveables) -> anyhow::Result<SceneRes> {
+    let path = DataPaths::asset_dir().join(LDTK_PROJECT);
+    read_ldtk_objects(&path, saveables)
+}
@@ -0,0 +1,25 @@
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::type_complexity)]
+
+use bevy_rapier2d::prelude::*;
+
+pub mod components;
+pub mod events;
+pub mod plugins;
+pub mod res;
+pub mod states;
+pub mod systems;
+pub mod utils;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum AppState {
+    Title,
+    Level,
+    EscMenu,
+}
+
+pub const RAPIER_SCALE: f32 = 50.0;
+pub const INTERACT_GROUP: Group = Group::GROUP_1;
+pub const UNIT_GROUP: Group = Group::GROUP_2;
+pub const ALL_GROUP: Group = Group::ALL;
+pub const NONE_GROUP: Group = Group::NONE;
@@ -10,20 +10,7 @@ use bevy_inspector_egui_rapier::InspectableRapierPlugin;
 use bevy_rapier2d::prelude::*;
 use tracing_subscriber::{filter, prelude::*, EnvFilter};
 
-mod components;
-mod events;
-mod plugins;
-mod res;
-mod states;
-mod systems;
-mod utils;
-
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub enum AppState {
-    Title,
-    Level,
-    EscMenu,
-}
+use bevy_rpg::{components, plugins, res, states, systems, utils, AppState, RAPIER_SCALE};
 
 fn fps_system(mut windows: ResMut<Windows>, diagnostics: Res<Diagnostics>) {
     let window = windows.get_primary_mut().unwrap();
@@ -38,12 +25,6 @@ fn fps_system(mut windows: ResMut<Windows>, diagnostics: Res<Diagnostics>) {
     }
 }
 
-pub const RAPIER_SCALE: f32 = 50.0;
-pub const INTERACT_GROUP: Group = Group::GROUP_1;
-pub const UNIT_GROUP: Group = Group::GROUP_2;
-pub const ALL_GROUP: Group = Group::ALL;
-pub const NONE_GROUP: Group = Group::NONE;
-
 // fn editor() {
 //     App::new()
 //         .add_plugins(DefaultPlugins)
@@ -52,7 +33,7 @@ pub const NONE_GROUP: Group = Group::NONE;
 //         .add_plugin(WorldInspectorPlugin::new())
 //         // .add_plugin(InspectorPlugin::<SceneRes>::new())
 //         // Editor
-//         .add_plugin(plugins::scene_editor::scene_loader::SceneLoaderPlugin)
+//         // .add_plugin(plugins::scene_editor::scene_loader::SceneLoaderPlugin)
 //         .add_plugin(plugins::scene_editor::editor::EditorPlugin)
 //         .add_plugin(EditorComponentPlugin)
 //         .register_editor_component::<Transform>()
@@ -64,7 +45,7 @@ pub const NONE_GROUP: Group = Group::NONE;
 //                 .insert(PanCam::default());
 //         })
 //         // Tile Map
-//         .add_plugin(plugins::tiled_asset::TiledAssetPlugin)
+//         // .add_plugin(plugins::tiled_asset::TiledAssetPlugin)
 //         .add_plugin(plugins::tile_map::TileMapPlugin)
 //         .add_system(
 //             |camera: Query<(&Camera, &GlobalTransform), Changed<GlobalTransform>>,
@@ -121,12 +102,22 @@ fn main() {
 
     App::new()
         .add_state(AppState::Title)
+        .insert_resource(plugins::save::paths::DataPaths::resolve())
         // .insert_resource(ClearColor(Color::rgb(0.5, 0.5, 0.5)))
         .insert_resource(Msaa::default())
-        .add_plugins(DefaultPlugins.build().disable::<bevy::log::LogPlugin>())
+        .add_plugins(
+            DefaultPlugins
+                .build()
+                .disable::<bevy::log::LogPlugin>()
+                // Hot reload of assets/data
+                .set(AssetPlugin {
+                    watch_for_changes: true,
+                    ..default()
+                }),
+        )
         // FPS
         // .add_plugin(LogDiagnosticsPlugin::default())
-        .add_plugin(FrameTimeDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
         // FPS
         .add_system(fps_system)
         // Rapier
@@ -140,11 +131,12 @@ fn main() {
         })
         // LDTK
         .add_plugin(LdtkPlugin)
-        .insert_resource(LevelSelection::Identifier("Level_27".into()))
         // .insert_resource(LevelSelection::Uid(0))
+        // Levels are streamed by GameWorldPlugin
+        .init_resource::<plugins::ldtk::systems::CurrentLevel>()
         .insert_resource(LdtkSettings {
             level_spawn_behavior: LevelSpawnBehavior::UseWorldTranslation {
-                load_level_neighbors: true,
+                load_level_neighbors: false,
             },
             set_clear_color: SetClearColor::FromLevelBackground,
             ..Default::default()
@@ -164,12 +156,19 @@ fn main() {
         .register_type::<components::lifespan::Lifespan>()
         .register_type::<plugins::movement::Movement>()
         // Plugins
-        // .add_plugin(plugins::game_world::GameWorldPlugin)
+        .add_plugin(plugins::game_world::GameWorldPlugin)
         .add_plugin(plugins::save::SavePlugin)
         // .add_plugin(plugins::tiled_asset::TiledAssetPlugin)
         // .add_plugin(plugins::tile_map::TileMapPlugin)
         .add_system(plugins::movement::update_movement)
+        .add_plugin(plugins::navigation::NavigationPlugin)
         .add_plugin(plugins::unit::UnitPlugin)
+        .add_plugin(plugins::attribute::AttributePlugin)
+        .add_plugin(plugins::buff::BuffPlugin)
+        .add_plugin(plugins::experience::ExperiencePlugin)
+        .add_plugin(plugins::summon::SummonPlugin)
+        .add_plugin(plugins::behavior::BehaviorPlugin)
+        .add_plugin(plugins::threat::ThreatPlugin)
         .add_plugin(plugins::unit_state::UnitStatePlugin)
         .add_plugin(plugins::unit_action::UnitActionPlugin)
         .add_plugin(plugins::actions::action::ActionPlugin)
@@ -214,7 +213,7 @@ fn main() {
                 commands.spawn(Camera2dBundle::default());
 
                 commands.spawn(LdtkWorldBundle {
-                    ldtk_handle: asset_server.load("maps/test.ldtk"),
+                    ldtk_handle: asset_server.load(plugins::ldtk::LDTK_PROJECT),
                     ..Default::default()
                 });
             },
@@ -0,0 +1,550 @@
+use anyhow::ensure;
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashMap},
+};
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    plugins::{
+        animation::ChangeAnimation,
+        buff::{Buff, BuffId, CreateBuff},
+        damage::{DamageEvent, HealEvent, HitDamageEvent},
+        knockback::{KnockbackVec, KNOCKBACK_DURATION},
+        movement::Movement,
+        team::Team,
+        unit::Unit,
+        unit_action::{UnitActions, UnitAnimation},
+        unit_state::{
+            ActionSystemLabel, PassiveUpdateLabel, UnitActionEnterActiveEvent,
+            UnitActionEnterEvent, UnitPassiveUpdateEvent, UnitState,
+        },
+    },
+    utils::{self, Knockback, Shape},
+    RAPIER_SCALE,
+};
+
+use super::{
+    action::{attach, Skill, SkillAction},
+    attack::AttackEvent,
+    base::BaseSkill,
+};
+
+const ABILITY_FILE: &str = "data/abilities.ability.ron";
+
+/// Skills with [`BaseSkill::Ability`] run the effects of an ability in
+/// `assets/data/abilities.ability.ron`, so a new skill only needs data: an ability, and a skill
+/// file using `SkillId::Custom`.
+pub struct AbilityPlugin;
+impl Plugin for AbilityPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //
+            .add_asset::<AbilityAsset>()
+            .init_asset_loader::<AbilityAssetLoader>()
+            .init_resource::<Abilities>()
+            .add_startup_system(load_abilities)
+            .add_system(update_abilities)
+            .register_type::<AbilityAction>()
+            .add_event::<CastAbility>()
+            .add_system(attach::<AbilityAction>)
+            .add_system(enter.label(ActionSystemLabel::Enter))
+            .add_system(enter_active.label(ActionSystemLabel::EnterActive))
+            .add_system(passive_update.label(PassiveUpdateLabel))
+            .add_system(on_attack)
+            .add_system(cast_ability);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ability {
+    pub name: String,
+    pub description: String,
+    pub ability_type: AbilityType,
+    pub effects: Vec<(TargetSelector, Effect)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AbilityType {
+    /// Cast as an action, the target unit must be of this side.
+    Active(ActiveTarget),
+    Passive(PassiveTrigger),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActiveTarget {
+    None,
+    Enemy,
+    Alliance,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PassiveTrigger {
+    /// The owner attacks or hits, `Target` is the unit attacked.
+    OnAttack,
+    /// The owner is attacked or hit, `Target` is the attacker.
+    OnAttacked,
+    /// Every n seconds, there is no `Target`.
+    Interval(f32),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TargetSelector {
+    Target,
+    Source,
+    AllEnemy,
+    AllEnemyExceptTarget,
+    AllAlliance,
+    /// Enemies within the radius of the target, or the source if there is none.
+    EnemyInRadius(f32),
+    AllianceInRadius(f32),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Effect {
+    Damage(i32),
+    Heal(i32),
+    /// Buff, level, duration.
+    Buff(BuffId, i32, Option<f32>),
+    /// Push away from the source.
+    Knockback(f32),
+    /// Shot from the source at each selected unit, or at the target position if the
+    /// source selects itself.
+    Projectile(ProjectileEffect),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectileEffect {
+    pub speed: f32,
+    pub lifespan: f32,
+    pub radius: f32,
+    pub damage: i32,
+    pub hit_stun: f32,
+    pub knockback: f32,
+    pub image: Option<String>,
+}
+
+#[derive(Debug, TypeUuid)]
+#[uuid = "a3c7e0f2-6d14-4b8e-8f5a-2b9d41c6e7a0"]
+pub struct AbilityAsset(pub Vec<Ability>);
+
+#[derive(Default)]
+struct AbilityAssetLoader;
+impl AssetLoader for AbilityAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let abilities = ron::de::from_bytes::<Vec<Ability>>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(AbilityAsset(abilities)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ability.ron"]
+    }
+}
+
+/// Valid abilities by name, updated when the file changes.
+#[derive(Debug, Default, Resource)]
+pub struct Abilities {
+    handle: Handle<AbilityAsset>,
+    abilities: HashMap<String, Ability>,
+}
+
+impl Abilities {
+    pub fn get(&self, name: &str) -> Option<&Ability> {
+        self.abilities.get(name)
+    }
+}
+
+fn load_abilities(asset_server: Res<AssetServer>, mut abilities: ResMut<Abilities>) {
+    abilities.handle = asset_server.load(ABILITY_FILE);
+}
+
+fn update_abilities(
+    mut events: EventReader<AssetEvent<AbilityAsset>>,
+    assets: Res<Assets<AbilityAsset>>,
+    mut abilities: ResMut<Abilities>,
+) {
+    for ev in events.iter() {
+        match ev {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                if handle != &abilities.handle {
+                    continue;
+                }
+                let asset = match assets.get(handle) {
+                    Some(x) => x,
+                    None => continue,
+                };
+                let mut map = HashMap::default();
+                for ability in asset.0.iter() {
+                    if let Err(e) = validate(ability) {
+                        error!("Invalid ability {:?}: {e:?}", ability.name);
+                        continue;
+                    }
+                    if map.insert(ability.name.clone(), ability.clone()).is_some() {
+                        warn!("Ability {:?} is defined more than once", ability.name);
+                    }
+                }
+                info!("Loaded {} abilities", map.len());
+                abilities.abilities = map;
+            }
+            AssetEvent::Removed { .. } => {}
+        }
+    }
+}
+
+fn validate(ability: &Ability) -> anyhow::Result<()> {
+    ensure!(!ability.name.trim().is_empty(), "name is empty");
+    if let AbilityType::Passive(PassiveTrigger::Interval(secs)) = ability.ability_type {
+        ensure!(secs > 0.0, "interval must be positive, got {secs}");
+    }
+    for (selector, effect) in ability.effects.iter() {
+        if let TargetSelector::EnemyInRadius(r) | TargetSelector::AllianceInRadius(r) = selector {
+            ensure!(*r > 0.0, "radius must be positive, got {r}");
+        }
+        match effect {
+            Effect::Damage(x) | Effect::Heal(x) => {
+                ensure!(*x >= 0, "amount must not be negative, got {x}")
+            }
+            Effect::Buff(_, _, Some(duration)) => {
+                ensure!(*duration > 0.0, "duration must be positive, got {duration}")
+            }
+            Effect::Projectile(p) => {
+                ensure!(p.speed > 0.0, "projectile speed must be positive");
+                ensure!(p.lifespan > 0.0, "projectile lifespan must be positive");
+                ensure!(p.radius > 0.0, "projectile radius must be positive");
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Run the effects of an ability. Sent by ability skills, and usable for anything else.
+#[derive(Debug, Clone)]
+pub struct CastAbility {
+    pub ability: String,
+    pub source: Entity,
+    pub target: Option<Entity>,
+    pub position: Option<Vec2>,
+}
+
+#[derive(Debug, Default, Component, Reflect)]
+pub struct AbilityAction {
+    pub ability: String,
+    pub cooldown: f32,
+}
+
+impl SkillAction for AbilityAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Ability(ability) => Some(AbilityAction {
+                ability: ability.clone(),
+                cooldown: 0.0,
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn enter(
+    mut events: EventReader<UnitActionEnterEvent>,
+    query: Query<(&AbilityAction,)>,
+    mut unit_q: Query<(&UnitState, &mut Movement, &GlobalTransform)>,
+    mut anim_events: EventWriter<ChangeAnimation>,
+) {
+    for ev in events.iter() {
+        if let Ok((_,)) = query.get(ev.action) {
+            if let Ok((us, mut movement, gt)) = unit_q.get_mut(ev.unit) {
+                movement.speed = 0.0;
+                if let Some(pos) = us.command.as_ref().and_then(|c| c.target_position) {
+                    movement.face = Some(pos - gt.translation().truncate());
+                }
+                anim_events.send(ChangeAnimation {
+                    entity: ev.unit,
+                    name: UnitAnimation::Attack.to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn enter_active(
+    mut events: EventReader<UnitActionEnterActiveEvent>,
+    query: Query<(&AbilityAction,)>,
+    unit_q: Query<(&UnitState,)>,
+    mut cast_events: EventWriter<CastAbility>,
+) {
+    for ev in events.iter() {
+        if let Ok((act,)) = query.get(ev.action) {
+            debug!("Ability enter active: {:?}", ev);
+            if let Ok((us,)) = unit_q.get(ev.unit) {
+                let command = us.command.as_ref();
+                cast_events.send(CastAbility {
+                    ability: act.ability.clone(),
+                    source: ev.unit,
+                    target: command.and_then(|c| c.target_unit),
+                    position: command.and_then(|c| c.target_position),
+                });
+            }
+        }
+    }
+}
+
+fn passive_update(
+    mut events: EventReader<UnitPassiveUpdateEvent>,
+    mut query: Query<(&mut AbilityAction,)>,
+    abilities: Res<Abilities>,
+    time: Res<Time>,
+    mut cast_events: EventWriter<CastAbility>,
+) {
+    for ev in events.iter() {
+        if let Ok((mut act,)) = query.get_mut(ev.skill) {
+            let interval = match abilities.get(&act.ability).map(|a| &a.ability_type) {
+                Some(AbilityType::Passive(PassiveTrigger::Interval(x))) => *x,
+                _ => continue,
+            };
+            act.cooldown -= time.delta_seconds();
+            if act.cooldown > 0.0 {
+                continue;
+            }
+            act.cooldown = interval;
+            cast_events.send(CastAbility {
+                ability: act.ability.clone(),
+                source: ev.unit,
+                target: None,
+                position: None,
+            });
+        }
+    }
+}
+
+fn on_attack(
+    mut attack_events: EventReader<AttackEvent>,
+    mut hit_events: EventReader<HitDamageEvent>,
+    abilities: Res<Abilities>,
+    unit_q: Query<(&UnitActions,)>,
+    action_q: Query<(&AbilityAction,)>,
+    mut cast_events: EventWriter<CastAbility>,
+) {
+    let mut cast = |owner: Entity, other: Entity, trigger: PassiveTrigger| {
+        if let Ok((ua,)) = unit_q.get(owner) {
+            for (act,) in ua.actions.iter().flat_map(|e| action_q.get(*e)) {
+                let ability_type = abilities.get(&act.ability).map(|a| &a.ability_type);
+                if ability_type == Some(&AbilityType::Passive(trigger.clone())) {
+                    cast_events.send(CastAbility {
+                        ability: act.ability.clone(),
+                        source: owner,
+                        target: Some(other),
+                        position: None,
+                    });
+                }
+            }
+        }
+    };
+    for ev in attack_events.iter() {
+        cast(ev.attacker, ev.target, PassiveTrigger::OnAttack);
+        cast(ev.target, ev.attacker, PassiveTrigger::OnAttacked);
+    }
+    for ev in hit_events.iter() {
+        cast(ev.source, ev.victim, PassiveTrigger::OnAttack);
+        cast(ev.victim, ev.source, PassiveTrigger::OnAttacked);
+    }
+}
+
+fn cast_ability(
+    mut events: EventReader<CastAbility>,
+    abilities: Res<Abilities>,
+    unit_q: Query<(Entity, &Unit, &Team, &GlobalTransform)>,
+    mut knockback_q: Query<&mut KnockbackVec>,
+    rapier_context: Res<RapierContext>,
+    mut damage_ev: EventWriter<DamageEvent>,
+    mut heal_ev: EventWriter<HealEvent>,
+    mut buff_ev: EventWriter<CreateBuff>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    for ev in events.iter() {
+        let ability = match abilities.get(&ev.ability) {
+            Some(x) => x,
+            None => {
+                error!("Ability {:?} not found", ev.ability);
+                continue;
+            }
+        };
+        let (source_team, source_pos) = match unit_q.get(ev.source) {
+            Ok((_, _, team, gt)) => (team, gt.translation().truncate()),
+            Err(_) => continue,
+        };
+        let target = ev.target.and_then(|e| unit_q.get(e).ok());
+        if let AbilityType::Active(side) = &ability.ability_type {
+            let valid = match (side, target) {
+                (ActiveTarget::None, _) => true,
+                (ActiveTarget::Enemy, Some((_, _, team, _))) => source_team.is_enemy(team),
+                (ActiveTarget::Alliance, Some((_, _, team, _))) => source_team.is_ally(team),
+                (_, None) => false,
+            };
+            if !valid {
+                debug!("Ability {:?} has no valid target: {ev:?}", ability.name);
+                continue;
+            }
+        }
+        let center = target
+            .map(|(_, _, _, gt)| gt.translation().truncate())
+            .or(ev.position)
+            .unwrap_or(source_pos);
+        debug!("Cast ability {:?}: {ev:?}", ability.name);
+
+        for (selector, effect) in ability.effects.iter() {
+            let units: Vec<Entity> = match selector {
+                TargetSelector::Target => target.map(|t| t.0).into_iter().collect(),
+                TargetSelector::Source => vec![ev.source],
+                TargetSelector::AllEnemy
+                | TargetSelector::AllEnemyExceptTarget
+                | TargetSelector::AllAlliance => unit_q
+                    .iter()
+                    .filter(|(e, unit, team, _)| {
+                        !unit.dead
+                            && match selector {
+                                TargetSelector::AllAlliance => source_team.is_ally(team),
+                                TargetSelector::AllEnemyExceptTarget => {
+                                    source_team.is_enemy(team) && Some(*e) != ev.target
+                                }
+                                _ => source_team.is_enemy(team),
+                            }
+                    })
+                    .map(|(e, ..)| e)
+                    .collect(),
+                TargetSelector::EnemyInRadius(radius)
+                | TargetSelector::AllianceInRadius(radius) => {
+                    let mut units = vec![];
+                    let shape = Collider::ball(radius * RAPIER_SCALE);
+                    rapier_context.intersections_with_shape(
+                        center,
+                        0.0,
+                        &shape,
+                        QueryFilter::default(),
+                        |entity| {
+                            if let Ok((_, unit, team, _)) = unit_q.get(entity) {
+                                let side = match selector {
+                                    TargetSelector::EnemyInRadius(_) => source_team.is_enemy(team),
+                                    _ => source_team.is_ally(team),
+                                };
+                                if side && !unit.dead {
+                                    units.push(entity);
+                                }
+                            }
+                            true
+                        },
+                    );
+                    units
+                }
+            };
+
+            for unit in units {
+                match effect {
+                    Effect::Damage(damage) => damage_ev.send(DamageEvent {
+                        unit,
+                        source_unit: Some(ev.source),
+                        damage: *damage,
+                    }),
+                    Effect::Heal(heal) => heal_ev.send(HealEvent {
+                        unit,
+                        source_unit: Some(ev.source),
+                        heal: *heal,
+                    }),
+                    Effect::Buff(buff_id, level, duration) => buff_ev.send(CreateBuff {
+                        unit,
+                        buff_id: *buff_id,
+                        buff: Buff {
+                            level: *level,
+                            duration: *duration,
+                        },
+                        source: Some(ev.source),
+                    }),
+                    Effect::Knockback(force) => {
+                        if let (Ok((.., gt)), Ok(mut knockback)) =
+                            (unit_q.get(unit), knockback_q.get_mut(unit))
+                        {
+                            let dir = gt.translation().truncate() - source_pos;
+                            knockback
+                                .knockbacks
+                                .push((dir.normalize_or_zero() * *force, KNOCKBACK_DURATION));
+                        }
+                    }
+                    Effect::Projectile(p) => {
+                        let aim = match unit_q.get(unit) {
+                            Ok((.., gt)) if unit != ev.source => gt.translation().truncate(),
+                            _ => ev.position.unwrap_or(center),
+                        };
+                        spawn_projectile(
+                            &mut commands,
+                            &asset_server,
+                            p,
+                            ev.source,
+                            source_team,
+                            source_pos,
+                            aim - source_pos,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn spawn_projectile(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    p: &ProjectileEffect,
+    source: Entity,
+    team: &Team,
+    from: Vec2,
+    dir: Vec2,
+) {
+    let dir = match dir.try_normalize() {
+        Some(x) => x,
+        None => return,
+    };
+    let position = Transform::from_translation((from + dir * RAPIER_SCALE).extend(0.0))
+        .with_rotation(Quat::from_rotation_z(dir.y.atan2(dir.x)));
+    let id = utils::spawn_projectile(
+        utils::Projectile {
+            position,
+            lifespan: p.lifespan,
+            linvel: dir * p.speed * RAPIER_SCALE,
+            source,
+            shape: Shape::Ball(p.radius),
+            target_team: team.enemy_target(),
+            damage: p.damage,
+            hit_stun: p.hit_stun,
+            knockback: Knockback::Center(p.knockback),
+        },
+        commands,
+    );
+    if let Some(image) = &p.image {
+        let size = p.radius * 2.0 * RAPIER_SCALE;
+        let sprite = commands
+            .spawn(SpriteBundle {
+                texture: asset_server.load(image.as_str()),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(size)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                ..default()
+            })
+            .id();
+        commands.entity(id).add_child(sprite);
+    }
+}
@@ -1,19 +1,49 @@
 use bevy::prelude::*;
 
+use crate::{
+    plugins::{unit::Unit, unit_action::UnitActions},
+    res::GameWorldConfig,
+};
+
 use super::{
-    attack::AttackPlugin, attack_aura::AttackAuraPlugin, burning::BurningPlugin,
-    burst_fire::BurstFirePlugin, dead::DeadPlugin, dead_finger::DeadFingerPlugin,
-    forbidden_array::ForbiddenArrayPlugin, ghost_light::GhostLightPlugin,
-    heal_aura::HealAuraPlugin, hook::HookPlugin, ice_spear::IceSpearPlugin, idle::IdlePlugin,
-    move_to::MoveToPlugin, skill_id::SkillId, slash::SlashPlugin,
-    spider_attack::SpiderAttackPlugin, stab::StabPlugin, stop::StopPlugin, stun::StunPlugin,
+    ability::AbilityPlugin,
+    attack::AttackPlugin,
+    attack_aura::AttackAuraPlugin,
+    base::BaseSkill,
+    burning::BurningPlugin,
+    burst_fire::BurstFirePlugin,
+    dead::DeadPlugin,
+    dead_finger::DeadFingerPlugin,
+    drone::DronePlugin,
+    explosion::ExplosionPlugin,
+    fire_breath::FireBreathPlugin,
+    fireball::FireballPlugin,
+    forbidden_array::ForbiddenArrayPlugin,
+    ghost_light::GhostLightPlugin,
+    heal_aura::HealAuraPlugin,
+    hook::HookPlugin,
+    ice_spear::IceSpearPlugin,
+    idle::IdlePlugin,
+    life_drain::LifeDrainPlugin,
+    move_to::MoveToPlugin,
+    on_hit::OnHitPlugin,
+    setting::{SkillCost, SkillUsage},
+    skill_data::{SkillDataPlugin, SkillSettings},
+    skill_id::SkillId,
+    slash::SlashPlugin,
+    spider_attack::SpiderAttackPlugin,
+    stab::StabPlugin,
+    stop::StopPlugin,
+    stun::StunPlugin,
+    summon::SummonSkillPlugin,
+    thunder::ThunderPlugin,
     wolf_attack::WolfAttackPlugin,
 };
 
 pub struct ActionPlugin;
 impl Plugin for ActionPlugin {
     fn build(&self, app: &mut App) {
-        app
+        app.add_plugin(SkillDataPlugin)
             //
             .add_plugin(StunPlugin)
             .add_plugin(DeadPlugin)
@@ -21,9 +51,14 @@ impl Plugin for ActionPlugin {
             .add_plugin(StopPlugin)
             .add_plugin(MoveToPlugin)
             .add_plugin(AttackPlugin)
+            .add_plugin(OnHitPlugin)
             //
             .add_plugin(BurningPlugin)
             .add_plugin(BurstFirePlugin)
+            .add_plugin(DronePlugin)
+            .add_plugin(ExplosionPlugin)
+            .add_plugin(FireBreathPlugin)
+            .add_plugin(FireballPlugin)
             .add_plugin(ForbiddenArrayPlugin)
             .add_plugin(GhostLightPlugin)
             .add_plugin(HookPlugin)
@@ -33,16 +68,180 @@ impl Plugin for ActionPlugin {
             .add_plugin(StabPlugin)
             .add_plugin(WolfAttackPlugin)
             .add_plugin(DeadFingerPlugin)
+            .add_plugin(ThunderPlugin)
+            .add_plugin(LifeDrainPlugin)
+            .add_plugin(SummonSkillPlugin)
             .add_plugin(HealAuraPlugin)
             .add_plugin(AttackAuraPlugin)
-            .register_type::<Skill>();
+            .add_plugin(AbilityPlugin)
+            .register_type::<Skill>()
+            .add_system(recharge_skills);
     }
 }
 
 #[derive(Debug, Default, Component, Reflect)]
 pub struct Skill {
     pub action_id: SkillId,
-    // pub recharge: i32,
-    // pub charged: i32,
+    /// Charges used and not recharged yet.
+    pub spent: u32,
+    /// Seconds the next charge has been recharging.
+    pub recharge: f32,
+    /// Seconds a charge takes to recharge, from the setting when it was spent.
+    pub cooldown: f32,
     pub level: i32,
 }
+
+/// Why a unit cannot use a skill now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkillRejectReason {
+    Cooldown,
+    Cost(SkillCost),
+}
+
+impl Skill {
+    pub fn charges(&self, usage: &SkillUsage) -> u32 {
+        usage.charges.saturating_sub(self.spent)
+    }
+
+    /// Seconds until the next charge is back, zero if none is spent.
+    pub fn remaining_cooldown(&self) -> f32 {
+        match self.spent {
+            0 => 0.0,
+            _ => (self.cooldown - self.recharge).max(0.0),
+        }
+    }
+
+    pub fn check(&self, unit: &Unit, usage: &SkillUsage) -> Result<(), SkillRejectReason> {
+        if self.charges(usage) == 0 {
+            return Err(SkillRejectReason::Cooldown);
+        }
+        match usage.cost {
+            // Cannot kill yourself
+            Some(cost @ SkillCost::Hp(hp)) if unit.hp <= hp => Err(SkillRejectReason::Cost(cost)),
+            Some(cost @ SkillCost::Mp(mp)) if unit.mp < mp as f32 => {
+                Err(SkillRejectReason::Cost(cost))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Take a charge and pay the cost, after a successful [`Skill::check`].
+    pub fn spend(&mut self, unit: &mut Unit, usage: &SkillUsage) {
+        if usage.cooldown > 0.0 {
+            self.spent += 1;
+            self.cooldown = usage.cooldown;
+        }
+        match usage.cost {
+            Some(SkillCost::Hp(hp)) => unit.hp -= hp,
+            Some(SkillCost::Mp(mp)) => unit.mp -= mp as f32,
+            None => {}
+        }
+    }
+}
+
+/// The component of an action module, attached to the skills whose setting has its `BaseSkill`.
+pub trait SkillAction: Component {
+    type Bundle: Bundle;
+
+    /// Components for a skill with this base, `None` if the base is for another action.
+    fn attach(base: &BaseSkill, skill: &Skill) -> Option<Self::Bundle>;
+}
+
+/// Attach `A` to new skills, and check every skill again when the settings are reloaded,
+/// so a file that changes the `base` of a skill moves it to the other action.
+pub fn attach<A: SkillAction>(
+    mut commands: Commands,
+    q: Query<(Entity, &Skill, ChangeTrackers<Skill>, Option<&A>)>,
+    settings: Res<SkillSettings>,
+) {
+    let reloaded = settings.is_changed();
+    for (e, skill, tracker, action) in q.iter() {
+        if !reloaded && !tracker.is_added() {
+            continue;
+        }
+        let setting = match settings.get(&skill.action_id) {
+            Some(x) => x,
+            None => continue,
+        };
+        match (A::attach(&setting.base, skill), action) {
+            // Attached actions keep their state over reloads
+            (Some(bundle), None) => {
+                commands.entity(e).insert(bundle);
+            }
+            (None, Some(_)) => {
+                commands.entity(e).remove::<A::Bundle>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The skill of a unit for an action, if it has one.
+pub fn find_skill<'a>(
+    ua: &UnitActions,
+    action_id: SkillId,
+    skill_q: &'a Query<&Skill>,
+) -> Option<&'a Skill> {
+    ua.actions
+        .iter()
+        .flat_map(|e| skill_q.get(*e))
+        .find(|s| s.action_id == action_id)
+}
+
+fn recharge_skills(mut query: Query<&mut Skill>, time: Res<Time>, config: Res<GameWorldConfig>) {
+    if !config.active {
+        return;
+    }
+    for mut skill in query.iter_mut() {
+        if skill.spent == 0 {
+            continue;
+        }
+        let cooldown = skill.cooldown;
+        skill.recharge += time.delta_seconds();
+        while skill.spent > 0 && skill.recharge >= cooldown {
+            skill.spent -= 1;
+            skill.recharge -= cooldown;
+        }
+        if skill.spent == 0 {
+            skill.recharge = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::actions::{dead::DeadAction, setting::SkillSetting, stun::StunAction};
+
+    fn setting(file: &str) -> SkillSetting {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/data/skills/");
+        ron::from_str(&std::fs::read_to_string(format!("{path}{file}")).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn reloaded_base_moves_skill_to_its_action() {
+        let mut app = App::new();
+        app.init_resource::<SkillSettings>()
+            .add_system(attach::<StunAction>)
+            .add_system(attach::<DeadAction>);
+        let mut stun = setting("stun.skill.ron");
+        app.world
+            .resource_mut::<SkillSettings>()
+            .insert(stun.clone());
+        let skill = app
+            .world
+            .spawn(Skill {
+                action_id: SkillId::Stun,
+                ..Default::default()
+            })
+            .id();
+        app.update();
+        assert!(app.world.get::<StunAction>(skill).is_some());
+
+        stun.base = BaseSkill::Dead;
+        app.world.resource_mut::<SkillSettings>().insert(stun);
+        app.update();
+        assert!(app.world.get::<StunAction>(skill).is_none());
+        assert!(app.world.get::<DeadAction>(skill).is_some());
+    }
+}
@@ -1,49 +1,52 @@
 use core::str;
 
-use bevy::prelude::*;
-use bevy_rapier2d::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use rand::Rng;
 
-use crate::{
-    plugins::{
-        animation::ChangeAnimation,
-        damage::DamageEvent,
-        movement::Movement,
-        team::Team,
-        unit::Unit,
-        unit_action::{UnitActions, UnitAnimation},
-        unit_state::{
-            ActionSystemLabel, UnitActionEnterActiveEvent, UnitActionEnterEvent, UnitState,
-        },
-        visual_effect::{VisualEffect, VisualEffectMarker},
-    },
-    RAPIER_SCALE,
+use crate::plugins::{
+    animation::ChangeAnimation,
+    attribute::{Attribute, AttributeModifier, AttributeModifierEvent},
+    damage::DamageEvent,
+    movement::Movement,
+    unit::Unit,
+    unit_action::UnitAnimation,
+    unit_state::{ActionSystemLabel, UnitActionEnterActiveEvent, UnitActionEnterEvent, UnitState},
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    on_hit::OnHitEvent,
+    skill_data::SkillSettings,
+};
 
+/// Basic attacks, and [`BaseSkill::CriticalHit`] passives raising the crit chance they roll with.
 pub struct AttackPlugin;
 impl Plugin for AttackPlugin {
     fn build(&self, app: &mut App) {
         app
             //
             .register_type::<AttackAction>()
-            .add_system(attach)
+            .add_system(attach::<AttackAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive))
             .add_event::<AttackEvent>()
-            .add_system(attack_event);
+            .add_system(attack_event)
+            // Skills despawned during the update are only seen as removed after it
+            .add_system_to_stage(CoreStage::PostUpdate, critical_hit);
     }
 }
 
 #[derive(Debug, Default, Component, Reflect)]
 pub struct AttackAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::Attack = setting.base {
-            commands.entity(e).insert(AttackAction {});
+impl SkillAction for AttackAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Attack => Some(AttackAction {}),
+            _ => None,
         }
     }
 }
@@ -115,184 +118,104 @@ pub struct AttackEvent {
 fn attack_event(
     mut events: EventReader<AttackEvent>,
     mut damage_ev: EventWriter<DamageEvent>,
-    rapier_context: Res<RapierContext>,
-    unit_q: Query<(&Unit, &UnitActions, &GlobalTransform, &Team)>,
-    skill_q: Query<&Skill>,
-    team_q: Query<&Team>,
-    mut commands: Commands,
+    mut hit_ev: EventWriter<OnHitEvent>,
+    unit_q: Query<&Unit>,
 ) {
     let mut rng = rand::thread_rng();
     for ev in events.iter() {
-        if let Ok([attacker, target]) = unit_q.get_many([ev.attacker, ev.target]) {
-            let mut damage = attacker.0.atk;
-            let mut actions: Vec<_> = attacker
-                .1
-                .actions
-                .iter()
-                .flat_map(|entity| skill_q.get(*entity))
-                .filter(|action| {
-                    let settting = action.action_id.setting();
-                    matches!(
-                        settting.base,
-                        BaseSkill::LifeSteal
-                            | BaseSkill::CriticalHit
-                            | BaseSkill::Diffusion(_)
-                            | BaseSkill::FrostBall(_)
-                            | BaseSkill::SmashWave(_)
-                    )
-                })
-                .collect();
-            actions.sort_by_key(|action| {
-                let settting = action.action_id.setting();
-                match settting.base {
-                    BaseSkill::LifeSteal => 10,
-                    BaseSkill::CriticalHit => 1,
-                    BaseSkill::Diffusion(_) => 9,
-                    BaseSkill::FrostBall(_) => 1,
-                    BaseSkill::SmashWave(_) => 1,
-                    _ => 0,
-                }
-            });
-            for skill in actions {
-                let settting = skill.action_id.setting();
-                match settting.base {
-                    BaseSkill::LifeSteal => {
-                        todo!();
-                    }
-                    BaseSkill::CriticalHit => {
-                        let percent = 5;
-                        if rng.gen_ratio(percent, 100) {
-                            damage *= 2;
-                        }
-                    }
-                    BaseSkill::Diffusion(d) => {
-                        let radius = d.radius * RAPIER_SCALE;
-                        let pos = target.2.translation().truncate();
-
-                        commands.spawn(VisualEffectMarker {
-                            visual_effect: VisualEffect::Diffusion,
-                            duration: Some(0.5),
-                            repeat: false,
-                            size: Vec2::new(radius * 2.0, radius * 2.0),
-                            auto_despawn: false,
-                            pos,
-                        });
-
-                        let shape = Collider::ball(radius);
-                        let shape_pos = pos;
-                        let shape_rot = 0.0;
-                        let filter = QueryFilter::default();
-                        rapier_context.intersections_with_shape(
-                            shape_pos,
-                            shape_rot,
-                            &shape,
-                            filter,
-                            |entity| {
-                                if entity != ev.target {
-                                    if let Ok(team) = team_q.get(entity) {
-                                        if attacker.3.is_enemy(team) {
-                                            damage_ev.send(DamageEvent {
-                                                unit: entity,
-                                                source_unit: Some(ev.attacker),
-                                                damage: damage * d.percentage.get(skill.level)
-                                                    / 100,
-                                            });
-                                        }
-                                    }
-                                }
-                                true
-                            },
-                        );
-                    }
-                    BaseSkill::FrostBall(fb) => {
-                        // if rng.gen_bool(0.05) {}
-                        // let percent = 50;
-                        if rng.gen_ratio(fb.chance, 100) {
-                            let radius = fb.radius * RAPIER_SCALE;
-                            let pos = target.2.translation().truncate();
-
-                            commands.spawn(VisualEffectMarker {
-                                visual_effect: VisualEffect::FrostBall,
-                                duration: Some(0.5),
-                                repeat: false,
-                                size: Vec2::new(radius * 2.0, radius * 2.0),
-                                auto_despawn: false,
-                                pos,
-                            });
-
-                            let shape = Collider::ball(radius);
-                            let shape_pos = pos;
-                            let shape_rot = 0.0;
-                            let filter = QueryFilter::default();
-                            rapier_context.intersections_with_shape(
-                                shape_pos,
-                                shape_rot,
-                                &shape,
-                                filter,
-                                |entity| {
-                                    if let Ok(team) = team_q.get(entity) {
-                                        if attacker.3.is_enemy(team) {
-                                            damage_ev.send(DamageEvent {
-                                                unit: entity,
-                                                source_unit: Some(ev.attacker),
-                                                damage: fb.damage.get(skill.level),
-                                            });
-                                        }
-                                    }
-                                    true
-                                },
-                            );
-                        }
-                    }
-                    BaseSkill::SmashWave(sw) => {
-                        if rng.gen_ratio(sw.chance, 100) {
-                            let radius = sw.radius * RAPIER_SCALE;
-                            let pos = attacker.2.translation().truncate();
-
-                            commands.spawn(VisualEffectMarker {
-                                visual_effect: VisualEffect::SmashWave,
-                                duration: Some(0.5),
-                                repeat: false,
-                                size: Vec2::new(radius * 2.0, radius * 2.0),
-                                auto_despawn: false,
-                                pos,
-                            });
-
-                            let shape = Collider::ball(radius);
-                            let shape_pos = pos;
-                            let shape_rot = 0.0;
-                            let filter = QueryFilter::default();
-                            rapier_context.intersections_with_shape(
-                                shape_pos,
-                                shape_rot,
-                                &shape,
-                                filter,
-                                |entity| {
-                                    if let Ok(team) = team_q.get(entity) {
-                                        if attacker.3.is_enemy(team) {
-                                            damage_ev.send(DamageEvent {
-                                                unit: entity,
-                                                source_unit: Some(ev.attacker),
-                                                damage: sw.damage.get(skill.level),
-                                            });
-                                        }
-                                    }
-                                    true
-                                },
-                            );
-                        }
-                    }
-                    _ => {
-                        error!("Missing filter / handle in Attack");
-                    }
-                }
+        if let Ok([attacker, _]) = unit_q.get_many([ev.attacker, ev.target]) {
+            let mut damage = attacker.atk;
+            let crit_chance = attacker.crit_chance.clamp(0, 100) as u32;
+            if crit_chance > 0 && rng.gen_ratio(crit_chance, 100) {
+                damage *= 2;
             }
-
             damage_ev.send(DamageEvent {
                 unit: ev.target,
                 source_unit: Some(ev.attacker),
                 damage,
             });
+            // Passives of the attacker
+            hit_ev.send(OnHitEvent {
+                attacker: ev.attacker,
+                target: ev.target,
+                damage,
+            });
         }
     }
 }
+
+/// The crit chance of the skill level, added to the owner.
+///
+/// `owners` remembers the owner of each added modifier, to take it back once the skill is
+/// removed or becomes another skill.
+fn critical_hit(
+    query: Query<(Entity, &Skill, &Parent), Changed<Skill>>,
+    removed: RemovedComponents<Skill>,
+    settings: Res<SkillSettings>,
+    mut owners: Local<HashMap<Entity, Entity>>,
+    mut mod_ev: EventWriter<AttributeModifierEvent>,
+) {
+    for entity in removed.iter() {
+        if let Some(owner) = owners.remove(&entity) {
+            mod_ev.send(AttributeModifierEvent::Remove(owner, entity));
+        }
+    }
+    for (entity, skill, parent) in query.iter() {
+        if let Some(BaseSkill::CriticalHit(x)) = settings.get(&skill.action_id).map(|s| &s.base) {
+            owners.insert(entity, parent.get());
+            mod_ev.send(AttributeModifierEvent::Add(
+                parent.get(),
+                AttributeModifier {
+                    source: entity,
+                    attribute: Attribute::CritChance,
+                    amount: x.chance.get(skill.level) as f32,
+                    percentage: 0,
+                },
+            ));
+        } else if let Some(owner) = owners.remove(&entity) {
+            mod_ev.send(AttributeModifierEvent::Remove(owner, entity));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::{
+        actions::{setting::SkillSetting, skill_id::SkillId},
+        attribute::{AttributePlugin, Attributes},
+    };
+
+    #[test]
+    fn removed_skill_takes_crit_chance_back() {
+        let mut app = App::new();
+        app.add_plugin(AttributePlugin)
+            .init_resource::<SkillSettings>()
+            .add_system_to_stage(CoreStage::PostUpdate, critical_hit);
+        let doc = include_str!("../../../assets/data/skills/critical_hit.skill.ron");
+        let setting: SkillSetting = ron::from_str(doc).unwrap();
+        app.world.resource_mut::<SkillSettings>().insert(setting);
+        let unit = Unit::default();
+        let unit = app.world.spawn((Attributes::from_unit(&unit), unit)).id();
+        let skill = app
+            .world
+            .spawn(Skill {
+                action_id: SkillId::CriticalHit,
+                level: 2,
+                ..Default::default()
+            })
+            .id();
+        app.world.entity_mut(unit).push_children(&[skill]);
+        let crit_chance = |app: &App| app.world.get::<Unit>(unit).unwrap().crit_chance;
+
+        // Sent after the update, applied in the next one
+        app.update();
+        app.update();
+        assert_eq!(crit_chance(&app), 10);
+
+        app.world.entity_mut(skill).despawn();
+        app.update();
+        app.update();
+        assert_eq!(crit_chance(&app), 0);
+    }
+}
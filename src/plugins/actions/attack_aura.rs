@@ -3,21 +3,28 @@ use bevy_rapier2d::prelude::*;
 
 use crate::{
     plugins::{
+        attribute::{Attribute, AttributeModifier, AttributeModifierEvent},
         team::Team,
-        unit::{AttackModifierEvent, AttributeModifier},
         visual_effect::{VisualEffect, VisualEffectMarker},
     },
     RAPIER_SCALE,
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+};
 
+/// Auras that give allies inside a modifier, [`BaseSkill::AttackAura`] and
+/// [`BaseSkill::SpeedAura`].
 pub struct AttackAuraPlugin;
 impl Plugin for AttackAuraPlugin {
     fn build(&self, app: &mut App) {
         app
             //
-            .add_system(attach)
+            .add_system(attach::<AttackAuraAction>)
+            .add_system(level_radius)
             .add_system(collision_detection)
             .add_event::<EnterAura>()
             .add_system(enter_aura)
@@ -31,21 +38,57 @@ pub struct AttackAuraAction {
     pub cooldown: f32,
 }
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::AttackAura(base) = setting.base {
-            commands
-                .entity(e)
-                .insert(AttackAuraAction::default())
-                .insert(SpatialBundle::default())
-                .insert((
-                    Collider::ball(base.radius * RAPIER_SCALE),
-                    ActiveEvents::COLLISION_EVENTS,
-                    ActiveCollisionTypes::DYNAMIC_STATIC | ActiveCollisionTypes::KINEMATIC_STATIC,
-                    Sensor,
-                    RigidBody::Fixed,
-                ));
+/// Radius, and the attribute and percentage given to allies inside.
+fn aura(base: &BaseSkill, level: i32) -> Option<(f32, Attribute, i32)> {
+    match base {
+        BaseSkill::AttackAura(x) => {
+            Some((x.radius.get(level), Attribute::Atk, x.percentage.get(level)))
+        }
+        BaseSkill::SpeedAura(x) => Some((
+            x.radius.get(level),
+            Attribute::AttackSpeed,
+            x.percentage.get(level),
+        )),
+        _ => None,
+    }
+}
+
+impl SkillAction for AttackAuraAction {
+    type Bundle = (
+        AttackAuraAction,
+        SpatialBundle,
+        Collider,
+        ActiveEvents,
+        ActiveCollisionTypes,
+        Sensor,
+        RigidBody,
+    );
+
+    fn attach(base: &BaseSkill, skill: &Skill) -> Option<Self::Bundle> {
+        let (radius, _, _) = aura(base, skill.level)?;
+        Some((
+            AttackAuraAction::default(),
+            SpatialBundle::default(),
+            Collider::ball(radius * RAPIER_SCALE),
+            ActiveEvents::COLLISION_EVENTS,
+            ActiveCollisionTypes::DYNAMIC_STATIC | ActiveCollisionTypes::KINEMATIC_STATIC,
+            Sensor,
+            RigidBody::Fixed,
+        ))
+    }
+}
+
+fn level_radius(
+    mut q: Query<(&Skill, &mut Collider), (With<AttackAuraAction>, Changed<Skill>)>,
+    settings: Res<SkillSettings>,
+) {
+    for (skill, mut collider) in q.iter_mut() {
+        let base = settings.get(&skill.action_id).map(|s| &s.base);
+        if let Some((radius, _, _)) = base.and_then(|base| aura(base, skill.level)) {
+            let radius = radius * RAPIER_SCALE;
+            if collider.as_ball().map(|b| b.radius()) != Some(radius) {
+                *collider = Collider::ball(radius);
+            }
         }
     }
 }
@@ -100,22 +143,27 @@ fn enter_aura(
     mut events: EventReader<EnterAura>,
     query: Query<(&AttackAuraAction, &Skill, &Parent)>,
     team_q: Query<&Team>,
-    mut mod_ev: EventWriter<AttackModifierEvent>,
+    mut mod_ev: EventWriter<AttributeModifierEvent>,
     mut commands: Commands,
+    settings: Res<SkillSettings>,
 ) {
     for ev in events.iter() {
         debug!("{ev:?}");
         if let Ok((_aura, skill, parent)) = query.get(ev.skill) {
-            let setting = skill.action_id.setting();
-            if let BaseSkill::AttackAura(base) = setting.base {
+            let setting = match settings.get(&skill.action_id) {
+                Some(x) => x,
+                None => continue,
+            };
+            if let Some((_, attribute, percentage)) = aura(&setting.base, skill.level) {
                 if let Ok([team, target_team]) = team_q.get_many([parent.get(), ev.entity]) {
                     if team.is_ally(target_team) {
-                        mod_ev.send(AttackModifierEvent::Add(
+                        mod_ev.send(AttributeModifierEvent::Add(
                             ev.entity,
                             AttributeModifier {
                                 source: ev.skill,
-                                amount: 0,
-                                percentage: base.percentage.get(skill.level),
+                                attribute,
+                                amount: 0.0,
+                                percentage,
                             },
                         ));
 
@@ -141,9 +189,9 @@ struct LeaveAura {
     pub skill: Entity,
     pub entity: Entity,
 }
-fn leave_aura(mut events: EventReader<LeaveAura>, mut mod_ev: EventWriter<AttackModifierEvent>) {
+fn leave_aura(mut events: EventReader<LeaveAura>, mut mod_ev: EventWriter<AttributeModifierEvent>) {
     for ev in events.iter() {
         debug!("{ev:?}");
-        mod_ev.send(AttackModifierEvent::Remove(ev.entity, ev.skill));
+        mod_ev.send(AttributeModifierEvent::Remove(ev.entity, ev.skill));
     }
 }
@@ -1,8 +1,9 @@
-use std::ops::Mul;
+use std::ops::{Add, Mul};
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Clone, Reflect, Serialize, Deserialize)]
 #[reflect_value()]
 pub enum BaseSkill {
     Stun,
@@ -17,10 +18,10 @@ pub enum BaseSkill {
     Stab,
     BurstFire(BurstFire),
     Hook,
-    Fireball,
-    Explosion,
-    Burning,
-    Drone,
+    Fireball(Fireball),
+    Explosion(Explosion),
+    Burning(Burning),
+    Drone(Drone),
     GhostLight(GhostLight),
     SpiderAttack,
     WolfAttack,
@@ -35,10 +36,14 @@ pub enum BaseSkill {
     Thunder(Thunder),
     LifeDrain(LifeDrain),
     //
+    // Summon
+    //
+    Summon(Summon),
+    //
     // Passive
     //
-    LifeSteal,
-    CriticalHit,
+    LifeSteal(LifeSteal),
+    CriticalHit(CriticalHit),
     Diffusion(Diffusion),
     FrostBall(FrostBall),
     SmashWave(SmashWave),
@@ -48,19 +53,104 @@ pub enum BaseSkill {
     HealAura(HealAura),
     AttackAura(AttackAura),
     SpeedAura(SpeedAura),
+    //
+    // Data
+    //
+    /// Name of an ability in `assets/data/abilities.ability.ron`.
+    Ability(String),
+}
+
+impl BaseSkill {
+    /// Values that change with the level, for tooltips.
+    pub fn level_values(&self, level: i32) -> Vec<(&'static str, String)> {
+        let int = |v: &Value<i32>| v.get(level).to_string();
+        let float = |v: &Value<f32>| format!("{:.1}", v.get(level));
+        match self {
+            BaseSkill::BurstFire(x) => vec![("Damage", int(&x.damage))],
+            BaseSkill::GhostLight(x) => vec![("Damage", int(&x.damage))],
+            BaseSkill::Fireball(x) => {
+                vec![("Damage", int(&x.damage)), ("Radius", float(&x.radius))]
+            }
+            BaseSkill::Explosion(x) => {
+                vec![("Damage", int(&x.damage)), ("Radius", float(&x.radius))]
+            }
+            BaseSkill::Burning(x) => vec![
+                ("Damage", int(&x.damage)),
+                ("Radius", float(&x.radius)),
+                ("Duration", float(&x.duration)),
+            ],
+            BaseSkill::Drone(x) => {
+                vec![("Damage", int(&x.damage)), ("Duration", float(&x.duration))]
+            }
+            BaseSkill::DeadFinger(x) => vec![("Damage", int(&x.damage))],
+            BaseSkill::FireBreath(x) => {
+                vec![("Damage", int(&x.damage)), ("Range", float(&x.range))]
+            }
+            BaseSkill::Thunder(x) => vec![
+                ("Damage", int(&x.damage)),
+                ("Radius", float(&x.radius)),
+                ("Duration", float(&x.duration)),
+            ],
+            BaseSkill::LifeDrain(x) => {
+                vec![("Amount", int(&x.amount)), ("Duration", float(&x.duration))]
+            }
+            BaseSkill::LifeSteal(x) => vec![("Heal %", int(&x.percentage))],
+            BaseSkill::CriticalHit(x) => vec![("Chance %", int(&x.chance))],
+            BaseSkill::Summon(x) => vec![("Max", int(&x.max)), ("Lifetime", float(&x.lifetime))],
+            BaseSkill::Diffusion(x) => vec![
+                ("Damage %", int(&x.percentage)),
+                ("Radius", float(&x.radius)),
+            ],
+            BaseSkill::FrostBall(x) => {
+                vec![("Damage", int(&x.damage)), ("Radius", float(&x.radius))]
+            }
+            BaseSkill::SmashWave(x) => {
+                vec![("Damage", int(&x.damage)), ("Radius", float(&x.radius))]
+            }
+            BaseSkill::HealAura(x) => {
+                vec![("Heal %", int(&x.percentage)), ("Radius", float(&x.radius))]
+            }
+            BaseSkill::AttackAura(x) => vec![
+                ("Attack %", int(&x.percentage)),
+                ("Radius", float(&x.radius)),
+            ],
+            BaseSkill::SpeedAura(x) => vec![
+                ("Speed %", int(&x.percentage)),
+                ("Radius", float(&x.radius)),
+            ],
+            _ => vec![],
+        }
+    }
+
+    /// Seconds a channeled skill stays `Active`, used instead of `active_time`.
+    pub fn channel_time(&self, level: i32) -> Option<f32> {
+        match self {
+            BaseSkill::Thunder(x) => Some(x.duration.get(level)),
+            BaseSkill::LifeDrain(x) => Some(x.duration.get(level)),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Clone, Reflect, Serialize, Deserialize)]
 pub enum Value<T: Default + Reflect + FromReflect> {
     Fixed(T),
     /// Multiply by level
     Multiply(T),
+    /// The first value at level 1, the second is added for every level after
+    Linear(T, T),
 }
-impl<T: Copy + From<i32> + Mul<Output = T> + Default + Reflect + FromReflect> Value<T> {
+impl<T> Value<T>
+where
+    T: Copy + From<i16> + Add<Output = T> + Mul<Output = T> + Default + Reflect + FromReflect,
+{
     pub fn get(&self, level: i32) -> T {
+        // Levels are small, `f32` has no `From<i32>`
+        let level = level.clamp(1, i16::MAX as i32) as i16;
         match self {
             Value::Fixed(v) => *v,
             Value::Multiply(v) => T::from(level) * (*v),
+            Value::Linear(base, step) => *base + T::from(level - 1) * (*step),
         }
     }
 }
@@ -70,74 +160,136 @@ impl<T: Default + Reflect + FromReflect> Default for Value<T> {
     }
 }
 
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct BurstFire {
     pub f: f32,
     pub i: i32,
+    /// Of each shot.
+    pub damage: Value<i32>,
 }
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct GhostLight {
     pub f: f32,
     pub i: i32,
+    /// Of each ghost.
+    pub damage: Value<i32>,
+}
+
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
+pub struct LifeSteal {
+    /// Of the damage dealt.
+    pub percentage: Value<i32>,
 }
 
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
+pub struct CriticalHit {
+    /// Crit chance added to the owner, a crit attack deals double damage.
+    pub chance: Value<i32>,
+}
+
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
+pub struct Fireball {
+    /// Of the explosion.
+    pub damage: Value<i32>,
+    pub radius: Value<f32>,
+}
+
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
+pub struct Explosion {
+    pub damage: Value<i32>,
+    pub radius: Value<f32>,
+}
+
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
+pub struct Burning {
+    /// Every tick of the burning ground.
+    pub damage: Value<i32>,
+    pub radius: Value<f32>,
+    pub duration: Value<f32>,
+}
+
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
+pub struct Drone {
+    /// Of each shot.
+    pub damage: Value<i32>,
+    pub duration: Value<f32>,
+}
+
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct Diffusion {
     pub percentage: Value<i32>,
-    pub radius: f32,
+    pub radius: Value<f32>,
 }
 
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct FrostBall {
     pub chance: u32,
     pub damage: Value<i32>,
-    pub radius: f32,
+    pub radius: Value<f32>,
 }
 
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct SmashWave {
     pub chance: u32,
     pub damage: Value<i32>,
-    pub radius: f32,
+    pub radius: Value<f32>,
 }
 
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct HealAura {
-    pub radius: f32,
+    pub radius: Value<f32>,
     pub percentage: Value<i32>,
 }
 
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct AttackAura {
-    pub radius: f32,
+    pub radius: Value<f32>,
     pub percentage: Value<i32>,
 }
 
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct SpeedAura {
-    pub radius: f32,
+    pub radius: Value<f32>,
     pub percentage: Value<i32>,
 }
 
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct LifeDrain {
-    pub duration: f32,
+    pub duration: Value<f32>,
     pub amount: Value<i32>,
 }
 
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct Thunder {
-    pub duration: f32,
-    pub radius: f32,
+    pub duration: Value<f32>,
+    pub radius: Value<f32>,
     pub damage: Value<i32>,
 }
 
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
+pub struct Summon {
+    pub unit: SummonUnit,
+    /// Alive at once, the oldest is dismissed for a new one.
+    pub max: Value<i32>,
+    pub lifetime: Value<f32>,
+    /// Meters from the owner before it is pulled back.
+    pub leash: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum SummonUnit {
+    #[default]
+    Wolf,
+}
+
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct DeadFinger {
     pub damage: Value<i32>,
 }
 
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
 pub struct FireBreath {
     pub damage: Value<i32>,
+    /// Length of the cone.
+    pub range: Value<f32>,
 }
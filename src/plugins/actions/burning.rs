@@ -1,17 +1,31 @@
 use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
 
 use crate::{
     plugins::{
         animation::ChangeAnimation,
+        damage::DamageEvent,
         movement::Movement,
         team::Team,
+        unit::Unit,
         unit_action::UnitAnimation,
-        unit_state::{ActionSystemLabel, UnitActionEnterActiveEvent, UnitActionEnterEvent},
+        unit_state::{
+            ActionSystemLabel, UnitActionEnterActiveEvent, UnitActionEnterEvent, UnitState,
+        },
+        visual_effect::{VisualEffect, VisualEffectMarker},
     },
-    utils::{Knockback, Shape},
+    res::GameWorldConfig,
+    RAPIER_SCALE,
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+};
+
+/// Seconds between the damage ticks of burning ground.
+const BURNING_TICK: f32 = 0.5;
 
 pub struct BurningPlugin;
 impl Plugin for BurningPlugin {
@@ -19,20 +33,35 @@ impl Plugin for BurningPlugin {
         app
             //
             .register_type::<BurningAction>()
-            .add_system(attach)
+            .add_system(attach::<BurningAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
-            .add_system(enter_active.label(ActionSystemLabel::EnterActive));
+            .add_system(enter_active.label(ActionSystemLabel::EnterActive))
+            .add_system(burning_ground);
     }
 }
 
+/// Damages the enemies standing in it until its visual effect runs out.
+#[derive(Debug, Component)]
+pub struct BurningGround {
+    source: Entity,
+    team: Team,
+    pos: Vec2,
+    /// In meters.
+    radius: f32,
+    damage: i32,
+    tick: f32,
+}
+
 #[derive(Debug, Default, Component, Reflect)]
 pub struct BurningAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::Burning = setting.base {
-            commands.entity(e).insert(BurningAction {});
+impl SkillAction for BurningAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Burning(_) => Some(BurningAction {}),
+            _ => None,
         }
     }
 }
@@ -58,58 +87,82 @@ fn enter(
 
 fn enter_active(
     mut events: EventReader<UnitActionEnterActiveEvent>,
-    query: Query<(&BurningAction,)>,
-    unit_q: Query<(&GlobalTransform, &Team)>,
+    query: Query<(&BurningAction, &Skill)>,
+    unit_q: Query<(&GlobalTransform, &Team, &UnitState)>,
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    settings: Res<SkillSettings>,
 ) {
     for ev in events.iter() {
-        if let Ok((_,)) = query.get(ev.action) {
+        if let Ok((_, skill)) = query.get(ev.action) {
             debug!("Burning enter active: {:?}", ev);
-            if let Ok((position, team)) = unit_q.get(ev.unit) {
-                let animation_entity = {
-                    let texture_handle = asset_server.load("images/fox/unknown.png");
-                    let texture_atlas = TextureAtlas::from_grid(
-                        texture_handle,
-                        Vec2::new(128.0, 128.0),
-                        1,
-                        1,
-                        None,
-                        None,
-                    );
-                    let texture_atlas_handle = texture_atlases.add(texture_atlas);
+            if let Some(BaseSkill::Burning(base)) = settings.get(&skill.action_id).map(|s| &s.base)
+            {
+                if let Ok((gt, team, us)) = unit_q.get(ev.unit) {
+                    // Around the caster without a target position
+                    let pos = us
+                        .command
+                        .as_ref()
+                        .and_then(|c| c.target_position)
+                        .unwrap_or_else(|| gt.translation().truncate());
+                    let radius = base.radius.get(skill.level);
                     commands
-                        .spawn(SpriteSheetBundle {
-                            texture_atlas: texture_atlas_handle,
-                            transform: Transform {
-                                translation: Vec3::new(0.0, 0.0, 1.0),
-                                ..Default::default()
-                            },
-                            sprite: TextureAtlasSprite {
-                                index: 0,
-                                ..Default::default()
-                            },
-                            ..Default::default()
+                        .spawn(VisualEffectMarker {
+                            visual_effect: VisualEffect::Fire,
+                            duration: Some(base.duration.get(skill.level)),
+                            repeat: true,
+                            size: Vec2::splat(radius * RAPIER_SCALE * 2.0),
+                            auto_despawn: false,
+                            pos,
                         })
-                        .id()
-                };
-                let id = crate::utils::spawn_melee(
-                    crate::utils::Melee {
-                        offset: Vec2::new(0.0, 0.0),
-                        lifespan: 0.5,
-                        source: ev.unit,
-                        shape: Shape::Ball(1.5),
-                        parent_position: Transform::from(*position),
-                        target_team: team.enemy_target(),
-                        damage: 1,
-                        hit_stun: 0.3,
-                        knockback: Knockback::Center(0.1),
-                    },
-                    &mut commands,
-                );
-                commands.entity(id).add_child(animation_entity);
+                        .insert(BurningGround {
+                            source: ev.unit,
+                            team: *team,
+                            pos,
+                            radius,
+                            damage: base.damage.get(skill.level),
+                            tick: 0.0,
+                        });
+                }
             }
         }
     }
 }
+
+fn burning_ground(
+    mut query: Query<&mut BurningGround>,
+    unit_q: Query<(&Unit, &Team)>,
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut damage_ev: EventWriter<DamageEvent>,
+    config: Res<GameWorldConfig>,
+) {
+    if !config.active {
+        return;
+    }
+    for mut ground in query.iter_mut() {
+        ground.tick -= time.delta_seconds();
+        if ground.tick > 0.0 {
+            continue;
+        }
+        ground.tick += BURNING_TICK;
+        let shape = Collider::ball(ground.radius * RAPIER_SCALE);
+        rapier_context.intersections_with_shape(
+            ground.pos,
+            0.0,
+            &shape,
+            QueryFilter::default(),
+            |entity| {
+                if let Ok((unit, team)) = unit_q.get(entity) {
+                    if ground.team.is_enemy(team) && !unit.dead {
+                        damage_ev.send(DamageEvent {
+                            unit: entity,
+                            source_unit: Some(ground.source),
+                            damage: ground.damage,
+                        });
+                    }
+                }
+                true
+            },
+        );
+    }
+}
@@ -15,7 +15,11 @@ use crate::{
     RAPIER_SCALE,
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+};
 
 pub struct BurstFirePlugin;
 impl Plugin for BurstFirePlugin {
@@ -23,7 +27,7 @@ impl Plugin for BurstFirePlugin {
         app
             //
             .register_type::<BurstFireAction>()
-            .add_system(attach)
+            .add_system(attach::<BurstFireAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive))
             .add_system(active_update.label(ActionSystemLabel::ActiveUpdate));
@@ -35,6 +39,7 @@ pub fn burst_fire(
     mut position: Transform,
     player: Entity,
     team: &Team,
+    damage: i32,
     asset_server: &Res<AssetServer>,
     texture_atlases: &mut ResMut<Assets<TextureAtlas>>,
 ) {
@@ -72,7 +77,7 @@ pub fn burst_fire(
             source: player,
             shape: Shape::Ball(0.25),
             target_team: team.enemy_target(),
-            damage: 1,
+            damage,
             hit_stun: 0.3,
             knockback: Knockback::Center(0.0),
         },
@@ -85,16 +90,20 @@ pub fn burst_fire(
 pub struct BurstFireAction {
     pub f: f32,
     pub i: i32,
+    pub damage: i32,
 }
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::BurstFire(data) = setting.base {
-            commands.entity(e).insert(BurstFireAction {
+impl SkillAction for BurstFireAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, skill: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::BurstFire(data) => Some(BurstFireAction {
                 f: data.f,
                 i: data.i,
-            });
+                damage: data.damage.get(skill.level),
+            }),
+            _ => None,
         }
     }
 }
@@ -125,14 +134,19 @@ fn enter_active(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    settings: Res<SkillSettings>,
 ) {
     for ev in events.iter() {
         if let Ok((mut a, act)) = query.get_mut(ev.action) {
             debug!("BurstFire enter active: {:?}", ev);
-            let s = act.action_id.setting();
-            if let BaseSkill::BurstFire(bf) = s.base {
+            let s = match settings.get(&act.action_id) {
+                Some(x) => x,
+                None => continue,
+            };
+            if let BaseSkill::BurstFire(bf) = &s.base {
                 a.f = bf.f;
                 a.i = bf.i;
+                a.damage = bf.damage.get(act.level);
             }
             if let Ok((position, team)) = unit_q.get(ev.unit) {
                 burst_fire(
@@ -140,6 +154,7 @@ fn enter_active(
                     Transform::from(*position),
                     ev.unit,
                     team,
+                    a.damage,
                     &asset_server,
                     &mut texture_atlases,
                 );
@@ -172,6 +187,7 @@ pub fn active_update(
                         Transform::from(*position),
                         ev.unit,
                         team,
+                        a.damage,
                         &asset_server,
                         &mut texture_atlases,
                     );
@@ -184,6 +200,7 @@ pub fn active_update(
                         Transform::from(*position),
                         ev.unit,
                         team,
+                        a.damage,
                         &asset_server,
                         &mut texture_atlases,
                     );
@@ -11,7 +11,10 @@ use crate::{
     NONE_GROUP,
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+};
 
 pub struct DeadPlugin;
 impl Plugin for DeadPlugin {
@@ -19,7 +22,7 @@ impl Plugin for DeadPlugin {
         app
             //
             .register_type::<DeadAction>()
-            .add_system(attach)
+            .add_system(attach::<DeadAction>)
             .add_system(enter.label(ActionSystemLabel::Enter));
     }
 }
@@ -27,11 +30,13 @@ impl Plugin for DeadPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct DeadAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::Dead = setting.base {
-            commands.entity(e).insert(DeadAction {});
+impl SkillAction for DeadAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Dead => Some(DeadAction {}),
+            _ => None,
         }
     }
 }
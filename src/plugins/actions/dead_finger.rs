@@ -9,7 +9,11 @@ use crate::plugins::{
     visual_effect::{VisualEffect, VisualEffectMarker},
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+};
 
 pub struct DeadFingerPlugin;
 impl Plugin for DeadFingerPlugin {
@@ -17,7 +21,7 @@ impl Plugin for DeadFingerPlugin {
         app
             //
             .register_type::<DeadFingerAction>()
-            .add_system(attach)
+            .add_system(attach::<DeadFingerAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive));
     }
@@ -26,11 +30,13 @@ impl Plugin for DeadFingerPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct DeadFingerAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::DeadFinger(_) = setting.base {
-            commands.entity(e).insert(DeadFingerAction {});
+impl SkillAction for DeadFingerAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::DeadFinger(_) => Some(DeadFingerAction {}),
+            _ => None,
         }
     }
 }
@@ -62,12 +68,16 @@ fn enter_active(
 
     mut damage_ev: EventWriter<DamageEvent>,
     mut commands: Commands,
+    settings: Res<SkillSettings>,
 ) {
     for ev in events.iter() {
         if let Ok((_, skill)) = query.get(ev.action) {
             debug!("DeadFinger enter active: {:?}", ev);
-            let setting = skill.action_id.setting();
-            if let BaseSkill::DeadFinger(base) = setting.base {
+            let setting = match settings.get(&skill.action_id) {
+                Some(x) => x,
+                None => continue,
+            };
+            if let BaseSkill::DeadFinger(base) = &setting.base {
                 if let Ok((us,)) = unit_q.get(ev.unit) {
                     if let Some(uc) = &us.command {
                         if let Some(target) = uc.target_unit {
@@ -0,0 +1,198 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::lifespan::Lifespan,
+    plugins::{
+        animation::ChangeAnimation,
+        movement::Movement,
+        team::Team,
+        unit::Unit,
+        unit_action::UnitAnimation,
+        unit_state::{ActionSystemLabel, UnitActionEnterActiveEvent, UnitActionEnterEvent},
+    },
+    res::GameWorldConfig,
+    utils::{self, Knockback, Shape},
+    RAPIER_SCALE,
+};
+
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+};
+
+/// Distance from the owner, in meters.
+const DRONE_ORBIT: f32 = 1.5;
+/// Radians per second.
+const DRONE_ORBIT_SPEED: f32 = 2.0;
+/// Enemies further than this, in meters, are ignored.
+const DRONE_RANGE: f32 = 6.0;
+/// Seconds between shots.
+const DRONE_FIRE_INTERVAL: f32 = 1.0;
+
+pub struct DronePlugin;
+impl Plugin for DronePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //
+            .register_type::<DroneAction>()
+            .add_system(attach::<DroneAction>)
+            .add_system(enter.label(ActionSystemLabel::Enter))
+            .add_system(enter_active.label(ActionSystemLabel::EnterActive))
+            .add_system(drone_update);
+    }
+}
+
+/// Orbits its owner and shoots the nearest enemy, gone with the owner.
+#[derive(Debug, Component)]
+pub struct Drone {
+    owner: Entity,
+    damage: i32,
+    angle: f32,
+    cooldown: f32,
+}
+
+#[derive(Debug, Default, Component, Reflect)]
+pub struct DroneAction {}
+
+impl SkillAction for DroneAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Drone(_) => Some(DroneAction {}),
+            _ => None,
+        }
+    }
+}
+
+fn enter(
+    mut events: EventReader<UnitActionEnterEvent>,
+    query: Query<(&DroneAction,)>,
+    mut unit_q: Query<(&mut Movement,)>,
+    mut anim_events: EventWriter<ChangeAnimation>,
+) {
+    for ev in events.iter() {
+        if let Ok((_,)) = query.get(ev.action) {
+            if let Ok((mut movement,)) = unit_q.get_mut(ev.unit) {
+                movement.speed = 0.0;
+                anim_events.send(ChangeAnimation {
+                    entity: ev.unit,
+                    name: UnitAnimation::Drone.to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn enter_active(
+    mut events: EventReader<UnitActionEnterActiveEvent>,
+    query: Query<(&DroneAction, &Skill)>,
+    unit_q: Query<&GlobalTransform>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<SkillSettings>,
+) {
+    for ev in events.iter() {
+        if let Ok((_, skill)) = query.get(ev.action) {
+            debug!("Drone enter active: {:?}", ev);
+            if let Some(BaseSkill::Drone(base)) = settings.get(&skill.action_id).map(|s| &s.base) {
+                if let Ok(gt) = unit_q.get(ev.unit) {
+                    commands
+                        .spawn(SpriteBundle {
+                            texture: asset_server
+                                .load("images/particlePack_1.1/PNG (Transparent)/star_01.png"),
+                            sprite: Sprite {
+                                color: Color::ORANGE,
+                                custom_size: Some(Vec2::splat(24.0)),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(gt.translation()),
+                            ..default()
+                        })
+                        .insert(Name::new("Drone"))
+                        .insert(Drone {
+                            owner: ev.unit,
+                            damage: base.damage.get(skill.level),
+                            angle: 0.0,
+                            cooldown: DRONE_FIRE_INTERVAL,
+                        })
+                        .insert(Lifespan {
+                            duration: base.duration.get(skill.level),
+                        });
+                }
+            }
+        }
+    }
+}
+
+fn drone_update(
+    mut query: Query<(Entity, &mut Drone, &mut Transform)>,
+    unit_q: Query<(Entity, &Unit, &Team, &GlobalTransform)>,
+    time: Res<Time>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameWorldConfig>,
+) {
+    if !config.active {
+        return;
+    }
+    let delta = time.delta_seconds();
+    for (entity, mut drone, mut transform) in query.iter_mut() {
+        let (owner_team, owner_pos) = match unit_q.get(drone.owner) {
+            Ok((_, unit, team, gt)) if !unit.dead => (*team, gt.translation()),
+            _ => {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+        };
+        drone.angle += DRONE_ORBIT_SPEED * delta;
+        let offset = Vec2::from_angle(drone.angle) * DRONE_ORBIT * RAPIER_SCALE;
+        transform.translation = owner_pos + offset.extend(2.0);
+
+        drone.cooldown -= delta;
+        if drone.cooldown > 0.0 {
+            continue;
+        }
+        let pos = transform.translation.truncate();
+        let nearest = unit_q
+            .iter()
+            .filter(|(_, unit, team, _)| owner_team.is_enemy(team) && !unit.dead)
+            .map(|(_, _, _, gt)| gt.translation().truncate())
+            .filter(|p| p.distance(pos) <= DRONE_RANGE * RAPIER_SCALE)
+            .min_by(|a, b| a.distance(pos).total_cmp(&b.distance(pos)));
+        // Keeps the shot ready until an enemy comes in range
+        if let Some(target) = nearest {
+            drone.cooldown = DRONE_FIRE_INTERVAL;
+            let dir = (target - pos).normalize_or_zero();
+            let id = utils::spawn_projectile(
+                utils::Projectile {
+                    position: Transform::from_translation(pos.extend(0.0)),
+                    lifespan: 1.0,
+                    linvel: dir * 10.0 * RAPIER_SCALE,
+                    // Kills are credited to the owner
+                    source: drone.owner,
+                    shape: Shape::Ball(0.15),
+                    target_team: owner_team.enemy_target(),
+                    damage: drone.damage,
+                    hit_stun: 0.1,
+                    knockback: Knockback::None,
+                },
+                &mut commands,
+            );
+            commands.entity(id).with_children(|builder| {
+                builder.spawn(SpriteBundle {
+                    texture: asset_server
+                        .load("images/particlePack_1.1/PNG (Transparent)/light_01.png"),
+                    sprite: Sprite {
+                        color: Color::ORANGE,
+                        custom_size: Some(Vec2::splat(12.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                    ..default()
+                });
+            });
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+
+use crate::{
+    plugins::{
+        animation::ChangeAnimation,
+        movement::Movement,
+        team::Team,
+        unit_action::UnitAnimation,
+        unit_state::{
+            ActionSystemLabel, UnitActionEnterActiveEvent, UnitActionEnterEvent, UnitState,
+        },
+        visual_effect::{VisualEffect, VisualEffectMarker},
+    },
+    utils::{self, Knockback, Shape},
+    RAPIER_SCALE,
+};
+
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+};
+
+pub struct ExplosionPlugin;
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //
+            .register_type::<ExplosionAction>()
+            .add_system(attach::<ExplosionAction>)
+            .add_system(enter.label(ActionSystemLabel::Enter))
+            .add_system(enter_active.label(ActionSystemLabel::EnterActive));
+    }
+}
+
+#[derive(Debug, Default, Component, Reflect)]
+pub struct ExplosionAction {}
+
+impl SkillAction for ExplosionAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Explosion(_) => Some(ExplosionAction {}),
+            _ => None,
+        }
+    }
+}
+
+fn enter(
+    mut events: EventReader<UnitActionEnterEvent>,
+    query: Query<(&ExplosionAction,)>,
+    mut unit_q: Query<(&mut Movement,)>,
+    mut anim_events: EventWriter<ChangeAnimation>,
+) {
+    for ev in events.iter() {
+        if let Ok((_,)) = query.get(ev.action) {
+            if let Ok((mut movement,)) = unit_q.get_mut(ev.unit) {
+                movement.speed = 0.0;
+                anim_events.send(ChangeAnimation {
+                    entity: ev.unit,
+                    name: UnitAnimation::Explosion.to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn enter_active(
+    mut events: EventReader<UnitActionEnterActiveEvent>,
+    query: Query<(&ExplosionAction, &Skill)>,
+    unit_q: Query<(&GlobalTransform, &Team, &UnitState)>,
+    mut commands: Commands,
+    settings: Res<SkillSettings>,
+) {
+    for ev in events.iter() {
+        if let Ok((_, skill)) = query.get(ev.action) {
+            debug!("Explosion enter active: {:?}", ev);
+            if let Some(BaseSkill::Explosion(base)) =
+                settings.get(&skill.action_id).map(|s| &s.base)
+            {
+                if let Ok((gt, team, us)) = unit_q.get(ev.unit) {
+                    let pos = us
+                        .command
+                        .as_ref()
+                        .and_then(|c| c.target_position)
+                        .unwrap_or_else(|| gt.translation().truncate());
+                    explosion(
+                        &mut commands,
+                        ev.unit,
+                        team,
+                        pos,
+                        base.radius.get(skill.level),
+                        base.damage.get(skill.level),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Damage and knock back the enemies of `team` around `pos`, `radius` is in meters.
+pub fn explosion(
+    commands: &mut Commands,
+    source: Entity,
+    team: &Team,
+    pos: Vec2,
+    radius: f32,
+    damage: i32,
+) -> Entity {
+    commands.spawn(VisualEffectMarker {
+        visual_effect: VisualEffect::Explosion,
+        duration: Some(0.5),
+        repeat: false,
+        size: Vec2::splat(radius * RAPIER_SCALE * 2.0),
+        auto_despawn: false,
+        pos,
+    });
+    utils::spawn_projectile(
+        utils::Projectile {
+            position: Transform::from_translation(pos.extend(0.0)),
+            lifespan: 0.1,
+            linvel: Vec2::ZERO,
+            source,
+            shape: Shape::Ball(radius),
+            target_team: team.enemy_target(),
+            damage,
+            hit_stun: 0.3,
+            knockback: Knockback::Center(1.0),
+        },
+        commands,
+    )
+}
@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    plugins::{
+        animation::ChangeAnimation,
+        buff::{Buff, BuffId, CreateBuff},
+        damage::DamageEvent,
+        movement::Movement,
+        team::Team,
+        unit::Unit,
+        unit_action::UnitAnimation,
+        unit_state::{
+            ActionSystemLabel, UnitActionEnterActiveEvent, UnitActionEnterEvent, UnitState,
+        },
+        visual_effect::{VisualEffect, VisualEffectMarker},
+    },
+    RAPIER_SCALE,
+};
+
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+};
+
+/// Half of the cone, in radians.
+const CONE_ANGLE: f32 = std::f32::consts::PI / 6.0;
+/// Seconds the enemies caught keep burning.
+const BURN_DURATION: f32 = 2.0;
+
+pub struct FireBreathPlugin;
+impl Plugin for FireBreathPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //
+            .register_type::<FireBreathAction>()
+            .add_system(attach::<FireBreathAction>)
+            .add_system(enter.label(ActionSystemLabel::Enter))
+            .add_system(enter_active.label(ActionSystemLabel::EnterActive));
+    }
+}
+
+#[derive(Debug, Default, Component, Reflect)]
+pub struct FireBreathAction {}
+
+impl SkillAction for FireBreathAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::FireBreath(_) => Some(FireBreathAction {}),
+            _ => None,
+        }
+    }
+}
+
+fn enter(
+    mut events: EventReader<UnitActionEnterEvent>,
+    query: Query<(&FireBreathAction,)>,
+    mut unit_q: Query<(&mut Movement, &UnitState, &GlobalTransform)>,
+    mut anim_events: EventWriter<ChangeAnimation>,
+) {
+    for ev in events.iter() {
+        if let Ok((_,)) = query.get(ev.action) {
+            if let Ok((mut movement, us, gt)) = unit_q.get_mut(ev.unit) {
+                movement.speed = 0.0;
+                if let Some(pos) = us.command.as_ref().and_then(|c| c.target_position) {
+                    movement.face = Some(pos - gt.translation().truncate());
+                }
+                anim_events.send(ChangeAnimation {
+                    entity: ev.unit,
+                    name: UnitAnimation::Attack.to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn enter_active(
+    mut events: EventReader<UnitActionEnterActiveEvent>,
+    query: Query<(&FireBreathAction, &Skill)>,
+    owner_q: Query<(&GlobalTransform, &Team, &UnitState, &Movement)>,
+    unit_q: Query<(&Unit, &Team, &GlobalTransform)>,
+    rapier_context: Res<RapierContext>,
+    mut damage_ev: EventWriter<DamageEvent>,
+    mut buff_ev: EventWriter<CreateBuff>,
+    mut commands: Commands,
+    settings: Res<SkillSettings>,
+) {
+    for ev in events.iter() {
+        if let Ok((_, skill)) = query.get(ev.action) {
+            debug!("FireBreath enter active: {:?}", ev);
+            if let Some(BaseSkill::FireBreath(base)) =
+                settings.get(&skill.action_id).map(|s| &s.base)
+            {
+                if let Ok((gt, team, us, movement)) = owner_q.get(ev.unit) {
+                    let pos = gt.translation().truncate();
+                    let dir = us
+                        .command
+                        .as_ref()
+                        .and_then(|c| c.target_position)
+                        .map(|t| t - pos)
+                        .or(movement.face)
+                        .and_then(|d| d.try_normalize())
+                        .unwrap_or(Vec2::X);
+                    let range = base.range.get(skill.level) * RAPIER_SCALE;
+                    for i in 1..=3 {
+                        let f = i as f32 / 3.0;
+                        commands.spawn(VisualEffectMarker {
+                            visual_effect: VisualEffect::Fire,
+                            duration: Some(0.5),
+                            repeat: false,
+                            size: Vec2::splat(range * f * 0.8),
+                            auto_despawn: false,
+                            pos: pos + dir * range * f * 0.75,
+                        });
+                    }
+                    rapier_context.intersections_with_shape(
+                        pos,
+                        0.0,
+                        &Collider::ball(range),
+                        QueryFilter::default(),
+                        |entity| {
+                            if let Ok((unit, t, target_gt)) = unit_q.get(entity) {
+                                let to = target_gt.translation().truncate() - pos;
+                                let in_cone = to.length() < f32::EPSILON
+                                    || dir.angle_between(to).abs() <= CONE_ANGLE;
+                                if team.is_enemy(t) && !unit.dead && in_cone {
+                                    damage_ev.send(DamageEvent {
+                                        unit: entity,
+                                        source_unit: Some(ev.unit),
+                                        damage: base.damage.get(skill.level),
+                                    });
+                                    buff_ev.send(CreateBuff {
+                                        unit: entity,
+                                        buff_id: BuffId::Burn,
+                                        buff: Buff {
+                                            level: 1,
+                                            duration: Some(BURN_DURATION),
+                                        },
+                                        source: Some(ev.unit),
+                                    });
+                                }
+                            }
+                            true
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
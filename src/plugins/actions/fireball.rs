@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+
+use crate::{
+    plugins::{
+        animation::ChangeAnimation,
+        damage::OnHitDamage,
+        hit::HitEvent,
+        movement::Movement,
+        team::Team,
+        unit_action::UnitAnimation,
+        unit_state::{
+            ActionSystemLabel, UnitActionEnterActiveEvent, UnitActionEnterEvent, UnitState,
+        },
+    },
+    res::GameWorldConfig,
+    utils::{self, Knockback, Shape},
+    RAPIER_SCALE,
+};
+
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    explosion::explosion,
+    skill_data::SkillSettings,
+};
+
+/// Meters per second.
+const FIREBALL_SPEED: f32 = 8.0;
+/// Seconds a fireball without a target position flies before exploding.
+const FIREBALL_MAX_TIME: f32 = 1.5;
+
+pub struct FireballPlugin;
+impl Plugin for FireballPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //
+            .register_type::<FireballAction>()
+            .add_system(attach::<FireballAction>)
+            .add_system(enter.label(ActionSystemLabel::Enter))
+            .add_system(enter_active.label(ActionSystemLabel::EnterActive))
+            .add_system(detonate);
+    }
+}
+
+/// Flies until it hits an enemy or reaches the target position, then explodes.
+#[derive(Debug, Component)]
+pub struct Fireball {
+    source: Entity,
+    team: Team,
+    damage: i32,
+    radius: f32,
+    /// Seconds left to the target position.
+    time: f32,
+}
+
+#[derive(Debug, Default, Component, Reflect)]
+pub struct FireballAction {}
+
+impl SkillAction for FireballAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Fireball(_) => Some(FireballAction {}),
+            _ => None,
+        }
+    }
+}
+
+fn enter(
+    mut events: EventReader<UnitActionEnterEvent>,
+    query: Query<(&FireballAction,)>,
+    mut unit_q: Query<(&mut Movement, &UnitState, &GlobalTransform)>,
+    mut anim_events: EventWriter<ChangeAnimation>,
+) {
+    for ev in events.iter() {
+        if let Ok((_,)) = query.get(ev.action) {
+            if let Ok((mut movement, us, gt)) = unit_q.get_mut(ev.unit) {
+                movement.speed = 0.0;
+                if let Some(pos) = us.command.as_ref().and_then(|c| c.target_position) {
+                    movement.face = Some(pos - gt.translation().truncate());
+                }
+                anim_events.send(ChangeAnimation {
+                    entity: ev.unit,
+                    name: UnitAnimation::Fireball.to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn enter_active(
+    mut events: EventReader<UnitActionEnterActiveEvent>,
+    query: Query<(&FireballAction, &Skill)>,
+    unit_q: Query<(&GlobalTransform, &Team, &UnitState, &Movement)>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<SkillSettings>,
+) {
+    for ev in events.iter() {
+        if let Ok((_, skill)) = query.get(ev.action) {
+            debug!("Fireball enter active: {:?}", ev);
+            if let Some(BaseSkill::Fireball(base)) = settings.get(&skill.action_id).map(|s| &s.base)
+            {
+                if let Ok((gt, team, us, movement)) = unit_q.get(ev.unit) {
+                    let pos = gt.translation().truncate();
+                    let target = us.command.as_ref().and_then(|c| c.target_position);
+                    let dir = target
+                        .map(|t| t - pos)
+                        .or(movement.face)
+                        .and_then(|d| d.try_normalize())
+                        .unwrap_or(Vec2::X);
+                    let time = target.map_or(FIREBALL_MAX_TIME, |t| {
+                        t.distance(pos) / (FIREBALL_SPEED * RAPIER_SCALE)
+                    });
+                    let id = utils::spawn_projectile(
+                        utils::Projectile {
+                            position: Transform::from_translation(
+                                (pos + dir * 0.5 * RAPIER_SCALE).extend(0.0),
+                            ),
+                            // Exploded by `detonate` before it runs out
+                            lifespan: time + 1.0,
+                            linvel: dir * FIREBALL_SPEED * RAPIER_SCALE,
+                            source: ev.unit,
+                            shape: Shape::Ball(0.25),
+                            target_team: team.enemy_target(),
+                            damage: 0,
+                            hit_stun: 0.0,
+                            knockback: Knockback::None,
+                        },
+                        &mut commands,
+                    );
+                    commands
+                        .entity(id)
+                        // The explosion deals the damage
+                        .remove::<OnHitDamage>()
+                        .insert(Name::new("Fireball"))
+                        .insert(Fireball {
+                            source: ev.unit,
+                            team: *team,
+                            damage: base.damage.get(skill.level),
+                            radius: base.radius.get(skill.level),
+                            time,
+                        })
+                        .with_children(|builder| {
+                            builder.spawn(SpriteBundle {
+                                texture: asset_server
+                                    .load("images/particlePack_1.1/PNG (Transparent)/flame_01.png"),
+                                sprite: Sprite {
+                                    color: Color::ORANGE,
+                                    custom_size: Some(Vec2::splat(32.0)),
+                                    ..default()
+                                },
+                                transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                                ..default()
+                            });
+                        });
+                }
+            }
+        }
+    }
+}
+
+fn detonate(
+    mut hit_events: EventReader<HitEvent>,
+    mut query: Query<(Entity, &mut Fireball, &GlobalTransform)>,
+    time: Res<Time>,
+    config: Res<GameWorldConfig>,
+    mut commands: Commands,
+) {
+    if !config.active {
+        return;
+    }
+    let hits: Vec<Entity> = hit_events.iter().map(|ev| ev.hit_entity).collect();
+    for (entity, mut fireball, gt) in query.iter_mut() {
+        fireball.time -= time.delta_seconds();
+        if fireball.time <= 0.0 || hits.contains(&entity) {
+            explosion(
+                &mut commands,
+                fireball.source,
+                &fireball.team,
+                gt.translation().truncate(),
+                fireball.radius,
+                fireball.damage,
+            );
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -12,7 +12,10 @@ use crate::{
     RAPIER_SCALE,
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+};
 
 pub struct ForbiddenArrayPlugin;
 impl Plugin for ForbiddenArrayPlugin {
@@ -20,7 +23,7 @@ impl Plugin for ForbiddenArrayPlugin {
         app
             //
             .register_type::<ForbiddenArrayAction>()
-            .add_system(attach)
+            .add_system(attach::<ForbiddenArrayAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive));
     }
@@ -29,11 +32,13 @@ impl Plugin for ForbiddenArrayPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct ForbiddenArrayAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::ForbiddenArray = setting.base {
-            commands.entity(e).insert(ForbiddenArrayAction {});
+impl SkillAction for ForbiddenArrayAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::ForbiddenArray => Some(ForbiddenArrayAction {}),
+            _ => None,
         }
     }
 }
@@ -17,7 +17,12 @@ use crate::{
     RAPIER_SCALE,
 };
 
-use super::{action::Skill, base::BaseSkill, skill_id::SkillId};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+    skill_id::SkillId,
+};
 
 pub struct GhostLightPlugin;
 impl Plugin for GhostLightPlugin {
@@ -25,7 +30,7 @@ impl Plugin for GhostLightPlugin {
         app
             //
             .register_type::<GhostLightAction>()
-            .add_system(attach)
+            .add_system(attach::<GhostLightAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive))
             .add_system(active_update.label(ActionSystemLabel::ActiveUpdate))
@@ -48,6 +53,7 @@ pub fn spawn_ghost(
     angle: f32,
     target: Option<Entity>,
     team: &Team,
+    damage: i32,
 ) -> Entity {
     let animation_entity = {
         let texture_handle = asset_server.load("images/spider/spritesheet.png");
@@ -85,7 +91,7 @@ pub fn spawn_ghost(
             source: spider,
             shape: Shape::Ball(0.25),
             target_team: team.enemy_target(),
-            damage: 1,
+            damage,
             hit_stun: 0.0,
             knockback: Knockback::Center(0.1),
         },
@@ -124,16 +130,20 @@ pub fn fire_ghost_light(
 pub struct GhostLightAction {
     pub f: f32,
     pub i: i32,
+    pub damage: i32,
 }
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::GhostLight(data) = setting.base {
-            commands.entity(e).insert(GhostLightAction {
+impl SkillAction for GhostLightAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, skill: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::GhostLight(data) => Some(GhostLightAction {
                 f: data.f,
                 i: data.i,
-            });
+                damage: data.damage.get(skill.level),
+            }),
+            _ => None,
         }
     }
 }
@@ -160,6 +170,7 @@ fn enter(
 fn enter_active(
     mut events: EventReader<UnitActionEnterActiveEvent>,
     mut query: Query<(&mut GhostLightAction, &Skill, &Parent)>,
+    settings: Res<SkillSettings>,
 ) {
     for ev in events.iter() {
         if let Ok((mut a, act, parent)) = query.get_mut(ev.action) {
@@ -167,10 +178,14 @@ fn enter_active(
                 continue;
             }
             debug!("GhostLight enter active: {:?}", ev);
-            let s = act.action_id.setting();
-            if let BaseSkill::GhostLight(gl) = s.base {
+            let s = match settings.get(&act.action_id) {
+                Some(x) => x,
+                None => continue,
+            };
+            if let BaseSkill::GhostLight(gl) = &s.base {
                 a.f = gl.f;
                 a.i = gl.i;
+                a.damage = gl.damage.get(act.level);
             }
         }
     }
@@ -209,6 +224,7 @@ pub fn active_update(
                         0.0,
                         unit_state.command.as_ref().and_then(|c| c.target_unit),
                         team,
+                        a.damage,
                     );
                 }
                 if a.f <= 0.6 && a.i == 1 {
@@ -222,6 +238,7 @@ pub fn active_update(
                         std::f32::consts::PI / 4.0,
                         unit_state.command.as_ref().and_then(|c| c.target_unit),
                         team,
+                        a.damage,
                     );
                     spawn_ghost(
                         &mut commands,
@@ -232,6 +249,7 @@ pub fn active_update(
                         std::f32::consts::PI * 7.0 / 4.0,
                         unit_state.command.as_ref().and_then(|c| c.target_unit),
                         team,
+                        a.damage,
                     );
                 }
                 if a.f <= 0.4 && a.i == 2 {
@@ -245,6 +263,7 @@ pub fn active_update(
                         std::f32::consts::PI / 2.0,
                         unit_state.command.as_ref().and_then(|c| c.target_unit),
                         team,
+                        a.damage,
                     );
                     spawn_ghost(
                         &mut commands,
@@ -255,6 +274,7 @@ pub fn active_update(
                         std::f32::consts::PI * 3.0 / 2.0,
                         unit_state.command.as_ref().and_then(|c| c.target_unit),
                         team,
+                        a.damage,
                     );
                 }
                 if a.f <= 0.2 && a.i == 3 {
@@ -268,6 +288,7 @@ pub fn active_update(
                         std::f32::consts::PI * 3.0 / 4.0,
                         unit_state.command.as_ref().and_then(|c| c.target_unit),
                         team,
+                        a.damage,
                     );
                     spawn_ghost(
                         &mut commands,
@@ -278,6 +299,7 @@ pub fn active_update(
                         std::f32::consts::PI * 5.0 / 4.0,
                         unit_state.command.as_ref().and_then(|c| c.target_unit),
                         team,
+                        a.damage,
                     );
                 }
                 if a.f <= 0.0 {
@@ -290,6 +312,7 @@ pub fn active_update(
                         std::f32::consts::PI,
                         unit_state.command.as_ref().and_then(|c| c.target_unit),
                         team,
+                        a.damage,
                     );
 
                     change_events.send(ChangeActionRequest {
@@ -12,14 +12,18 @@ use crate::{
     RAPIER_SCALE,
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+};
 
 pub struct HealAuraPlugin;
 impl Plugin for HealAuraPlugin {
     fn build(&self, app: &mut App) {
         app
             //
-            .add_system(attach)
+            .add_system(attach::<HealAuraAction>)
             .add_system(passive_update.label(PassiveUpdateLabel));
     }
 }
@@ -29,11 +33,13 @@ pub struct HealAuraAction {
     pub cooldown: f32,
 }
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::HealAura(base) = setting.base {
-            commands.entity(e).insert(HealAuraAction::default());
+impl SkillAction for HealAuraAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::HealAura(_) => Some(HealAuraAction::default()),
+            _ => None,
         }
     }
 }
@@ -47,6 +53,7 @@ pub fn passive_update(
     rapier_context: Res<RapierContext>,
     mut heal_ev: EventWriter<HealEvent>,
     mut commands: Commands,
+    settings: Res<SkillSettings>,
 ) {
     for ev in events.iter() {
         if let Ok((mut act, skill)) = query.get_mut(ev.skill) {
@@ -57,10 +64,13 @@ pub fn passive_update(
                 act.cooldown = 1.0;
             }
             // debug!("passive_update: {ev:?}, {act:?}");
-            let setting = skill.action_id.setting();
-            if let BaseSkill::HealAura(base) = setting.base {
+            let setting = match settings.get(&skill.action_id) {
+                Some(x) => x,
+                None => continue,
+            };
+            if let BaseSkill::HealAura(base) = &setting.base {
                 if let Ok((team, gt)) = owner_q.get(ev.unit) {
-                    let shape = Collider::ball(base.radius * RAPIER_SCALE);
+                    let shape = Collider::ball(base.radius.get(skill.level) * RAPIER_SCALE);
                     let shape_pos = gt.translation().truncate();
                     let shape_rot = 0.0;
                     let filter = QueryFilter::default();
@@ -13,7 +13,10 @@ use crate::{
     RAPIER_SCALE,
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+};
 
 pub struct HookPlugin;
 impl Plugin for HookPlugin {
@@ -21,7 +24,7 @@ impl Plugin for HookPlugin {
         app
             //
             .register_type::<HookAction>()
-            .add_system(attach)
+            .add_system(attach::<HookAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive));
     }
@@ -30,11 +33,13 @@ impl Plugin for HookPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct HookAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::Hook = setting.base {
-            commands.entity(e).insert(HookAction {});
+impl SkillAction for HookAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Hook => Some(HookAction {}),
+            _ => None,
         }
     }
 }
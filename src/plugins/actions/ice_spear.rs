@@ -12,7 +12,10 @@ use crate::{
     RAPIER_SCALE,
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+};
 
 pub struct IceSpearPlugin;
 impl Plugin for IceSpearPlugin {
@@ -20,7 +23,7 @@ impl Plugin for IceSpearPlugin {
         app
             //
             .register_type::<IceSpearAction>()
-            .add_system(attach)
+            .add_system(attach::<IceSpearAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive));
     }
@@ -29,11 +32,13 @@ impl Plugin for IceSpearPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct IceSpearAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::IceSpear = setting.base {
-            commands.entity(e).insert(IceSpearAction {});
+impl SkillAction for IceSpearAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::IceSpear => Some(IceSpearAction {}),
+            _ => None,
         }
     }
 }
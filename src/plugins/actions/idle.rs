@@ -1,20 +1,20 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::{
-    plugins::{
-        animation::ChangeAnimation,
-        movement::Movement,
-        team::Team,
-        unit::Unit,
-        unit_action::UnitAnimation,
-        unit_state::{ActionSystemLabel, UnitActionActiveUpdateEvent, UnitActionEnterEvent},
-        units::unit_command::UnitCommand,
-    },
-    RAPIER_SCALE,
+use crate::plugins::{
+    animation::ChangeAnimation,
+    movement::Movement,
+    team::Team,
+    unit::Unit,
+    unit_action::UnitAnimation,
+    unit_state::{ActionSystemLabel, UnitActionActiveUpdateEvent, UnitActionEnterEvent},
+    units::unit_command::UnitCommand,
 };
 
-use super::{action::Skill, base::BaseSkill, skill_id::SkillId};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+};
 
 pub struct IdlePlugin;
 impl Plugin for IdlePlugin {
@@ -22,7 +22,7 @@ impl Plugin for IdlePlugin {
         app
             //
             // .add_system(range_detection)
-            .add_system(attach)
+            .add_system(attach::<IdleAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             // .add_system(active_update.label(ActionSystemLabel::ActiveUpdate))
             ;
@@ -82,18 +82,13 @@ pub struct IdleAction {
     pub units: Vec<Entity>,
 }
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::Idle = setting.base {
-            commands
-                .entity(e)
-                .insert(IdleAction::default())
-                // .insert(Collider::ball(2.5 * RAPIER_SCALE))
-                // .insert(ActiveEvents::COLLISION_EVENTS)
-                // .insert(ActiveCollisionTypes::all())
-                // .insert(Sensor)
-                ;
+impl SkillAction for IdleAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Idle => Some(IdleAction::default()),
+            _ => None,
         }
     }
 }
@@ -127,7 +122,7 @@ pub fn active_update(
         if let Ok((idle, _)) = query.get_mut(ev.action) {
             // debug!("Idle: {:?}", ev);
             if !idle.units.is_empty() {
-                if let Ok((mut uc, _)) = unit_q.get_mut(ev.unit) {
+                if let Ok((_uc, _)) = unit_q.get_mut(ev.unit) {
                     // uc.action_id = SkillId::Attack;
                     // uc.target_unit = idle.units.get(0).cloned();
                 }
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+
+use crate::plugins::{
+    animation::ChangeAnimation,
+    damage::{DamageEvent, HealEvent},
+    movement::Movement,
+    unit::Unit,
+    unit_action::UnitAnimation,
+    unit_state::{
+        ActionSystemLabel, ChangeActionRequest, UnitActionChannelTickEvent, UnitActionEnterEvent,
+        UnitState,
+    },
+    visual_effect::{VisualEffect, VisualEffectMarker},
+};
+
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+    skill_id::SkillId,
+};
+
+pub struct LifeDrainPlugin;
+impl Plugin for LifeDrainPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //
+            .register_type::<LifeDrainAction>()
+            .add_system(attach::<LifeDrainAction>)
+            .add_system(enter.label(ActionSystemLabel::Enter))
+            .add_system(channel_tick.label(ActionSystemLabel::ActiveUpdate));
+    }
+}
+
+#[derive(Debug, Default, Component, Reflect)]
+pub struct LifeDrainAction {}
+
+impl SkillAction for LifeDrainAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::LifeDrain(_) => Some(LifeDrainAction {}),
+            _ => None,
+        }
+    }
+}
+
+fn enter(
+    mut events: EventReader<UnitActionEnterEvent>,
+    query: Query<(&LifeDrainAction,)>,
+    mut unit_q: Query<(&mut Movement,)>,
+    mut anim_events: EventWriter<ChangeAnimation>,
+) {
+    for ev in events.iter() {
+        if let Ok((_,)) = query.get(ev.action) {
+            if let Ok((mut movement,)) = unit_q.get_mut(ev.unit) {
+                movement.speed = 0.0;
+                anim_events.send(ChangeAnimation {
+                    entity: ev.unit,
+                    name: UnitAnimation::Idle.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Move hp from the target to the caster, the channel ends with the target.
+fn channel_tick(
+    mut events: EventReader<UnitActionChannelTickEvent>,
+    query: Query<(&LifeDrainAction, &Skill)>,
+    owner_q: Query<(&UnitState, &GlobalTransform)>,
+    target_q: Query<(&Unit, &GlobalTransform)>,
+    mut damage_ev: EventWriter<DamageEvent>,
+    mut heal_ev: EventWriter<HealEvent>,
+    mut change_events: EventWriter<ChangeActionRequest>,
+    mut commands: Commands,
+    settings: Res<SkillSettings>,
+) {
+    for ev in events.iter() {
+        if let Ok((_, skill)) = query.get(ev.action) {
+            let setting = match settings.get(&skill.action_id) {
+                Some(x) => x,
+                None => continue,
+            };
+            if let BaseSkill::LifeDrain(base) = &setting.base {
+                if let Ok((us, owner_gt)) = owner_q.get(ev.unit) {
+                    let target = us.command.as_ref().and_then(|c| c.target_unit);
+                    let (target, target_gt) = match target.and_then(|t| {
+                        target_q
+                            .get(t)
+                            .ok()
+                            .filter(|(unit, _)| !unit.dead)
+                            .map(|(_, gt)| (t, gt))
+                    }) {
+                        Some(x) => x,
+                        None => {
+                            change_events.send(ChangeActionRequest {
+                                action_id: SkillId::Idle,
+                                command: default(),
+                                entity: ev.unit,
+                            });
+                            continue;
+                        }
+                    };
+                    let amount = base.amount.get(skill.level);
+                    damage_ev.send(DamageEvent {
+                        unit: target,
+                        source_unit: Some(ev.unit),
+                        damage: amount,
+                    });
+                    heal_ev.send(HealEvent {
+                        unit: ev.unit,
+                        source_unit: Some(ev.unit),
+                        heal: amount,
+                    });
+                    commands.spawn(VisualEffectMarker {
+                        visual_effect: VisualEffect::DeadFinger,
+                        duration: Some(0.3),
+                        repeat: false,
+                        size: Vec2 { x: 40.0, y: 40.0 },
+                        auto_despawn: false,
+                        pos: target_gt.translation().truncate(),
+                    });
+                    commands.spawn(VisualEffectMarker {
+                        visual_effect: VisualEffect::Heal,
+                        duration: Some(0.3),
+                        repeat: false,
+                        size: Vec2 { x: 40.0, y: 40.0 },
+                        auto_despawn: false,
+                        pos: owner_gt.translation().truncate(),
+                    });
+                }
+            }
+        }
+    }
+}
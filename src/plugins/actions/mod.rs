@@ -1,3 +1,4 @@
+pub mod ability;
 pub mod action;
 pub mod attack;
 pub mod attack_aura;
@@ -6,14 +7,21 @@ pub mod burning;
 pub mod burst_fire;
 pub mod dead;
 pub mod dead_finger;
+pub mod drone;
+pub mod explosion;
+pub mod fire_breath;
+pub mod fireball;
 pub mod forbidden_array;
 pub mod ghost_light;
 pub mod heal_aura;
 pub mod hook;
 pub mod ice_spear;
 pub mod idle;
+pub mod life_drain;
 pub mod move_to;
+pub mod on_hit;
 pub mod setting;
+pub mod skill_data;
 pub mod skill_id;
 pub mod slash;
 pub mod spawn_attack;
@@ -21,6 +29,8 @@ pub mod spider_attack;
 pub mod stab;
 pub mod stop;
 pub mod stun;
+pub mod summon;
+pub mod thunder;
 pub mod wolf_attack;
 
 // struct Ability {
@@ -3,20 +3,28 @@ use bevy::prelude::*;
 use crate::plugins::{
     animation::ChangeAnimation,
     movement::Movement,
+    navigation::{NavGrid, NavPath},
     unit::Unit,
     unit_action::UnitAnimation,
     unit_state::{ActionSystemLabel, UnitActionActiveUpdateEvent, UnitActionEnterEvent, UnitState},
     units::unit_command::UnitCommand,
 };
 
-use super::{action::Skill, base::BaseSkill, skill_id::SkillId};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_id::SkillId,
+};
+
+/// Waypoints closer than this, in pixels, count as reached.
+const WAYPOINT_REACHED: f32 = 16.0;
 
 pub struct MoveToPlugin;
 impl Plugin for MoveToPlugin {
     fn build(&self, app: &mut App) {
         app
             //
-            .add_system(attach)
+            .add_system(attach::<MoveToAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(active_update.label(ActionSystemLabel::ActiveUpdate));
     }
@@ -25,11 +33,13 @@ impl Plugin for MoveToPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct MoveToAction;
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::MoveTo = setting.base {
-            commands.entity(e).insert(MoveToAction);
+impl SkillAction for MoveToAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::MoveTo => Some(MoveToAction),
+            _ => None,
         }
     }
 }
@@ -62,14 +72,33 @@ pub fn active_update(
         &GlobalTransform,
         &mut Movement,
         &UnitState,
+        &mut NavPath,
     )>,
+    grid: Res<NavGrid>,
 ) {
     for ev in events.iter() {
         if let Ok((_, _)) = query.get_mut(ev.action) {
             // debug!("MoveTo: {:?}", ev);
-            if let Ok((u, mut command, position, mut movement, us)) = unit_q.get_mut(ev.unit) {
-                if let Some(pos) = us.command.as_ref().and_then(|c| c.target_position) {
-                    let dir = pos - position.translation().truncate();
+            if let Ok((u, mut command, position, mut movement, us, mut path)) =
+                unit_q.get_mut(ev.unit)
+            {
+                if let Some(goal) = us.command.as_ref().and_then(|c| c.target_position) {
+                    let here = position.translation().truncate();
+                    // Only search again when the goal moved to another cell
+                    if path.waypoints.is_empty() || NavGrid::cell(path.goal) != NavGrid::cell(goal)
+                    {
+                        path.waypoints = grid.find_path(here, goal).unwrap_or_else(|| vec![goal]);
+                    }
+                    path.goal = goal;
+                    if let Some(last) = path.waypoints.last_mut() {
+                        *last = goal;
+                    }
+                    while path.waypoints.len() > 1
+                        && path.waypoints[0].distance(here) < WAYPOINT_REACHED
+                    {
+                        path.waypoints.remove(0);
+                    }
+                    let dir = path.waypoints[0] - here;
                     // debug!("dir.length_squared(): {}", dir.length_squared());
                     if dir.length_squared() > 100.0 {
                         movement.face = Some(dir);
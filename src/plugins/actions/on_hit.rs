@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    plugins::{
+        buff::{Buff, BuffId, CreateBuff},
+        damage::{DamageEvent, HealEvent, HitDamageEvent},
+        team::Team,
+        unit_action::UnitActions,
+        visual_effect::{VisualEffect, VisualEffectMarker},
+    },
+    RAPIER_SCALE,
+};
+
+use super::{action::Skill, base::BaseSkill, skill_data::SkillSettings};
+
+/// Seconds enemies caught by a frost ball are slowed.
+const FROST_BALL_SLOW: f32 = 2.0;
+
+/// Passives of the attacker reacting to its attacks and projectiles landing.
+pub struct OnHitPlugin;
+impl Plugin for OnHitPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //
+            .add_event::<OnHitEvent>()
+            .add_system(hit_damage)
+            .add_system(on_hit_passives.after(hit_damage));
+    }
+}
+
+/// A hit landed, the damage has already been dealt.
+#[derive(Debug, Clone)]
+pub struct OnHitEvent {
+    pub attacker: Entity,
+    pub target: Entity,
+    pub damage: i32,
+}
+
+fn hit_damage(mut events: EventReader<HitDamageEvent>, mut sends: EventWriter<OnHitEvent>) {
+    for ev in events.iter() {
+        if ev.source != ev.victim {
+            sends.send(OnHitEvent {
+                attacker: ev.source,
+                target: ev.victim,
+                damage: ev.damage,
+            });
+        }
+    }
+}
+
+/// Order the passives are applied in.
+fn priority(base: &BaseSkill) -> Option<i32> {
+    match base {
+        BaseSkill::FrostBall(_) | BaseSkill::SmashWave(_) => Some(1),
+        BaseSkill::Diffusion(_) => Some(2),
+        BaseSkill::LifeSteal(_) => Some(3),
+        _ => None,
+    }
+}
+
+fn on_hit_passives(
+    mut events: EventReader<OnHitEvent>,
+    rapier_context: Res<RapierContext>,
+    unit_q: Query<(&UnitActions, &GlobalTransform, &Team)>,
+    pos_q: Query<&GlobalTransform>,
+    skill_q: Query<&Skill>,
+    team_q: Query<&Team>,
+    mut damage_ev: EventWriter<DamageEvent>,
+    mut heal_ev: EventWriter<HealEvent>,
+    mut buff_ev: EventWriter<CreateBuff>,
+    mut commands: Commands,
+    settings: Res<SkillSettings>,
+) {
+    let mut rng = rand::thread_rng();
+    for ev in events.iter() {
+        let (ua, attacker_gt, attacker_team) = match unit_q.get(ev.attacker) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        let target_pos = match pos_q.get(ev.target) {
+            Ok(gt) => gt.translation().truncate(),
+            Err(_) => continue,
+        };
+        let mut passives: Vec<_> = ua
+            .actions
+            .iter()
+            .flat_map(|entity| skill_q.get(*entity))
+            .filter_map(|skill| {
+                let base = &settings.get(&skill.action_id)?.base;
+                priority(base).map(|p| (p, skill.level, base))
+            })
+            .collect();
+        passives.sort_by_key(|(p, ..)| *p);
+
+        let damage = ev.damage;
+        // Enemies of the attacker in range, except the ones in `exclude`
+        let enemies_in = |pos: Vec2, radius: f32, exclude: Option<Entity>| {
+            let mut enemies = vec![];
+            rapier_context.intersections_with_shape(
+                pos,
+                0.0,
+                &Collider::ball(radius),
+                QueryFilter::default(),
+                |entity| {
+                    if Some(entity) != exclude {
+                        if let Ok(team) = team_q.get(entity) {
+                            if attacker_team.is_enemy(team) {
+                                enemies.push(entity);
+                            }
+                        }
+                    }
+                    true
+                },
+            );
+            enemies
+        };
+        for (_, level, base) in passives {
+            match base {
+                BaseSkill::Diffusion(d) => {
+                    let radius = d.radius.get(level) * RAPIER_SCALE;
+                    commands.spawn(VisualEffectMarker {
+                        visual_effect: VisualEffect::Diffusion,
+                        duration: Some(0.5),
+                        repeat: false,
+                        size: Vec2::new(radius * 2.0, radius * 2.0),
+                        auto_despawn: false,
+                        pos: target_pos,
+                    });
+                    for entity in enemies_in(target_pos, radius, Some(ev.target)) {
+                        damage_ev.send(DamageEvent {
+                            unit: entity,
+                            source_unit: Some(ev.attacker),
+                            damage: damage * d.percentage.get(level) / 100,
+                        });
+                    }
+                }
+                BaseSkill::FrostBall(fb) if rng.gen_ratio(fb.chance.min(100), 100) => {
+                    let radius = fb.radius.get(level) * RAPIER_SCALE;
+                    commands.spawn(VisualEffectMarker {
+                        visual_effect: VisualEffect::FrostBall,
+                        duration: Some(0.5),
+                        repeat: false,
+                        size: Vec2::new(radius * 2.0, radius * 2.0),
+                        auto_despawn: false,
+                        pos: target_pos,
+                    });
+                    for entity in enemies_in(target_pos, radius, None) {
+                        damage_ev.send(DamageEvent {
+                            unit: entity,
+                            source_unit: Some(ev.attacker),
+                            damage: fb.damage.get(level),
+                        });
+                        buff_ev.send(CreateBuff {
+                            unit: entity,
+                            buff_id: BuffId::Slow,
+                            buff: Buff {
+                                level: 1,
+                                duration: Some(FROST_BALL_SLOW),
+                            },
+                            source: Some(ev.attacker),
+                        });
+                    }
+                }
+                BaseSkill::SmashWave(sw) if rng.gen_ratio(sw.chance.min(100), 100) => {
+                    let radius = sw.radius.get(level) * RAPIER_SCALE;
+                    let pos = attacker_gt.translation().truncate();
+                    commands.spawn(VisualEffectMarker {
+                        visual_effect: VisualEffect::SmashWave,
+                        duration: Some(0.5),
+                        repeat: false,
+                        size: Vec2::new(radius * 2.0, radius * 2.0),
+                        auto_despawn: false,
+                        pos,
+                    });
+                    for entity in enemies_in(pos, radius, None) {
+                        damage_ev.send(DamageEvent {
+                            unit: entity,
+                            source_unit: Some(ev.attacker),
+                            damage: sw.damage.get(level),
+                        });
+                    }
+                }
+                BaseSkill::LifeSteal(ls) => {
+                    let heal = damage * ls.percentage.get(level) / 100;
+                    if heal > 0 {
+                        heal_ev.send(HealEvent {
+                            unit: ev.attacker,
+                            source_unit: Some(ev.attacker),
+                            heal,
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
@@ -1,9 +1,12 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
 use crate::plugins::{actions::skill_id::SkillId, unit_state::ActionState};
 
 use super::base::BaseSkill;
 
-#[derive(Debug, Clone)]
-pub struct SkillSetting<'a> {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillSetting {
     pub action_id: SkillId,
 
     pub startup_time: Option<f32>,
@@ -13,24 +16,60 @@ pub struct SkillSetting<'a> {
     pub cancelable: bool,
     pub target: TargetSetting,
     pub target_range: Option<u32>,
+    #[serde(default)]
+    pub usage: SkillUsage,
+    /// Buying the skill again raises its level up to this.
+    #[serde(default = "default_max_level")]
+    pub max_level: i32,
 
-    pub name: &'a str,
-    pub desc: &'a str,
-    pub icon: &'a str,
+    pub name: Cow<'static, str>,
+    pub desc: Cow<'static, str>,
+    pub icon: Cow<'static, str>,
     pub base: BaseSkill,
 
     pub skill_type: SkillType,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+fn default_max_level() -> i32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SkillType {
     Active,
     Passive,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TargetSetting {
     None,
     Unit,
     Position,
 }
+
+/// Cooldown, charges and cost, skills without any can be used at will.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkillUsage {
+    /// Seconds for one spent charge to come back.
+    pub cooldown: f32,
+    /// Uses before having to wait for the cooldown.
+    pub charges: u32,
+    pub cost: Option<SkillCost>,
+}
+
+impl Default for SkillUsage {
+    fn default() -> Self {
+        Self {
+            cooldown: 0.0,
+            charges: 1,
+            cost: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SkillCost {
+    Hp(i32),
+    Mp(i32),
+}
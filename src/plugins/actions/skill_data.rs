@@ -0,0 +1,379 @@
+use anyhow::{bail, ensure};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashMap},
+};
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+use crate::plugins::{save::paths::DataPaths, unit_state::ActionState};
+
+use super::{
+    base::{BaseSkill, Value},
+    setting::{SkillCost, SkillSetting, TargetSetting},
+    skill_id::SkillId,
+};
+
+const SKILL_DIR: &str = "data/skills";
+
+/// Skill settings from `assets/data/skills/*.skill.ron`, reloaded when a file changes.
+///
+/// Every skill needs a file. A file that fails to parse or validate is reported, and the skill
+/// keeps its last good setting if it had one.
+pub struct SkillDataPlugin;
+impl Plugin for SkillDataPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<SkillAsset>()
+            .init_asset_loader::<SkillAssetLoader>()
+            .init_resource::<SkillFiles>()
+            .init_resource::<SkillSettings>()
+            .add_startup_system(load_skill_files)
+            .add_system(apply_skill_assets);
+    }
+}
+
+/// The valid setting of every skill.
+#[derive(Debug, Default, Resource)]
+pub struct SkillSettings(HashMap<SkillId, SkillSetting>);
+
+impl SkillSettings {
+    pub fn get(&self, id: &SkillId) -> Option<&SkillSetting> {
+        self.0.get(id)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn insert(&mut self, setting: SkillSetting) {
+        self.0.insert(setting.action_id, setting);
+    }
+}
+
+#[derive(Debug, TypeUuid)]
+#[uuid = "5b0d8a4e-2f61-4c3b-9a57-0e6f3d1c8b92"]
+pub struct SkillAsset(pub SkillSetting);
+
+#[derive(Default)]
+struct SkillAssetLoader;
+impl AssetLoader for SkillAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let setting = ron::de::from_bytes::<SkillSetting>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(SkillAsset(setting)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["skill.ron"]
+    }
+}
+
+/// Keeps the folder loaded, and remembers which skill each file set for when it is removed.
+#[derive(Debug, Default, Resource)]
+struct SkillFiles {
+    handles: Vec<HandleUntyped>,
+    skills: HashMap<Handle<SkillAsset>, SkillId>,
+}
+
+/// Read the files right away so skills spawned in the first frames have their setting,
+/// the asset server only follows the changes.
+fn load_skill_files(
+    asset_server: Res<AssetServer>,
+    paths: Res<DataPaths>,
+    mut files: ResMut<SkillFiles>,
+    mut settings: ResMut<SkillSettings>,
+) {
+    let (loaded, errors) = read_skill_dir(&paths.assets.join(SKILL_DIR));
+    for e in errors {
+        error!("{e:?}");
+    }
+    settings.0 = loaded;
+    match asset_server.load_folder(SKILL_DIR) {
+        Ok(handles) => files.handles = handles,
+        Err(e) => error!("Failed to load skill data in {SKILL_DIR:?}: {e:?}"),
+    }
+}
+
+/// The valid settings in `dir`, and what is wrong with the others and the skills without one.
+fn read_skill_dir(dir: &Path) -> (HashMap<SkillId, SkillSetting>, Vec<anyhow::Error>) {
+    let mut settings = HashMap::default();
+    let mut errors = vec![];
+    match std::fs::read_dir(dir) {
+        Ok(entries) => {
+            for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+                if !path.to_string_lossy().ends_with(".skill.ron") {
+                    continue;
+                }
+                let setting = std::fs::read(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|bytes| Ok(ron::de::from_bytes::<SkillSetting>(&bytes)?))
+                    .and_then(|setting| validate(&setting).map(|_| setting));
+                match setting {
+                    Ok(x) => {
+                        if settings.contains_key(&x.action_id) {
+                            warn!(
+                                "{:?} is defined more than once, using {path:?}",
+                                x.action_id
+                            );
+                        }
+                        settings.insert(x.action_id, x);
+                    }
+                    Err(e) => errors.push(e.context(format!("Invalid skill data {path:?}"))),
+                }
+            }
+        }
+        Err(e) => errors.push(anyhow::Error::from(e).context(format!("Failed to read {dir:?}"))),
+    }
+    for id in SkillId::iter().filter(|id| !matches!(id, SkillId::Custom(_))) {
+        if !settings.contains_key(&id) {
+            errors.push(anyhow::anyhow!("No valid skill data for {id:?} in {dir:?}"));
+        }
+    }
+    (settings, errors)
+}
+
+fn apply_skill_assets(
+    mut events: EventReader<AssetEvent<SkillAsset>>,
+    assets: Res<Assets<SkillAsset>>,
+    asset_server: Res<AssetServer>,
+    mut files: ResMut<SkillFiles>,
+    mut settings: ResMut<SkillSettings>,
+) {
+    for ev in events.iter() {
+        match ev {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                let path = asset_server.get_handle_path(handle);
+                let path = path.as_ref().map(|p| p.path());
+                let setting = match assets.get(handle) {
+                    Some(SkillAsset(x)) => x,
+                    None => continue,
+                };
+                if let Err(e) = validate(setting) {
+                    error!("Invalid skill data {path:?}: {e:?}");
+                    continue;
+                }
+                let id = setting.action_id;
+                if let Some(old) = files.skills.insert(handle.clone_weak(), id) {
+                    if old != id {
+                        // The file was changed to describe another skill
+                        error!("Skill data of {old:?} moved to {id:?}, {old:?} has no setting");
+                        settings.0.remove(&old);
+                    }
+                }
+                if files.skills.iter().any(|(h, s)| *s == id && h != handle) {
+                    warn!("{id:?} is defined more than once, using {path:?}");
+                }
+                if let AssetEvent::Modified { .. } = ev {
+                    info!("Reloaded {id:?} from {path:?}");
+                }
+                settings.0.insert(id, setting.clone());
+            }
+            AssetEvent::Removed { handle } => {
+                if let Some(id) = files.skills.remove(handle) {
+                    error!("Skill data of {id:?} removed, {id:?} has no setting");
+                    settings.0.remove(&id);
+                }
+            }
+        }
+    }
+}
+
+fn validate(setting: &SkillSetting) -> anyhow::Result<()> {
+    for (name, time) in [
+        ("startup_time", setting.startup_time),
+        ("active_time", setting.active_time),
+        ("recover_time", setting.recover_time),
+    ] {
+        if let Some(time) = time {
+            ensure!(
+                time.is_finite() && time >= 0.0,
+                "{name} must not be negative, got {time}"
+            );
+        }
+    }
+    if setting.action_state == ActionState::Startup && setting.startup_time.is_none() {
+        bail!("action_state is Startup but there is no startup_time");
+    }
+    if setting.target == TargetSetting::None && setting.target_range.is_some() {
+        bail!("target_range is set but the skill has no target");
+    }
+    let usage = &setting.usage;
+    ensure!(
+        usage.cooldown.is_finite() && usage.cooldown >= 0.0,
+        "cooldown must not be negative, got {}",
+        usage.cooldown
+    );
+    ensure!(usage.charges > 0, "charges must be at least 1");
+    if let Some(SkillCost::Hp(x) | SkillCost::Mp(x)) = usage.cost {
+        ensure!(x >= 0, "cost must not be negative, got {x}");
+    }
+    ensure!(!setting.name.trim().is_empty(), "name is empty");
+    ensure!(!setting.icon.trim().is_empty(), "icon is empty");
+    ensure!(
+        setting.max_level >= 1,
+        "max_level must be at least 1, got {}",
+        setting.max_level
+    );
+    validate_base(&setting.base, setting.max_level)
+}
+
+/// Values only grow or shrink linearly, checking the first and last level covers the rest.
+fn validate_base(base: &BaseSkill, max_level: i32) -> anyhow::Result<()> {
+    let positive = |name: &str, v: &Value<f32>| {
+        for level in [1, max_level] {
+            positive_f32(name, v.get(level))?;
+        }
+        anyhow::Ok(())
+    };
+    let not_negative = |name: &str, v: &Value<i32>| {
+        for level in [1, max_level] {
+            let x = v.get(level);
+            ensure!(
+                x >= 0,
+                "{name} must not be negative, got {x} at level {level}"
+            );
+        }
+        anyhow::Ok(())
+    };
+    match base {
+        BaseSkill::BurstFire(x) => {
+            positive_f32("f", x.f)?;
+            not_negative("damage", &x.damage)
+        }
+        BaseSkill::GhostLight(x) => {
+            positive_f32("f", x.f)?;
+            not_negative("damage", &x.damage)
+        }
+        BaseSkill::Fireball(x) => {
+            positive("radius", &x.radius)?;
+            not_negative("damage", &x.damage)
+        }
+        BaseSkill::Explosion(x) => {
+            positive("radius", &x.radius)?;
+            not_negative("damage", &x.damage)
+        }
+        BaseSkill::Burning(x) => {
+            positive("radius", &x.radius)?;
+            positive("duration", &x.duration)?;
+            not_negative("damage", &x.damage)
+        }
+        BaseSkill::Drone(x) => {
+            positive("duration", &x.duration)?;
+            not_negative("damage", &x.damage)
+        }
+        BaseSkill::DeadFinger(x) => not_negative("damage", &x.damage),
+        BaseSkill::FireBreath(x) => {
+            positive("range", &x.range)?;
+            not_negative("damage", &x.damage)
+        }
+        BaseSkill::Thunder(x) => {
+            positive("duration", &x.duration)?;
+            positive("radius", &x.radius)?;
+            not_negative("damage", &x.damage)
+        }
+        BaseSkill::LifeDrain(x) => {
+            positive("duration", &x.duration)?;
+            not_negative("amount", &x.amount)
+        }
+        BaseSkill::LifeSteal(x) => not_negative("percentage", &x.percentage),
+        BaseSkill::CriticalHit(x) => not_negative("chance", &x.chance),
+        BaseSkill::Summon(x) => {
+            for level in [1, max_level] {
+                let max = x.max.get(level);
+                ensure!(
+                    max >= 1,
+                    "max must be at least 1, got {max} at level {level}"
+                );
+            }
+            positive("lifetime", &x.lifetime)?;
+            positive_f32("leash", x.leash)
+        }
+        BaseSkill::Diffusion(x) => {
+            positive("radius", &x.radius)?;
+            not_negative("percentage", &x.percentage)
+        }
+        BaseSkill::FrostBall(x) => {
+            chance(x.chance)?;
+            positive("radius", &x.radius)?;
+            not_negative("damage", &x.damage)
+        }
+        BaseSkill::SmashWave(x) => {
+            chance(x.chance)?;
+            positive("radius", &x.radius)?;
+            not_negative("damage", &x.damage)
+        }
+        BaseSkill::HealAura(x) => {
+            positive("radius", &x.radius)?;
+            not_negative("percentage", &x.percentage)
+        }
+        BaseSkill::AttackAura(x) => {
+            positive("radius", &x.radius)?;
+            not_negative("percentage", &x.percentage)
+        }
+        BaseSkill::SpeedAura(x) => {
+            positive("radius", &x.radius)?;
+            not_negative("percentage", &x.percentage)
+        }
+        BaseSkill::Ability(name) => {
+            ensure!(!name.trim().is_empty(), "ability name is empty");
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn positive_f32(name: &str, v: f32) -> anyhow::Result<()> {
+    ensure!(v.is_finite() && v > 0.0, "{name} must be positive, got {v}");
+    Ok(())
+}
+
+fn chance(v: u32) -> anyhow::Result<()> {
+    ensure!(v <= 100, "chance is a percentage, got {v}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_skill_has_valid_data() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join(SKILL_DIR);
+        let (settings, errors) = read_skill_dir(&dir);
+        assert!(errors.is_empty(), "{errors:?}");
+        for (id, setting) in settings.iter() {
+            assert_eq!(*id, setting.action_id);
+        }
+    }
+
+    #[test]
+    fn missing_skill_is_an_error() {
+        let dir =
+            std::env::temp_dir().join(format!("bevy_rpg_missing_skill_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let src = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join(SKILL_DIR)
+            .join("idle.skill.ron");
+        std::fs::copy(src, dir.join("idle.skill.ron")).unwrap();
+        std::fs::write(dir.join("stun.skill.ron"), "(action_id: Stun)").unwrap();
+
+        let (settings, errors) = read_skill_dir(&dir);
+        assert!(settings.contains_key(&SkillId::Idle));
+        let errors: Vec<_> = errors.iter().map(|e| format!("{e:?}")).collect();
+        assert!(errors.iter().any(|e| e.contains("stun.skill.ron")));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("No valid skill data for Stun")));
+        assert!(!errors.iter().any(|e| e.contains("for Idle ")));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -9,7 +9,10 @@ use crate::plugins::{
     unit_state::{ActionSystemLabel, UnitActionEnterActiveEvent, UnitActionEnterEvent},
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+};
 
 pub struct SlashPlugin;
 impl Plugin for SlashPlugin {
@@ -17,7 +20,7 @@ impl Plugin for SlashPlugin {
         app
             //
             .register_type::<SlashAction>()
-            .add_system(attach)
+            .add_system(attach::<SlashAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive));
     }
@@ -26,11 +29,13 @@ impl Plugin for SlashPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct SlashAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::Slash = setting.base {
-            commands.entity(e).insert(SlashAction {});
+impl SkillAction for SlashAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Slash => Some(SlashAction {}),
+            _ => None,
         }
     }
 }
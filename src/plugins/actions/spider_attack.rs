@@ -14,7 +14,10 @@ use crate::{
     RAPIER_SCALE,
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+};
 
 pub struct SpiderAttackPlugin;
 impl Plugin for SpiderAttackPlugin {
@@ -22,7 +25,7 @@ impl Plugin for SpiderAttackPlugin {
         app
             //
             .register_type::<SpiderAttackAction>()
-            .add_system(attach)
+            .add_system(attach::<SpiderAttackAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive));
     }
@@ -31,11 +34,13 @@ impl Plugin for SpiderAttackPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct SpiderAttackAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::SpiderAttack = setting.base {
-            commands.entity(e).insert(SpiderAttackAction {});
+impl SkillAction for SpiderAttackAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::SpiderAttack => Some(SpiderAttackAction {}),
+            _ => None,
         }
     }
 }
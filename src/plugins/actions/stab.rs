@@ -12,7 +12,10 @@ use crate::{
     utils::{self, Knockback, Shape},
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+};
 
 pub struct StabPlugin;
 impl Plugin for StabPlugin {
@@ -20,7 +23,7 @@ impl Plugin for StabPlugin {
         app
             //
             .register_type::<StabAction>()
-            .add_system(attach)
+            .add_system(attach::<StabAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive));
     }
@@ -29,11 +32,13 @@ impl Plugin for StabPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct StabAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::Stab = setting.base {
-            commands.entity(e).insert(StabAction {});
+impl SkillAction for StabAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Stab => Some(StabAction {}),
+            _ => None,
         }
     }
 }
@@ -7,7 +7,10 @@ use crate::plugins::{
     unit_state::{ActionSystemLabel, UnitActionEnterActiveEvent, UnitActionEnterEvent},
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+};
 
 pub struct StopPlugin;
 impl Plugin for StopPlugin {
@@ -15,7 +18,7 @@ impl Plugin for StopPlugin {
         app
             //
             .register_type::<StopAction>()
-            .add_system(attach)
+            .add_system(attach::<StopAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive));
     }
@@ -24,11 +27,13 @@ impl Plugin for StopPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct StopAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::Stop = setting.base {
-            commands.entity(e).insert(StopAction {});
+impl SkillAction for StopAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Stop => Some(StopAction {}),
+            _ => None,
         }
     }
 }
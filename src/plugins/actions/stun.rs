@@ -7,7 +7,10 @@ use crate::plugins::{
     unit_state::{ActionSystemLabel, UnitActionEnterEvent},
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+};
 
 pub struct StunPlugin;
 impl Plugin for StunPlugin {
@@ -15,7 +18,7 @@ impl Plugin for StunPlugin {
         app
             //
             .register_type::<StunAction>()
-            .add_system(attach)
+            .add_system(attach::<StunAction>)
             .add_system(enter.label(ActionSystemLabel::Enter));
     }
 }
@@ -23,11 +26,13 @@ impl Plugin for StunPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct StunAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::Stun = setting.base {
-            commands.entity(e).insert(StunAction {});
+impl SkillAction for StunAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Stun => Some(StunAction {}),
+            _ => None,
         }
     }
 }
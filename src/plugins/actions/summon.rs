@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+
+use crate::plugins::{
+    animation::ChangeAnimation,
+    movement::Movement,
+    summon::SummonEvent,
+    unit_action::UnitAnimation,
+    unit_state::{ActionSystemLabel, UnitActionEnterActiveEvent, UnitActionEnterEvent},
+};
+
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+};
+
+pub struct SummonSkillPlugin;
+impl Plugin for SummonSkillPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //
+            .register_type::<SummonAction>()
+            .add_system(attach::<SummonAction>)
+            .add_system(enter.label(ActionSystemLabel::Enter))
+            .add_system(enter_active.label(ActionSystemLabel::EnterActive));
+    }
+}
+
+#[derive(Debug, Default, Component, Reflect)]
+pub struct SummonAction {}
+
+impl SkillAction for SummonAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Summon(_) => Some(SummonAction {}),
+            _ => None,
+        }
+    }
+}
+
+fn enter(
+    mut events: EventReader<UnitActionEnterEvent>,
+    query: Query<(&SummonAction,)>,
+    mut unit_q: Query<(&mut Movement,)>,
+    mut anim_events: EventWriter<ChangeAnimation>,
+) {
+    for ev in events.iter() {
+        if let Ok((_,)) = query.get(ev.action) {
+            if let Ok((mut movement,)) = unit_q.get_mut(ev.unit) {
+                movement.speed = 0.0;
+                anim_events.send(ChangeAnimation {
+                    entity: ev.unit,
+                    name: UnitAnimation::Idle.to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn enter_active(
+    mut events: EventReader<UnitActionEnterActiveEvent>,
+    query: Query<(&SummonAction, &Skill)>,
+    mut summon_events: EventWriter<SummonEvent>,
+    settings: Res<SkillSettings>,
+) {
+    for ev in events.iter() {
+        if let Ok((_, skill)) = query.get(ev.action) {
+            debug!("Summon enter active: {:?}", ev);
+            if let Some(BaseSkill::Summon(base)) = settings.get(&skill.action_id).map(|s| &s.base) {
+                summon_events.send(SummonEvent {
+                    owner: ev.unit,
+                    unit: base.unit,
+                    max: base.max.get(skill.level),
+                    lifetime: base.lifetime.get(skill.level),
+                    leash: base.leash,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    plugins::{
+        animation::ChangeAnimation,
+        damage::DamageEvent,
+        movement::Movement,
+        team::Team,
+        unit::Unit,
+        unit_action::UnitAnimation,
+        unit_state::{
+            ActionSystemLabel, UnitActionChannelTickEvent, UnitActionEnterEvent, UnitState,
+        },
+        visual_effect::{VisualEffect, VisualEffectMarker},
+    },
+    RAPIER_SCALE,
+};
+
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+    skill_data::SkillSettings,
+};
+
+pub struct ThunderPlugin;
+impl Plugin for ThunderPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //
+            .register_type::<ThunderAction>()
+            .add_system(attach::<ThunderAction>)
+            .add_system(enter.label(ActionSystemLabel::Enter))
+            .add_system(channel_tick.label(ActionSystemLabel::ActiveUpdate));
+    }
+}
+
+#[derive(Debug, Default, Component, Reflect)]
+pub struct ThunderAction {}
+
+impl SkillAction for ThunderAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::Thunder(_) => Some(ThunderAction {}),
+            _ => None,
+        }
+    }
+}
+
+fn enter(
+    mut events: EventReader<UnitActionEnterEvent>,
+    query: Query<(&ThunderAction,)>,
+    mut unit_q: Query<(&mut Movement,)>,
+    mut anim_events: EventWriter<ChangeAnimation>,
+) {
+    for ev in events.iter() {
+        if let Ok((_,)) = query.get(ev.action) {
+            if let Ok((mut movement,)) = unit_q.get_mut(ev.unit) {
+                movement.speed = 0.0;
+                anim_events.send(ChangeAnimation {
+                    entity: ev.unit,
+                    name: UnitAnimation::Idle.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Strike every enemy around the target position.
+fn channel_tick(
+    mut events: EventReader<UnitActionChannelTickEvent>,
+    query: Query<(&ThunderAction, &Skill)>,
+    owner_q: Query<(&UnitState, &Team)>,
+    unit_q: Query<(&Unit, &Team, &GlobalTransform)>,
+    rapier_context: Res<RapierContext>,
+    mut damage_ev: EventWriter<DamageEvent>,
+    mut commands: Commands,
+    settings: Res<SkillSettings>,
+) {
+    for ev in events.iter() {
+        if let Ok((_, skill)) = query.get(ev.action) {
+            let setting = match settings.get(&skill.action_id) {
+                Some(x) => x,
+                None => continue,
+            };
+            if let BaseSkill::Thunder(base) = &setting.base {
+                if let Ok((us, team)) = owner_q.get(ev.unit) {
+                    let pos = match us.command.as_ref().and_then(|c| c.target_position) {
+                        Some(x) => x,
+                        None => continue,
+                    };
+                    let radius = base.radius.get(skill.level);
+                    let shape = Collider::ball(radius * RAPIER_SCALE);
+                    rapier_context.intersections_with_shape(
+                        pos,
+                        0.0,
+                        &shape,
+                        QueryFilter::default(),
+                        |entity| {
+                            if let Ok((unit, t, gt)) = unit_q.get(entity) {
+                                if team.is_enemy(t) && !unit.dead {
+                                    damage_ev.send(DamageEvent {
+                                        unit: entity,
+                                        source_unit: Some(ev.unit),
+                                        damage: base.damage.get(skill.level),
+                                    });
+                                    commands.spawn(VisualEffectMarker {
+                                        visual_effect: VisualEffect::DeadFinger,
+                                        duration: Some(0.3),
+                                        repeat: false,
+                                        size: Vec2 { x: 40.0, y: 40.0 },
+                                        auto_despawn: false,
+                                        pos: gt.translation().truncate(),
+                                    });
+                                }
+                            }
+                            true
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
@@ -14,7 +14,10 @@ use crate::{
     utils::{self, Knockback, Shape},
 };
 
-use super::{action::Skill, base::BaseSkill};
+use super::{
+    action::{attach, Skill, SkillAction},
+    base::BaseSkill,
+};
 
 pub struct WolfAttackPlugin;
 impl Plugin for WolfAttackPlugin {
@@ -22,7 +25,7 @@ impl Plugin for WolfAttackPlugin {
         app
             //
             .register_type::<WolfAttackAction>()
-            .add_system(attach)
+            .add_system(attach::<WolfAttackAction>)
             .add_system(enter.label(ActionSystemLabel::Enter))
             .add_system(enter_active.label(ActionSystemLabel::EnterActive));
     }
@@ -31,11 +34,13 @@ impl Plugin for WolfAttackPlugin {
 #[derive(Debug, Default, Component, Reflect)]
 pub struct WolfAttackAction {}
 
-fn attach(mut commands: Commands, q: Query<(Entity, &Skill), Added<Skill>>) {
-    for (e, a) in q.iter() {
-        let setting = a.action_id.setting();
-        if let BaseSkill::WolfAttack = setting.base {
-            commands.entity(e).insert(WolfAttackAction {});
+impl SkillAction for WolfAttackAction {
+    type Bundle = Self;
+
+    fn attach(base: &BaseSkill, _: &Skill) -> Option<Self> {
+        match base {
+            BaseSkill::WolfAttack => Some(WolfAttackAction {}),
+            _ => None,
         }
     }
 }
@@ -61,7 +61,7 @@ fn attach(mut commands: Commands, query: Query<(Entity, &Area), Changed<Area>>)
     }
 }
 
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Clone, PartialEq, Reflect)]
 pub struct PlayerEnterEvent(pub Entity);
 
 impl Default for PlayerEnterEvent {
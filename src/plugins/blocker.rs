@@ -5,7 +5,7 @@ use std::{collections::HashMap, time::Duration};
 
 use super::{
     animation::{AnimationData, AnimationSheet, AnimationState},
-    save::SaveBlocker,
+    save::saveable::SaveComponent,
     unit_action::UnitAnimation,
 };
 
@@ -76,7 +76,7 @@ pub fn spawn_blocker(
         })
         .insert(Name::new("Blocker"))
         // Save
-        .insert(SaveBlocker)
+        .insert(SaveComponent::<Blocker>::default())
         // Rapier
         // .insert(RigidBody::Fixed)
         // .insert(Collider::cuboid(hx, hy))
@@ -9,7 +9,7 @@ use super::{
     animation::{AnimationData, AnimationSheet, AnimationState, ChangeAnimation},
     item::{Inventory, ItemId},
     player::Hero,
-    save::SaveChest,
+    save::saveable::SaveComponent,
     unit_action::UnitAnimation,
 };
 
@@ -27,6 +27,7 @@ impl Plugin for ChestPlugin {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Component, Reflect, Default)]
+#[serde(default)]
 pub struct Chest {
     pub opened: bool,
     pub item_id: ItemId,
@@ -97,7 +98,7 @@ pub fn spawn_chest(
         // Interact
         .insert(super::interaction::Interaction::Chest)
         // Save
-        .insert(SaveChest)
+        .insert(SaveComponent::<Chest>::default())
         // Rapier
         .insert(RigidBody::Fixed)
         .insert(Collider::cuboid(32.0, 32.0))
@@ -4,8 +4,9 @@ use crate::utils::Knockback;
 
 use super::{
     actions::skill_id::SkillId,
+    buff::Buffs,
     hit::HitEvent,
-    unit::{Unit, UnitDieEvent},
+    unit::{Unit, UnitDieEvent, UnitKilledEvent},
     unit_state::{ChangeActionRequest, UnitState},
 };
 
@@ -72,16 +73,25 @@ pub struct DamageEvent {
 }
 fn damage_event(
     mut events: EventReader<DamageEvent>,
-    mut query: Query<&mut Unit>,
+    mut query: Query<(&mut Unit, Option<&mut Buffs>)>,
     mut die_events: EventWriter<UnitDieEvent>,
+    mut killed_events: EventWriter<UnitKilledEvent>,
     mut change_events: EventWriter<ChangeActionRequest>,
 ) {
     for ev in events.iter() {
-        if let Ok(mut unit) = query.get_mut(ev.unit) {
-            unit.hp -= ev.damage;
+        if let Ok((mut unit, buffs)) = query.get_mut(ev.unit) {
+            let mut damage = unit.damage_taken(ev.damage);
+            if let Some(mut buffs) = buffs {
+                damage = buffs.absorb(damage);
+            }
+            unit.hp -= damage;
             if unit.hp <= 0 && !unit.dead {
                 unit.dead = true;
                 die_events.send(UnitDieEvent(ev.unit));
+                killed_events.send(UnitKilledEvent {
+                    unit: ev.unit,
+                    killer: ev.source_unit,
+                });
                 change_events.send(ChangeActionRequest {
                     action_id: SkillId::Dead,
                     command: default(),
@@ -101,12 +111,10 @@ pub struct HealEvent {
 fn heal_event(mut events: EventReader<HealEvent>, mut query: Query<(&mut Unit, &UnitState)>) {
     for ev in events.iter() {
         if let Ok((mut unit, us)) = query.get_mut(ev.unit) {
-            if !unit.dead && us.action_id != SkillId::Dead {
-                if unit.hp < unit.hp_max {
-                    unit.hp += ev.heal;
-                    if unit.hp > unit.hp_max {
-                        unit.hp = unit.hp_max;
-                    }
+            if !unit.dead && us.action_id != SkillId::Dead && unit.hp < unit.hp_max {
+                unit.hp += ev.heal;
+                if unit.hp > unit.hp_max {
+                    unit.hp = unit.hp_max;
                 }
             }
         }
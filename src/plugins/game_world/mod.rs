@@ -1,24 +1,19 @@
 use bevy::{prelude::*, time::Stopwatch};
-use bevy_rapier2d::prelude::*;
+use bevy_ecs_ldtk::prelude::{LdtkAsset, LevelSet};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 use strum::{Display, EnumIter};
 
 use self::world_cache::{WorldCache, WorldCachePlugin};
 
 use super::{
-    animation::AnimationState,
-    area::{Area, PlayerEnterEvent},
-    blocker::Blocker,
-    chest::Chest,
-    item::{Equipment, Inventory},
     player::Hero,
-    save::{ClearOnReset, ClearSave, SaveBuffer, WriteSaveFile},
+    save::{saveable::Saveables, ClearSave, SaveBuffer, WriteSaveFile},
     scene_editor::scene_loader::SceneRes,
     spatial_map::{CHUNK_SIZE, TILE_SIZE},
-    tiled_asset::TiledAsset,
-    trigger::EventTrigger,
-    unit::{Unit, UnitDieEvent},
 };
 
 mod world_cache;
@@ -33,9 +28,13 @@ impl Plugin for GameWorldPlugin {
             .register_type::<GameObjectId>()
             .register_type::<GameObjectType>()
             .add_system(follow_player)
+            .init_resource::<StreamingConfig>()
             .add_event::<LoadPosition>()
             .add_system(load_position)
+            .add_system(load_levels.after(load_position))
             .add_system(load_chunk)
+            // Filled by the LDtk entities, see `process_my_entity`
+            .init_resource::<SceneRes>()
             .add_event::<LoadObject>()
             .add_system(load_object.after(unload_object))
             .init_resource::<Loaded>()
@@ -52,6 +51,8 @@ impl Plugin for GameWorldPlugin {
 
 /// Tile in map
 pub const MAP_SIZE: i32 = 100;
+/// LDtk level spawned before the hero exists, the hero spawns in it.
+pub const START_LEVEL: &str = "Level_27";
 
 #[derive(Debug, Clone, Copy, Component, Reflect)]
 pub struct WorldChunkRoot;
@@ -59,7 +60,12 @@ pub struct WorldChunkRoot;
 ///
 /// y is following bevy transform: 1 is up, -1 is down.
 /// Use -y when working with tiled.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Component, Reflect)]
+///
+/// Saved as a `(x, y)` tuple, the key of `SpatialMap`.
+#[derive(
+    Debug, Default, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize, Component, Reflect,
+)]
+#[serde(from = "(i32, i32)", into = "(i32, i32)")]
 pub struct WorldChunk {
     pub x: i32,
     pub y: i32,
@@ -69,15 +75,15 @@ impl WorldChunk {
     ///
     /// y: 1 is up, -1 is down.
     pub fn new(x: f32, y: f32) -> WorldChunk {
-        let mut cx = x as i32 / TILE_SIZE / CHUNK_SIZE;
-        if x < 0.0 {
-            cx -= 1;
-        }
-        let mut cy = y as i32 / TILE_SIZE / CHUNK_SIZE;
-        if y < 0.0 {
-            cy -= 1;
+        WorldTile::from_position(x, y).chunk()
+    }
+
+    /// Chunk containing the tile, rounded down for negative tiles too.
+    pub fn from_tile(tile: WorldTile) -> WorldChunk {
+        WorldChunk {
+            x: tile.x.div_euclid(CHUNK_SIZE),
+            y: tile.y.div_euclid(CHUNK_SIZE),
         }
-        WorldChunk { x: cx, y: cy }
     }
 
     fn x_range(&self) -> (i32, i32) {
@@ -104,6 +110,34 @@ impl WorldChunk {
     pub fn asset_load_path(&self) -> HashSet<String> {
         HashSet::from_iter(self.tiles().iter().map(|wt| wt.asset_load_path()))
     }
+
+    /// Chunks between the two on the longer axis, 0 for the same chunk.
+    pub fn distance(&self, other: &WorldChunk) -> i32 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    /// Whether the rect from `min` to `max` in world space overlaps
+    /// the chunks within `radius` of this one.
+    pub fn overlaps(&self, radius: i32, min: Vec2, max: Vec2) -> bool {
+        let size = (TILE_SIZE * CHUNK_SIZE) as f32;
+        let x1 = (self.x - radius) as f32 * size;
+        let x2 = (self.x + radius + 1) as f32 * size;
+        let y1 = (self.y - radius) as f32 * size;
+        let y2 = (self.y + radius + 1) as f32 * size;
+        min.x < x2 && max.x > x1 && min.y < y2 && max.y > y1
+    }
+}
+
+impl From<(i32, i32)> for WorldChunk {
+    fn from((x, y): (i32, i32)) -> Self {
+        WorldChunk { x, y }
+    }
+}
+
+impl From<WorldChunk> for (i32, i32) {
+    fn from(chunk: WorldChunk) -> Self {
+        (chunk.x, chunk.y)
+    }
 }
 
 /// y is following bevy transform: 1 is up, -1 is down.
@@ -117,33 +151,21 @@ impl WorldTile {
         Self { x, y }
     }
 
-    fn transform(&self) -> Transform {
-        Transform::from_xyz(
-            ((TILE_SIZE / 2) + (self.x * TILE_SIZE)) as f32,
-            ((TILE_SIZE / 2) + (self.y * TILE_SIZE)) as f32,
-            0.0,
-        )
+    /// Tile containing the position, rounded down for negative positions too.
+    pub fn from_position(x: f32, y: f32) -> Self {
+        Self {
+            x: (x / TILE_SIZE as f32).floor() as i32,
+            y: (y / TILE_SIZE as f32).floor() as i32,
+        }
     }
 
-    /// Index in file, range: `0..MAP_SIZE`
-    fn tiled_index(&self) -> (i32, i32) {
-        (
-            self.x.rem_euclid(MAP_SIZE),
-            (-self.y - 1).rem_euclid(MAP_SIZE),
-        )
+    pub fn chunk(&self) -> WorldChunk {
+        WorldChunk::from_tile(*self)
     }
 
     fn tiled_file_index(&self) -> (i32, i32) {
         let (xi, yi) = (self.x, -self.y - 1);
-        let mut x = xi / MAP_SIZE;
-        if xi < 0 {
-            x -= 1;
-        }
-        let mut y = yi / MAP_SIZE;
-        if yi < 0 {
-            y -= 1;
-        }
-        (x, y)
+        (xi.div_euclid(MAP_SIZE), yi.div_euclid(MAP_SIZE))
     }
     fn tiled_file_name(&self) -> String {
         let (x, y) = self.tiled_file_index();
@@ -161,9 +183,22 @@ impl WorldTile {
 pub struct GameObjectId(pub String);
 
 #[derive(
-    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Component, Reflect, EnumIter, Display,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Component,
+    Reflect,
+    EnumIter,
+    Display,
+    Default,
 )]
 pub enum GameObjectType {
+    #[default]
     None,
     Unknown,
     Player,
@@ -178,12 +213,6 @@ pub enum GameObjectType {
     Rock,
 }
 
-impl Default for GameObjectType {
-    fn default() -> Self {
-        GameObjectType::None
-    }
-}
-
 impl From<&str> for GameObjectType {
     fn from(s: &str) -> Self {
         match s {
@@ -196,6 +225,7 @@ impl From<&str> for GameObjectType {
             "Blocker" => GameObjectType::Blocker,
             "Area" => GameObjectType::Area,
             "Chest" => GameObjectType::Chest,
+            "Rock" => GameObjectType::Rock,
             "" => {
                 warn!("Empty obj_type!");
                 GameObjectType::None
@@ -213,25 +243,89 @@ pub struct GameWorld {
     pub current_chunk: Option<WorldChunk>,
     pub loading_chunks: HashSet<WorldChunk>,
     pub loaded_chunks: HashSet<WorldChunk>,
+    /// Time each object has been outside its unload radius.
+    pub out_of_range: HashMap<Entity, Stopwatch>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone, Reflect)]
-#[reflect_value()]
-pub struct Ecs {
-    pub objects: HashMap<GameObjectId, GameObjectType>,
-    pub units: HashMap<GameObjectId, Unit>,
-    pub transforms: HashMap<GameObjectId, (Vec3, Quat, Vec3)>,
-    pub resets: HashMap<GameObjectId, ClearOnReset>,
-    pub blockers: HashMap<GameObjectId, Blocker>,
-    pub areas: HashMap<GameObjectId, Area>,
-    pub inventorys: HashMap<GameObjectId, Inventory>,
-    pub equipments: HashMap<GameObjectId, Equipment>,
-    pub chests: HashMap<GameObjectId, Chest>,
-    pub enter_triggers: HashMap<GameObjectId, EventTrigger<PlayerEnterEvent>>,
-    pub die_triggers: HashMap<GameObjectId, EventTrigger<UnitDieEvent>>,
-    pub collision_groupss: HashMap<GameObjectId, (u32, u32)>,
-    pub animation_states: HashMap<GameObjectId, AnimationState>,
+impl GameWorld {
+    /// Tick the time of the objects `outside` their unload radius, returns those outside
+    /// for at least `grace_period` seconds.
+    ///
+    /// Objects that are not outside anymore, back in range or gone, start over.
+    pub fn tick_out_of_range(
+        &mut self,
+        outside: impl IntoIterator<Item = Entity>,
+        delta: Duration,
+        grace_period: f32,
+    ) -> Vec<Entity> {
+        let mut out_of_range = HashMap::new();
+        let mut expired = vec![];
+        for entity in outside {
+            let mut stopwatch = self.out_of_range.remove(&entity).unwrap_or_default();
+            stopwatch.tick(delta);
+            if stopwatch.elapsed_secs() >= grace_period {
+                expired.push(entity);
+            } else {
+                out_of_range.insert(entity, stopwatch);
+            }
+        }
+        self.out_of_range = out_of_range;
+        expired
+    }
 }
+
+/// How far around the hero the world is loaded, in chunks.
+#[derive(Debug, Clone, Resource)]
+pub struct StreamingConfig {
+    /// Chunks and levels within this radius are loaded.
+    pub load_radius: i32,
+    /// Chunks, levels and objects are unloaded outside this radius,
+    /// keep it larger than `load_radius`.
+    pub unload_radius: i32,
+    /// Unload radius for some object types, e.g. to keep blockers and bosses loaded longer.
+    pub unload_radius_overrides: HashMap<GameObjectType, i32>,
+    /// Seconds an object stays loaded after leaving its unload radius.
+    pub grace_period: f32,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            load_radius: 2,
+            unload_radius: 3,
+            unload_radius_overrides: HashMap::from([
+                (GameObjectType::Blocker, 5),
+                (GameObjectType::Area, 5),
+                // A boss that chased the hero out of its arena is not reset by a short retreat
+                (GameObjectType::Fox, 5),
+            ]),
+            grace_period: 3.0,
+        }
+    }
+}
+
+impl StreamingConfig {
+    /// Radius a chunk or level is loaded within, depending on whether it is loaded now.
+    ///
+    /// Chunks are unloaded further away than they are loaded,
+    /// so walking along a chunk edge does not load and unload them every step.
+    pub fn radius(&self, loaded: bool) -> i32 {
+        if loaded {
+            self.unload_radius.max(self.load_radius)
+        } else {
+            self.load_radius
+        }
+    }
+
+    pub fn unload_radius_of(&self, object_type: Option<&GameObjectType>) -> i32 {
+        object_type
+            .and_then(|t| self.unload_radius_overrides.get(t))
+            .copied()
+            .unwrap_or(self.unload_radius)
+            .max(self.load_radius)
+    }
+}
+
 #[derive(Debug)]
 pub struct LoadObject(pub GameObjectId);
 
@@ -240,9 +334,13 @@ pub struct Loaded {
     pub objects: HashSet<GameObjectId>,
 }
 
-fn follow_player(player_q: Query<&Transform, With<Hero>>, mut events: EventWriter<LoadPosition>) {
+fn follow_player(
+    player_q: Query<&GlobalTransform, With<Hero>>,
+    mut events: EventWriter<LoadPosition>,
+) {
+    // The hero is a child of its LDtk entity, only the global position is in world space
     if let Ok(position) = player_q.get_single() {
-        let t = position.translation;
+        let t = position.translation();
         let x = t.x;
         let y = t.y;
         events.send(LoadPosition(x, y));
@@ -255,84 +353,115 @@ fn load_position(
     mut events: EventReader<LoadPosition>,
     mut commands: Commands,
     mut game_world: ResMut<GameWorld>,
-    mut cache: ResMut<WorldCache>,
+    config: Res<StreamingConfig>,
+    time: Res<Time>,
     chunk_q: Query<(Entity, &WorldChunk)>,
-    transform_query: Query<(Entity, &Transform), With<GameObjectId>>,
+    transform_query: Query<(Entity, &Transform, Option<&GameObjectType>), With<GameObjectId>>,
     mut unload_events: EventWriter<UnloadObject>,
-    // mut load_map_events: EventWriter<LoadMap>,
 ) {
-    for ev in events.iter() {
-        // info!("{ev:?}");
-        let LoadPosition(x, y) = *ev;
-
-        // Chunk index
-        let chunk = WorldChunk::new(x, y);
-        if game_world.current_chunk != Some(chunk) {
-            info!("LoadPosition, {ev:?}, chunk: {chunk:?}");
-            game_world.current_chunk = Some(chunk);
-        }
+    // Only the latest position matters, the grace period is ticked once per frame
+    let ev = match events.iter().last() {
+        Some(x) => *x,
+        None => return,
+    };
+    let LoadPosition(x, y) = ev;
 
-        // define unload range
-        let x1 = chunk.x - 2;
-        let x2 = chunk.x + 2;
-        let y1 = chunk.y - 2;
-        let y2 = chunk.y + 2;
-        // remove out of range chunk from loading
-        game_world
-            .loading_chunks
-            .retain(|c| x1 <= c.x && c.x <= x2 && y1 <= c.y && c.y <= y2);
-        // remove loaded chunk
-        game_world
-            .loaded_chunks
-            .retain(|c| x1 <= c.x && c.x <= x2 && y1 <= c.y && c.y <= y2);
-        // Unload chunk
-        for (entity, _chunk) in chunk_q
-            .iter()
-            .filter(|(_, c)| !(x1 <= c.x && c.x <= x2 && y1 <= c.y && c.y <= y2))
-        {
-            // info!("Unload chunk: {:?}", chunk);
-            commands.entity(entity).despawn_recursive();
-        }
+    // Chunk index
+    let chunk = WorldChunk::new(x, y);
+    if game_world.current_chunk != Some(chunk) {
+        info!("LoadPosition, {ev:?}, chunk: {chunk:?}");
+        game_world.current_chunk = Some(chunk);
+    }
 
-        // unload object out of range
-        let px1 = (x1 * TILE_SIZE * CHUNK_SIZE) as f32;
-        let px2 = ((x2 + 1) * TILE_SIZE * CHUNK_SIZE) as f32;
-        let py1 = (y1 * TILE_SIZE * CHUNK_SIZE) as f32;
-        let py2 = ((y2 + 1) * TILE_SIZE * CHUNK_SIZE) as f32;
-        let events = transform_query
-            .iter()
-            .filter(|(_, t)| {
-                let x = t.translation.x;
-                let y = t.translation.y;
-                x < px1 || x > px2 || y < py1 || y > py2
-            })
-            .map(|(e, _)| UnloadObject(e))
-            .collect::<Vec<_>>();
-        if !events.is_empty() {
-            info!("Unload: {:?}", events);
-            info!("x: {x}, y: {y}, py1: {py1}, py2: {py2}, y1: {y1}, y2: {y2}");
-            unload_events.send_batch(events.into_iter());
+    let unload_radius = config.radius(true);
+    // remove out of range chunk from loading
+    game_world
+        .loading_chunks
+        .retain(|c| c.distance(&chunk) <= unload_radius);
+    // remove loaded chunk
+    game_world
+        .loaded_chunks
+        .retain(|c| c.distance(&chunk) <= unload_radius);
+    // Unload chunk
+    for (entity, _chunk) in chunk_q
+        .iter()
+        .filter(|(_, c)| c.distance(&chunk) > unload_radius)
+    {
+        // info!("Unload chunk: {:?}", chunk);
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // unload object out of range for longer than the grace period
+    let outside = transform_query
+        .iter()
+        .filter(|(_, t, object_type)| {
+            let object_chunk = WorldChunk::new(t.translation.x, t.translation.y);
+            object_chunk.distance(&chunk) > config.unload_radius_of(*object_type)
+        })
+        .map(|(entity, _, _)| entity);
+    let events = game_world
+        .tick_out_of_range(outside, time.delta(), config.grace_period)
+        .into_iter()
+        .map(UnloadObject)
+        .collect::<Vec<_>>();
+    if !events.is_empty() {
+        info!("Unload: {:?}, chunk: {chunk:?}", events);
+        unload_events.send_batch(events);
+    }
+
+    // Load new chunk
+    let r = config.radius(false);
+    for x in chunk.x - r..=chunk.x + r {
+        for y in chunk.y - r..=chunk.y + r {
+            let chunk = WorldChunk { x, y };
+            if !game_world.loaded_chunks.contains(&chunk) {
+                // info!("Load new chunk: {chunk:?}");
+                game_world.loading_chunks.insert(chunk);
+                // Levels are drawn by LDtk, see `load_levels`
+            }
         }
+    }
+}
 
-        // Load range
-        // let x1 = chunk.x - 1;
-        // let x2 = chunk.x + 1;
-        // let y1 = chunk.y - 1;
-        // let y2 = chunk.y + 1;
-        // Load new chunk
-        for x in x1..=x2 {
-            for y in y1..=y2 {
-                let chunk = WorldChunk { x, y };
-                if !game_world.loaded_chunks.contains(&chunk) {
-                    // info!("Load new chunk: {chunk:?}");
-                    game_world.loading_chunks.insert(chunk);
-                    // Cache
-                    cache.load(chunk);
-                    // Draw tile map
-                    todo!();
-                    // load_map_events.send_batch(chunk.asset_load_path().into_iter().map(LoadMap));
-                }
+/// Spawn the LDtk levels overlapping the loaded chunks and despawn the others.
+///
+/// Objects in a despawned level are unloaded by `load_position` and are kept in `SaveBuffer`.
+fn load_levels(
+    game_world: Res<GameWorld>,
+    config: Res<StreamingConfig>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    mut world_q: Query<(&Handle<LdtkAsset>, &mut LevelSet)>,
+) {
+    for (handle, mut level_set) in world_q.iter_mut() {
+        let ldtk = match ldtk_assets.get(handle) {
+            Some(x) => x,
+            None => continue,
+        };
+        let levels = ldtk.project.levels.iter();
+        let iids = match game_world.current_chunk {
+            Some(chunk) => {
+                levels
+                    .filter(|level| {
+                        // LDtk y is down, levels are placed with their bottom left corner
+                        let min =
+                            Vec2::new(level.world_x as f32, (-level.world_y - level.px_hei) as f32);
+                        let max =
+                            Vec2::new((level.world_x + level.px_wid) as f32, -level.world_y as f32);
+                        let loaded = level_set.iids.contains(&level.iid);
+                        chunk.overlaps(config.radius(loaded), min, max)
+                    })
+                    .map(|level| level.iid.clone())
+                    .collect::<HashSet<_>>()
             }
+            // No hero to follow yet
+            None => levels
+                .filter(|level| level.identifier == START_LEVEL)
+                .map(|level| level.iid.clone())
+                .collect(),
+        };
+        if level_set.iids != iids {
+            info!("Load levels: {iids:?}");
+            level_set.iids = iids;
         }
     }
 }
@@ -341,7 +470,6 @@ fn load_chunk(
     mut commands: Commands,
     mut game_world: ResMut<GameWorld>,
     cache: Res<WorldCache>,
-    tileds: Res<Assets<TiledAsset>>,
     mut events: EventWriter<LoadObject>,
     chunks_q: Query<Entity, With<WorldChunkRoot>>,
     save: Res<SaveBuffer>,
@@ -356,15 +484,14 @@ fn load_chunk(
                 .insert(WorldChunkRoot)
                 .insert(Name::new("WorldChunkRoot"))
                 .id(),
-            bevy::ecs::query::QuerySingleError::MultipleEntities(_) => todo!(),
+            bevy::ecs::query::QuerySingleError::MultipleEntities(_) => {
+                error!("Multiple WorldChunkRoot, chunks are not loaded");
+                return;
+            }
         },
     };
-    // check res and get tiled file asset
+    // Tiles and walls are drawn by LDtk, chunks only hold game objects
     for &chunk in game_world.loading_chunks.iter() {
-        if !cache.ready(&chunk) {
-            continue;
-        }
-        // info!("Ready to load: {chunk:?}");
         loaded.push(chunk);
 
         let chunk_entity = commands
@@ -376,55 +503,8 @@ fn load_chunk(
             .insert(Name::new(format!("{chunk:?}")))
             .id();
         commands.entity(chunk_root).add_child(chunk_entity);
-        // Tile Collider
-        trace!("Start creating tile collider for chunk: {chunk:?}");
-        for world_tile in chunk.tiles() {
-            // info!("Create collider for tile: {world_tile:?}");
-            if let Some(handle) = cache
-                .tiled_handles
-                .get(&world_tile.asset_load_path())
-                .expect("file should be loaded")
-            {
-                // info!("asset_load_path: {:?}", world_tile.asset_load_path());
-                // info!("handle: {handle:?}");
-                let tiled = tileds.get(handle).unwrap();
-                let (x, y) = world_tile.tiled_index();
-                for layer in tiled.map.layers() {
-                    match layer.layer_type() {
-                        tiled::LayerType::TileLayer(layer) => match layer {
-                            tiled::TileLayer::Finite(data) => {
-                                if let Some(layer_tile) = data.get_tile(x, y) {
-                                    if let Some(tile) = layer_tile.get_tile() {
-                                        if let Some(_collision) = &tile.collision {
-                                            commands.entity(chunk_entity).with_children(|child| {
-                                                child
-                                                    .spawn(SpatialBundle {
-                                                        transform: world_tile.transform(),
-                                                        ..Default::default()
-                                                    })
-                                                    .insert(Name::new("Tile Collider"))
-                                                    .insert(world_tile)
-                                                    .insert(RigidBody::Fixed)
-                                                    .insert(Collider::cuboid(
-                                                        TILE_SIZE as f32 / (2.0),
-                                                        TILE_SIZE as f32 / (2.0),
-                                                    ));
-                                            });
-                                        }
-                                    }
-                                }
-                            }
-                            tiled::TileLayer::Infinite(_) => todo!(),
-                        },
-                        tiled::LayerType::ObjectLayer(_) => {}
-                        tiled::LayerType::ImageLayer(_) => todo!(),
-                        tiled::LayerType::GroupLayer(_) => todo!(),
-                    }
-                }
-            }
-        }
         // Game Object
-        let v = cache.get_objects(&save, &editor, &(chunk.x, chunk.y));
+        let v = cache.get_objects(&save, &editor, &chunk);
         if !v.is_empty() {
             events.send_batch(v.iter().map(|id| LoadObject(id.clone())));
         }
@@ -445,6 +525,8 @@ fn load_object(
     mut loaded: ResMut<Loaded>,
     save: Res<SaveBuffer>,
     editor: Res<SceneRes>,
+    saveables: Res<Saveables>,
+    hero_q: Query<(), With<Hero>>,
 ) {
     for ev in events.iter() {
         // info!("LoadObject: {ev:?}");
@@ -457,10 +539,21 @@ fn load_object(
             info!("LoadObject: {ev:?}, start loading object");
             loaded.objects.insert(id.clone());
         }
-        let entity = match cache.get_object_type(&save, &editor, id) {
+        let entity = match cache.get::<GameObjectType>(&save, &editor, &saveables, id) {
+            Some(GameObjectType::Player) if !hero_q.is_empty() => {
+                // The hero is spawned by the LDtk level
+                info!("LoadObject: {ev:?}, hero already exists");
+                continue;
+            }
             Some(o) => {
-                let pos = cache.get_transform(&save, &editor, id).unwrap();
-                let pos = (pos.0.x, pos.0.y).into();
+                let pos = match cache.get_transform(&save, &editor, &saveables, id) {
+                    Some(x) => x.translation.truncate(),
+                    None => {
+                        error!("Missing transform for object: LoadObject: {ev:?}");
+                        loaded.objects.remove(id);
+                        continue;
+                    }
+                };
                 info!("LoadObject: {ev:?}, object load pos: {pos:?}");
                 match o {
                     GameObjectType::None => continue,
@@ -537,40 +630,13 @@ fn load_object(
         };
         info!("LoadObject: {ev:?}, object loaded, {entity:?}, {id:?}");
 
-        if let Some(u) = cache.get_unit(&save, &editor, id) {
-            commands.entity(entity).insert(u.clone());
-        }
-        if let Some(u) = cache.get_blocker(&save, &editor, id) {
-            commands.entity(entity).insert(u.clone());
-        }
-        if let Some(u) = cache.get_area(&save, &editor, id) {
-            commands.entity(entity).insert(u.clone());
-        }
-        if let Some(u) = cache.get_inventory(&save, &editor, id) {
-            commands.entity(entity).insert(u.clone());
-        }
-        if let Some(u) = cache.get_equipment(&save, &editor, id) {
-            commands.entity(entity).insert(u.clone());
-        }
-        if let Some(u) = cache.get_chest(&save, &editor, id) {
-            commands.entity(entity).insert(u.clone());
-        }
-        if let Some(u) = cache.get_enter_trigger(&save, &editor, id) {
-            commands.entity(entity).insert(u.clone());
-        }
-        if let Some(u) = cache.get_die_trigger(&save, &editor, id) {
-            commands.entity(entity).insert(u.clone());
-        }
-        if let Some((memberships, filters)) = cache.get_collision_groups(&save, &editor, id) {
-            commands.entity(entity).insert(CollisionGroups::new(
-                Group::from_bits_truncate(memberships),
-                Group::from_bits_truncate(filters),
-            ));
-        }
-        if let Some(u) = cache.get_animation_state(&save, &editor, id) {
-            commands.entity(entity).insert(u.clone());
+        let mut entity_commands = commands.entity(entity);
+        for info in saveables.iter() {
+            if let Some(data) = cache.get_data(&save, &editor, info.key, id) {
+                info.restore(&mut entity_commands, data);
+            }
         }
-        commands.entity(entity).insert(id.clone());
+        entity_commands.insert(id.clone());
     }
 }
 
@@ -653,3 +719,76 @@ fn reset_world(
         load_events.send(LoadObject(GameObjectId("player".into())));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn chunks_unload_further_than_they_load(distance in 0..10i32) {
+            let config = StreamingConfig::default();
+            // A chunk loaded at the edge stays loaded a chunk further out
+            if distance <= config.radius(false) {
+                prop_assert!(distance < config.radius(true));
+            }
+            // Loaded chunks are only dropped outside the unload radius
+            prop_assert_eq!(distance <= config.radius(true), distance <= config.unload_radius);
+        }
+
+        #[test]
+        fn unload_radius_never_inside_load_radius(
+            load_radius in 0..6i32,
+            unload_radius in 0..6i32,
+            boss_radius in 0..6i32,
+        ) {
+            let config = StreamingConfig {
+                load_radius,
+                unload_radius,
+                unload_radius_overrides: HashMap::from([(GameObjectType::Fox, boss_radius)]),
+                ..Default::default()
+            };
+            prop_assert!(config.radius(true) >= config.radius(false));
+            prop_assert!(config.unload_radius_of(None) >= load_radius);
+            prop_assert!(config.unload_radius_of(Some(&GameObjectType::Fox)) >= load_radius);
+        }
+    }
+
+    #[test]
+    fn bosses_stay_loaded_longer() {
+        let config = StreamingConfig::default();
+        let wolf = config.unload_radius_of(Some(&GameObjectType::Wolf));
+        let fox = config.unload_radius_of(Some(&GameObjectType::Fox));
+        assert_eq!(wolf, config.unload_radius);
+        assert!(fox > wolf);
+    }
+
+    #[test]
+    fn objects_unload_after_grace_period() {
+        let mut game_world = GameWorld::default();
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+        let second = Duration::from_secs(1);
+        assert!(game_world.tick_out_of_range([a], second, 3.0).is_empty());
+        assert!(game_world.tick_out_of_range([a, b], second, 3.0).is_empty());
+        assert_eq!(game_world.tick_out_of_range([a, b], second, 3.0), vec![a]);
+        // Unloaded objects are not tracked anymore
+        assert!(!game_world.out_of_range.contains_key(&a));
+        assert!(game_world.out_of_range.contains_key(&b));
+    }
+
+    #[test]
+    fn grace_period_restarts_in_range() {
+        let mut game_world = GameWorld::default();
+        let a = Entity::from_raw(1);
+        let second = Duration::from_secs(1);
+        game_world.tick_out_of_range([a], second * 2, 3.0);
+        // Back in range for a frame
+        game_world.tick_out_of_range([], second, 3.0);
+        assert!(game_world.out_of_range.is_empty());
+        assert!(game_world
+            .tick_out_of_range([a], second * 2, 3.0)
+            .is_empty());
+        assert_eq!(game_world.tick_out_of_range([a], second, 3.0), vec![a]);
+    }
+}
@@ -29,8 +29,9 @@ impl Plugin for InteractionPlugin {
     }
 }
 
-#[derive(Debug, Component, Reflect)]
+#[derive(Debug, Component, Reflect, Default)]
 pub enum Interaction {
+    #[default]
     None,
     Shop,
     Talk,
@@ -38,12 +39,6 @@ pub enum Interaction {
     Chest,
 }
 
-impl Default for Interaction {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 #[derive(Debug)]
 pub struct InteractEvent {
     pub entity: Entity,
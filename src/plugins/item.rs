@@ -1,11 +1,12 @@
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContext};
-use bevy_inspector_egui::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::actions::skill_id::SkillId;
+use super::damage::HealEvent;
 use super::player::Hero;
+use super::unit::Unit;
 
 pub struct ItemPlugin;
 impl Plugin for ItemPlugin {
@@ -19,7 +20,9 @@ impl Plugin for ItemPlugin {
             .add_event::<EquipEvent>()
             .add_system(equip)
             .add_event::<SwitchEquipment>()
-            .add_system(switch);
+            .add_system(switch)
+            .add_event::<UseItemEvent>()
+            .add_system(use_item);
     }
 }
 
@@ -30,8 +33,9 @@ pub struct Inventory {
     pub money: i32,
 }
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, Reflect, Default)]
 pub enum ItemId {
+    #[default]
     None,
     Unknown,
     HpPotion,
@@ -39,11 +43,6 @@ pub enum ItemId {
     Sword,
     Spear,
 }
-impl Default for ItemId {
-    fn default() -> Self {
-        Self::None
-    }
-}
 impl From<&str> for ItemId {
     fn from(s: &str) -> Self {
         match s {
@@ -82,12 +81,12 @@ impl ItemId {
             ItemId::HpPotion => ItemaSetting {
                 name: "HpPotion".to_string(),
                 icon: "images/chest/chest.png".to_string(),
-                kind: ItemKind::Consume,
+                kind: ItemKind::Consume(Consumable::Hp(30)),
             },
             ItemId::MpPotion => ItemaSetting {
                 name: "MpPotion".to_string(),
                 icon: "images/chest/chest.png".to_string(),
-                kind: ItemKind::Consume,
+                kind: ItemKind::Consume(Consumable::Mp(30)),
             },
             ItemId::Sword => ItemaSetting {
                 name: "Sword".to_string(),
@@ -117,17 +116,13 @@ pub struct ItemaSetting {
     pub kind: ItemKind,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Reflect)]
+#[derive(Debug, Clone, Serialize, Deserialize, Reflect, Default)]
 #[reflect_value()]
 pub enum ItemKind {
+    #[default]
     None,
     Weapon(Weapon),
-    Consume,
-}
-impl Default for ItemKind {
-    fn default() -> Self {
-        Self::None
-    }
+    Consume(Consumable),
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, Reflect)]
@@ -136,6 +131,13 @@ pub struct Weapon {
     pub sub_action_id: SkillId,
 }
 
+/// What using the item restores.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Consumable {
+    Hp(i32),
+    Mp(i32),
+}
+
 #[derive(
     Debug, Default, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, Reflect, Component,
 )]
@@ -162,11 +164,12 @@ pub struct InventoryUiRes {
 pub fn inventory_ui(
     mut egui_context: ResMut<EguiContext>,
     mut inventory_ui: ResMut<InventoryUiRes>,
-    query: Query<(&Inventory, &Equipment), With<Hero>>,
+    query: Query<(Entity, &Inventory, &Equipment), With<Hero>>,
     mut events: EventWriter<EquipEvent>,
     mut switch_events: EventWriter<SwitchEquipment>,
+    mut use_events: EventWriter<UseItemEvent>,
 ) {
-    if let Ok((inventory, equipment)) = query.get_single() {
+    if let Ok((entity, inventory, equipment)) = query.get_single() {
         let inventory_ui = &mut *inventory_ui;
         let show = &mut inventory_ui.show;
         egui::Window::new("Item")
@@ -231,12 +234,12 @@ pub fn inventory_ui(
                                         });
                                     }
                                 }
-                                ItemKind::Consume => {
+                                ItemKind::Consume(_) => {
                                     if ui.button("Use").clicked() {
-                                        // events.send(EquipEvent {
-                                        //     slot: inventory_ui.focus,
-                                        //     item_id: *k,
-                                        // });
+                                        use_events.send(UseItemEvent {
+                                            unit: entity,
+                                            item_id: *k,
+                                        });
                                     }
                                 }
                             }
@@ -273,3 +276,50 @@ pub fn switch(
         }
     }
 }
+
+pub struct UseItemEvent {
+    pub unit: Entity,
+    pub item_id: ItemId,
+}
+pub fn use_item(
+    mut events: EventReader<UseItemEvent>,
+    mut query: Query<(&mut Inventory, &mut Unit)>,
+    mut heal_events: EventWriter<HealEvent>,
+) {
+    for ev in events.iter() {
+        if let Ok((mut inventory, mut unit)) = query.get_mut(ev.unit) {
+            if unit.dead {
+                continue;
+            }
+            let consumable = match ev.item_id.setting().kind {
+                ItemKind::Consume(x) => x,
+                _ => continue,
+            };
+            // Keep the potion when it would do nothing
+            let useful = match consumable {
+                Consumable::Hp(_) => unit.hp < unit.hp_max,
+                Consumable::Mp(_) => unit.mp < unit.mp_max,
+            };
+            if !useful {
+                continue;
+            }
+            match inventory.items.get_mut(&ev.item_id) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    if *count == 0 {
+                        inventory.items.remove(&ev.item_id);
+                    }
+                }
+                _ => continue,
+            }
+            match consumable {
+                Consumable::Hp(heal) => heal_events.send(HealEvent {
+                    unit: ev.unit,
+                    source_unit: None,
+                    heal,
+                }),
+                Consumable::Mp(mp) => unit.mp = (unit.mp + mp as f32).min(unit.mp_max),
+            }
+        }
+    }
+}
@@ -1,30 +1,43 @@
+use anyhow::Context;
 use bevy::prelude::*;
-use bevy_ecs_ldtk::prelude::*;
+use bevy_ecs_ldtk::{ldtk::LdtkJson, prelude::*};
 use bevy_rapier2d::prelude::*;
+use std::path::Path;
 
 use crate::plugins::{
     area::{Area, PlayerEnterEvent},
     blocker::Blocker,
     chest::Chest,
-    game_world::GameObjectId,
+    game_world::{GameObjectId, GameObjectType, LoadObject},
     item::ItemId,
     rogue::{
         rogue::{BackTo, StartTeleport, StartTeleportTarget},
         shop::{ShopSlot, SlotAction, SlotItem},
     },
-    save::SaveBuffer,
+    save::saveable::Saveables,
+    scene_editor::scene_loader::SceneRes,
     trigger::{EventTrigger, TriggerAction},
 };
 
 pub fn process_my_entity(
     mut commands: Commands,
     entity_query: Query<(Entity, &Transform, &EntityInstance), Added<EntityInstance>>,
+    parent_q: Query<&Parent>,
+    transform_q: Query<&Transform>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     asset_server: Res<AssetServer>,
-    save: Res<SaveBuffer>,
+    mut editor: ResMut<SceneRes>,
+    saveables: Res<Saveables>,
+    mut load_events: EventWriter<LoadObject>,
 ) {
     for (entity, transform, entity_instance) in entity_query.iter() {
         debug!("process_my_entity: {}", entity_instance.identifier);
+        if let Some(id) = register_object(&mut editor, &saveables, entity_instance) {
+            let position = world_position(entity, &parent_q, &transform_q);
+            place_object(&mut editor, &saveables, &id, position);
+            load_events.send(LoadObject(id));
+            continue;
+        }
         match entity_instance.identifier.as_str() {
             "Player" => {
                 let id = crate::plugins::player::spawn_hero(
@@ -40,192 +53,6 @@ pub fn process_my_entity(
                     })
                     .add_child(id);
             }
-            "Wolf" => {
-                let id = crate::plugins::wolf::spawn_wolf(
-                    &mut commands,
-                    Vec2::ZERO,
-                    &asset_server,
-                    &mut texture_atlases,
-                );
-                commands
-                    .entity(id)
-                    .insert(GameObjectId(entity_instance.iid.clone()));
-                commands.entity(entity).add_child(id);
-            }
-            "Spider" => {
-                let id = crate::plugins::spider::spawn_spider(
-                    &mut commands,
-                    Vec2::ZERO,
-                    &asset_server,
-                    &mut texture_atlases,
-                );
-
-                commands
-                    .entity(id)
-                    .insert(GameObjectId(entity_instance.iid.clone()));
-                commands.entity(entity).add_child(id);
-            }
-            "Fox" => {
-                let id = crate::plugins::fox::spawn_fox(
-                    &mut commands,
-                    Vec2::ZERO,
-                    &asset_server,
-                    &mut texture_atlases,
-                );
-
-                commands
-                    .entity(id)
-                    .insert(GameObjectId(entity_instance.iid.clone()));
-                commands.entity(entity).add_child(id);
-            }
-            "Shop" => {
-                let id = crate::plugins::shop::spawn_shop(
-                    &mut commands,
-                    Vec2::ZERO,
-                    &asset_server,
-                    &mut texture_atlases,
-                );
-
-                commands
-                    .entity(id)
-                    .insert(GameObjectId(entity_instance.iid.clone()));
-                commands.entity(entity).add_child(id);
-            }
-            "ResetPoint" => {
-                let id = crate::plugins::reset_point::spawn_reset_point(
-                    &mut commands,
-                    Vec2::ZERO,
-                    &asset_server,
-                    &mut texture_atlases,
-                );
-
-                commands
-                    .entity(id)
-                    .insert(GameObjectId(entity_instance.iid.clone()));
-                commands.entity(entity).add_child(id);
-            }
-            "Blocker" => {
-                let id = crate::plugins::blocker::spawn_blocker(
-                    &mut commands,
-                    Vec2::ZERO,
-                    &asset_server,
-                    &mut texture_atlases,
-                );
-
-                let blocking = entity_instance
-                    .field_instances
-                    .iter()
-                    .find(|f| f.identifier == *"blocking")
-                    .unwrap();
-                let blocking = if let FieldValue::Bool(blocking) = &blocking.value {
-                    *blocking
-                } else {
-                    error!("blocking is not bool field!");
-                    false
-                };
-                let blocking = save
-                    .0
-                    .data
-                    .blockers
-                    .get(&GameObjectId(entity_instance.iid.clone()))
-                    .map(|b| b.blocking)
-                    .unwrap_or(blocking);
-                commands.entity(id).insert(Blocker {
-                    hx: (entity_instance.width / 2) as f32,
-                    hy: (entity_instance.height / 2) as f32,
-                    blocking,
-                });
-
-                commands
-                    .entity(id)
-                    .insert(GameObjectId(entity_instance.iid.clone()));
-                commands.entity(entity).add_child(id);
-            }
-            "Area" => {
-                let id = crate::plugins::area::spawn_area(
-                    &mut commands,
-                    Vec2::ZERO,
-                    &asset_server,
-                    &mut texture_atlases,
-                );
-                commands.entity(id).insert(Area {
-                    hx: (entity_instance.width / 2) as f32,
-                    hy: (entity_instance.height / 2) as f32,
-                    disable: false,
-                });
-
-                let actions = entity_instance
-                    .field_instances
-                    .iter()
-                    .find(|f| f.identifier == *"actions")
-                    .unwrap();
-                let actions_targets = entity_instance
-                    .field_instances
-                    .iter()
-                    .find(|f| f.identifier == *"actions_targets")
-                    .unwrap();
-
-                if let FieldValue::Enums(actions) = &actions.value {
-                    if let FieldValue::EntityRefs(targets) = &actions_targets.value {
-                        let mut trigger_actions = vec![];
-                        for (i, action) in actions.iter().enumerate() {
-                            let action = action.as_ref().unwrap();
-                            let entity_iid =
-                                targets.get(i).unwrap().as_ref().unwrap().entity_iid.clone();
-                            trigger_actions.push(TriggerAction::new(action, entity_iid));
-                        }
-                        debug!("trigger_actions: {:?}", trigger_actions);
-                        commands
-                            .entity(id)
-                            .insert(EventTrigger::<PlayerEnterEvent> {
-                                actions: trigger_actions,
-                                ..default()
-                            });
-                    }
-                }
-
-                commands
-                    .entity(id)
-                    .insert(GameObjectId(entity_instance.iid.clone()));
-                commands.entity(entity).add_child(id);
-            }
-            "Chest" => {
-                let id = crate::plugins::chest::spawn_chest(
-                    &mut commands,
-                    Vec2::ZERO,
-                    &asset_server,
-                    &mut texture_atlases,
-                );
-                let item = entity_instance
-                    .field_instances
-                    .iter()
-                    .find(|f| f.identifier == *"item_id")
-                    .unwrap();
-                if let FieldValue::Enum(Some(s)) = &item.value {
-                    //
-                    let item_id = ItemId::from(s.as_str());
-                    let opened = false;
-                    commands.entity(id).insert(Chest { item_id, opened });
-                }
-
-                commands
-                    .entity(id)
-                    .insert(GameObjectId(entity_instance.iid.clone()));
-                commands.entity(entity).add_child(id);
-            }
-            "Rock" => {
-                let id = crate::plugins::rock::spawn_rock(
-                    &mut commands,
-                    Vec2::ZERO,
-                    &asset_server,
-                    &mut texture_atlases,
-                );
-
-                commands
-                    .entity(id)
-                    .insert(GameObjectId(entity_instance.iid.clone()));
-                commands.entity(entity).add_child(id);
-            }
             "StartTeleport" => {
                 let texture_handle = asset_server.load("images/player/spritesheet.png");
                 let texture_atlas = TextureAtlas::from_grid(
@@ -259,15 +86,23 @@ pub fn process_my_entity(
                     ;
             }
             "StartTeleportTarget" => {
+                // Teleport target, kept when the level is unloaded
                 commands
                     .entity(entity)
                     .insert(Name::new(format!("StartTeleportTarget ({entity:?})")))
+                    .insert(Worldly {
+                        entity_iid: entity_instance.iid.clone(),
+                    })
                     .insert(StartTeleportTarget);
             }
             "BackTo" => {
+                // Teleport target, kept when the level is unloaded
                 commands
                     .entity(entity)
                     .insert(Name::new(format!("BackTo ({entity:?})")))
+                    .insert(Worldly {
+                        entity_iid: entity_instance.iid.clone(),
+                    })
                     .insert(BackTo);
             }
             "ShopItem" => {
@@ -318,3 +153,136 @@ pub fn process_my_entity(
         }
     }
 }
+
+/// Read the game objects of an LDtk project, the same defaults the game registers.
+///
+/// Positions are only known once the levels are spawned, so there are no transforms.
+pub fn read_ldtk_objects(path: &Path, saveables: &Saveables) -> anyhow::Result<SceneRes> {
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {path:?}"))?;
+    let project: LdtkJson = serde_json::from_slice(&data)
+        .with_context(|| format!("Failed to parse LDtk project {path:?}"))?;
+    let mut editor = SceneRes::default();
+    let entity_instances = project
+        .levels
+        .iter()
+        .flat_map(|level| level.layer_instances.iter().flatten())
+        .flat_map(|layer| layer.entity_instances.iter());
+    for entity_instance in entity_instances {
+        register_object(&mut editor, saveables, entity_instance);
+    }
+    Ok(editor)
+}
+
+/// Register an LDtk entity as the editor defaults of a game object, `None` if it is not one.
+///
+/// The object is spawned by `load_object` outside of the level, so it outlives the level
+/// and is written to the save when it is unloaded.
+pub fn register_object(
+    editor: &mut SceneRes,
+    saveables: &Saveables,
+    entity_instance: &EntityInstance,
+) -> Option<GameObjectId> {
+    let object_type = match entity_instance.identifier.as_str() {
+        "Wolf" | "Spider" | "Fox" | "Shop" | "ResetPoint" | "Rock" | "Blocker" | "Area"
+        | "Chest" => GameObjectType::from(entity_instance.identifier.as_str()),
+        _ => return None,
+    };
+    let id = GameObjectId(entity_instance.iid.clone());
+    editor.ecs.insert(saveables, id.clone(), object_type);
+    let hx = (entity_instance.width / 2) as f32;
+    let hy = (entity_instance.height / 2) as f32;
+    match object_type {
+        GameObjectType::Blocker => {
+            let blocking = match field(entity_instance, "blocking") {
+                Some(FieldValue::Bool(x)) => *x,
+                _ => {
+                    error!("blocking of {id:?} is not a bool field!");
+                    false
+                }
+            };
+            editor
+                .ecs
+                .insert(saveables, id.clone(), Blocker { hx, hy, blocking });
+        }
+        GameObjectType::Area => {
+            editor.ecs.insert(
+                saveables,
+                id.clone(),
+                Area {
+                    hx,
+                    hy,
+                    disable: false,
+                },
+            );
+            let actions = field(entity_instance, "actions");
+            let targets = field(entity_instance, "actions_targets");
+            if let (Some(FieldValue::Enums(actions)), Some(FieldValue::EntityRefs(targets))) =
+                (actions, targets)
+            {
+                let mut trigger_actions = vec![];
+                for (action, target) in actions.iter().zip(targets.iter()) {
+                    match (action, target) {
+                        (Some(action), Some(target)) => trigger_actions
+                            .push(TriggerAction::new(action, target.entity_iid.clone())),
+                        _ => warn!("Area {id:?} has an action without a target"),
+                    }
+                }
+                debug!("trigger_actions: {:?}", trigger_actions);
+                editor.ecs.insert(
+                    saveables,
+                    id.clone(),
+                    EventTrigger::<PlayerEnterEvent> {
+                        actions: trigger_actions,
+                        ..default()
+                    },
+                );
+            }
+        }
+        GameObjectType::Chest => {
+            if let Some(FieldValue::Enum(Some(s))) = field(entity_instance, "item_id") {
+                let item_id = ItemId::from(s.as_str());
+                let opened = false;
+                editor
+                    .ecs
+                    .insert(saveables, id.clone(), Chest { item_id, opened });
+            }
+        }
+        _ => {}
+    }
+    Some(id)
+}
+
+fn field<'a>(entity_instance: &'a EntityInstance, identifier: &str) -> Option<&'a FieldValue> {
+    entity_instance
+        .field_instances
+        .iter()
+        .find(|f| f.identifier == identifier)
+        .map(|f| &f.value)
+}
+
+/// Put a registered object at its position in the world.
+fn place_object(editor: &mut SceneRes, saveables: &Saveables, id: &GameObjectId, position: Vec3) {
+    editor.ecs.insert_as::<Transform, (Vec3, Quat, Vec3)>(
+        saveables,
+        id.clone(),
+        (position, Quat::IDENTITY, Vec3::ONE),
+    );
+    editor.map.update(id.clone(), position.truncate().into());
+}
+
+/// Position of an LDtk entity in the world, the sum of its level, layer and own translation.
+fn world_position(
+    entity: Entity,
+    parent_q: &Query<&Parent>,
+    transform_q: &Query<&Transform>,
+) -> Vec3 {
+    let mut position = Vec3::ZERO;
+    let mut current = Some(entity);
+    while let Some(e) = current {
+        if let Ok(transform) = transform_q.get(e) {
+            position += transform.translation;
+        }
+        current = parent_q.get(e).ok().map(|p| p.get());
+    }
+    position
+}
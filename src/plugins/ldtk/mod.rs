@@ -1,3 +1,6 @@
 pub mod components;
 pub mod entities;
 pub mod systems;
+
+/// The LDtk project the world is built from, relative to the asset directory.
+pub const LDTK_PROJECT: &str = "maps/test.ldtk";
@@ -1,4 +1,4 @@
-use crate::plugins::player::Hero;
+use crate::plugins::{game_world::START_LEVEL, player::Hero};
 
 use super::components::*;
 use bevy::prelude::*;
@@ -105,7 +105,7 @@ pub fn spawn_wall_collision(
         if let Ok(grandparent) = parent_query.get(parent.get()) {
             level_to_wall_locations
                 .entry(grandparent.get())
-                .or_insert(HashSet::new())
+                .or_default()
                 .insert(grid_coords);
         }
     });
@@ -320,7 +320,7 @@ pub fn camera_fit_inside_current_level(
         (&Transform, &Handle<LdtkLevel>),
         (Without<OrthographicProjection>, Without<Hero>),
     >,
-    level_selection: Res<LevelSelection>,
+    current_level: Res<CurrentLevel>,
     ldtk_levels: Res<Assets<LdtkLevel>>,
 ) {
     if let Ok(Transform {
@@ -335,7 +335,7 @@ pub fn camera_fit_inside_current_level(
         for (level_transform, level_handle) in &level_query {
             if let Some(ldtk_level) = ldtk_levels.get(level_handle) {
                 let level = &ldtk_level.level;
-                if level_selection.is_match(&0, level) {
+                if current_level.0.is_match(&0, level) {
                     let level_ratio = level.px_wid as f32 / ldtk_level.level.px_hei as f32;
 
                     orthographic_projection.scaling_mode = bevy::render::camera::ScalingMode::None;
@@ -369,10 +369,23 @@ pub fn camera_fit_inside_current_level(
     }
 }
 
+/// The level the hero is in.
+///
+/// Not the `LevelSelection` resource, that would make `bevy_ecs_ldtk` spawn only this level
+/// and its neighbours, the levels are streamed by `GameWorldPlugin` instead.
+#[derive(Debug, Clone, Resource)]
+pub struct CurrentLevel(pub LevelSelection);
+
+impl Default for CurrentLevel {
+    fn default() -> Self {
+        Self(LevelSelection::Identifier(START_LEVEL.into()))
+    }
+}
+
 pub fn update_level_selection(
     level_query: Query<(&Handle<LdtkLevel>, &Transform), Without<Hero>>,
     player_query: Query<&GlobalTransform, With<Hero>>,
-    mut level_selection: ResMut<LevelSelection>,
+    mut current_level: ResMut<CurrentLevel>,
     ldtk_levels: Res<Assets<LdtkLevel>>,
 ) {
     for (level_handle, level_transform) in &level_query {
@@ -390,9 +403,9 @@ pub fn update_level_selection(
                     && player_transform.translation().x > level_bounds.min.x
                     && player_transform.translation().y < level_bounds.max.y
                     && player_transform.translation().y > level_bounds.min.y
-                    && !level_selection.is_match(&0, &ldtk_level.level)
+                    && !current_level.0.is_match(&0, &ldtk_level.level)
                 {
-                    *level_selection = LevelSelection::Iid(ldtk_level.level.iid.clone());
+                    current_level.0 = LevelSelection::Iid(ldtk_level.level.iid.clone());
                 }
             }
         }
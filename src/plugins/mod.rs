@@ -14,6 +14,7 @@ pub mod items;
 pub mod knockback;
 pub mod ldtk;
 pub mod movement;
+pub mod navigation;
 pub mod player;
 pub mod reset_point;
 pub mod rock;
@@ -30,6 +31,12 @@ pub mod trigger;
 pub mod units;
 pub mod visual_effect;
 pub mod wolf;
+pub use units::attribute;
+pub use units::behavior;
+pub use units::buff;
+pub use units::experience;
+pub use units::summon;
+pub use units::threat;
 pub use units::unit;
 pub use units::unit_action;
 pub use units::unit_state;
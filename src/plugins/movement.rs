@@ -12,7 +12,7 @@ pub struct Movement {
     pub face: Option<Vec2>,
 }
 
-pub(crate) fn update_movement(
+pub fn update_movement(
     time: Res<Time>,
     mut query: Query<(
         &Movement,
@@ -0,0 +1,330 @@
+use bevy::{
+    prelude::*,
+    transform::TransformSystem,
+    utils::{HashMap, HashSet},
+};
+use bevy_ecs_ldtk::prelude::*;
+use std::{cmp::Ordering, collections::BinaryHeap, f32::consts::SQRT_2};
+
+use super::{blocker::Blocker, ldtk::components::Wall, spatial_map::TILE_SIZE};
+
+/// Cells expanded before a goal is given up as unreachable.
+const MAX_SEARCH: usize = 4096;
+/// Half the width of a unit kept clear of walls when cutting corners, in cells.
+const CLEARANCE: f32 = 0.3;
+
+const NEIGHBORS: [(IVec2, f32); 8] = [
+    (IVec2::new(1, 0), 1.0),
+    (IVec2::new(-1, 0), 1.0),
+    (IVec2::new(0, 1), 1.0),
+    (IVec2::new(0, -1), 1.0),
+    (IVec2::new(1, 1), SQRT_2),
+    (IVec2::new(1, -1), SQRT_2),
+    (IVec2::new(-1, 1), SQRT_2),
+    (IVec2::new(-1, -1), SQRT_2),
+];
+
+/// A grid of the LDtk wall cells of the loaded levels and of blocking [`Blocker`]s, for units to
+/// walk around them.
+///
+/// It is rebuilt when walls spawn or despawn with their level and when a blocker toggles.
+pub struct NavigationPlugin;
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavGrid>()
+            .register_type::<NavPath>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                rebuild_grid.after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct NavGrid {
+    blocked: HashSet<IVec2>,
+}
+
+impl NavGrid {
+    pub fn cell(pos: Vec2) -> IVec2 {
+        (pos / TILE_SIZE as f32).floor().as_ivec2()
+    }
+
+    pub fn center(cell: IVec2) -> Vec2 {
+        (cell.as_vec2() + 0.5) * TILE_SIZE as f32
+    }
+
+    pub fn is_blocked(&self, cell: IVec2) -> bool {
+        self.blocked.contains(&cell)
+    }
+
+    /// Whether a unit can walk straight from `a` to `b`.
+    pub fn line_of_sight(&self, a: Vec2, b: Vec2) -> bool {
+        let d = b - a;
+        let side = d.perp().normalize_or_zero() * CLEARANCE * TILE_SIZE as f32;
+        let steps = (d.length() / (TILE_SIZE as f32 / 4.0)).ceil().max(1.0) as i32;
+        (0..=steps).all(|i| {
+            let p = a + d * (i as f32 / steps as f32);
+            [p, p + side, p - side]
+                .iter()
+                .all(|p| !self.is_blocked(Self::cell(*p)))
+        })
+    }
+
+    /// Waypoints from `from` to `to` found with A*, smoothed so each one is the furthest visible.
+    ///
+    /// The last waypoint is `to`. `None` when `to` is in a wall or too far around them.
+    pub fn find_path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+        if self.line_of_sight(from, to) {
+            return Some(vec![to]);
+        }
+        let start = Self::cell(from);
+        let goal = Self::cell(to);
+        if self.is_blocked(goal) {
+            return None;
+        }
+
+        let mut open = BinaryHeap::from([Open {
+            cell: start,
+            f: octile(start, goal),
+        }]);
+        let mut came_from: HashMap<IVec2, IVec2> = HashMap::default();
+        let mut cost: HashMap<IVec2, f32> = HashMap::default();
+        cost.insert(start, 0.0);
+        while let Some(Open { cell, .. }) = open.pop() {
+            if cell == goal {
+                let mut cells = vec![goal];
+                while let Some(prev) = came_from.get(cells.last().unwrap()) {
+                    if *prev == start {
+                        break;
+                    }
+                    cells.push(*prev);
+                }
+                let mut points: Vec<Vec2> = cells.into_iter().rev().map(Self::center).collect();
+                *points.last_mut().unwrap() = to;
+                return Some(self.smooth(from, &points));
+            }
+            if came_from.len() > MAX_SEARCH {
+                return None;
+            }
+            let g = cost[&cell];
+            for (offset, step) in NEIGHBORS {
+                let next = cell + offset;
+                if self.is_blocked(next) {
+                    continue;
+                }
+                // Diagonals may not squeeze between two walls touching at a corner
+                if offset.x != 0
+                    && offset.y != 0
+                    && (self.is_blocked(cell + IVec2::new(offset.x, 0))
+                        || self.is_blocked(cell + IVec2::new(0, offset.y)))
+                {
+                    continue;
+                }
+                let g = g + step;
+                if cost.get(&next).is_none_or(|c| g < *c) {
+                    cost.insert(next, g);
+                    came_from.insert(next, cell);
+                    open.push(Open {
+                        cell: next,
+                        f: g + octile(next, goal),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Skip the points that can be walked past in a straight line.
+    fn smooth(&self, from: Vec2, points: &[Vec2]) -> Vec<Vec2> {
+        let mut waypoints = vec![];
+        let mut current = from;
+        let mut i = 0;
+        while i < points.len() {
+            let mut j = points.len() - 1;
+            while j > i && !self.line_of_sight(current, points[j]) {
+                j -= 1;
+            }
+            current = points[j];
+            waypoints.push(current);
+            i = j + 1;
+        }
+        waypoints
+    }
+}
+
+fn octile(a: IVec2, b: IVec2) -> f32 {
+    let d = (a - b).abs();
+    let (min, max) = (d.x.min(d.y) as f32, d.x.max(d.y) as f32);
+    max + (SQRT_2 - 1.0) * min
+}
+
+/// Lowest cost first in the [`BinaryHeap`].
+struct Open {
+    cell: IVec2,
+    f: f32,
+}
+impl PartialEq for Open {
+    fn eq(&self, other: &Self) -> bool {
+        self.f == other.f
+    }
+}
+impl Eq for Open {}
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.f.total_cmp(&self.f)
+    }
+}
+
+/// The waypoints of a unit walking to `goal`, the next one first.
+#[derive(Debug, Default, Clone, Component, Reflect)]
+pub struct NavPath {
+    pub goal: Vec2,
+    pub waypoints: Vec<Vec2>,
+}
+
+fn rebuild_grid(
+    mut grid: ResMut<NavGrid>,
+    added_walls: Query<(), Added<Wall>>,
+    removed_walls: RemovedComponents<Wall>,
+    changed_blockers: Query<(), Changed<Blocker>>,
+    removed_blockers: RemovedComponents<Blocker>,
+    wall_q: Query<(&GridCoords, &Parent), With<Wall>>,
+    parent_q: Query<&Parent, Without<Wall>>,
+    level_q: Query<&GlobalTransform, With<Handle<LdtkLevel>>>,
+    blocker_q: Query<(&GlobalTransform, &Blocker)>,
+    mut path_q: Query<&mut NavPath>,
+) {
+    if added_walls.is_empty()
+        && removed_walls.iter().next().is_none()
+        && changed_blockers.is_empty()
+        && removed_blockers.iter().next().is_none()
+    {
+        return;
+    }
+    grid.blocked.clear();
+    for (coords, parent) in wall_q.iter() {
+        // The parent of an int-grid cell is its layer, the level is above it
+        let level = parent_q
+            .get(parent.get())
+            .and_then(|layer| level_q.get(layer.get()));
+        if let Ok(level) = level {
+            let pos = level.translation().truncate()
+                + (Vec2::new(coords.x as f32, coords.y as f32) + 0.5) * TILE_SIZE as f32;
+            grid.blocked.insert(NavGrid::cell(pos));
+        }
+    }
+    for (gt, blocker) in blocker_q.iter() {
+        if !blocker.blocking {
+            continue;
+        }
+        // Same size as the collider of `blocker::attach`
+        let pos = gt.translation().truncate();
+        let half = Vec2::splat(31.0);
+        let (min, max) = (NavGrid::cell(pos - half), NavGrid::cell(pos + half));
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                grid.blocked.insert(IVec2::new(x, y));
+            }
+        }
+    }
+    // Paths may go through a new wall, or around one that is gone
+    for mut path in path_q.iter_mut() {
+        path.waypoints.clear();
+    }
+    debug!(
+        "Navigation grid rebuilt, {} cells blocked",
+        grid.blocked.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(cells: impl IntoIterator<Item = (i32, i32)>) -> NavGrid {
+        NavGrid {
+            blocked: cells.into_iter().map(|(x, y)| IVec2::new(x, y)).collect(),
+        }
+    }
+
+    /// A wall at x = 2 from y = -3 to y = 3.
+    fn wall() -> NavGrid {
+        grid((-3..=3).map(|y| (2, y)))
+    }
+
+    #[test]
+    fn wall_blocks_line_of_sight() {
+        let grid = wall();
+        let (a, b) = (
+            NavGrid::center(IVec2::ZERO),
+            NavGrid::center(IVec2::new(4, 0)),
+        );
+        assert!(!grid.line_of_sight(a, b));
+        assert!(grid.line_of_sight(a, NavGrid::center(IVec2::new(0, 5))));
+    }
+
+    #[test]
+    fn open_line_goes_straight() {
+        let grid = wall();
+        let (a, b) = (
+            NavGrid::center(IVec2::ZERO),
+            NavGrid::center(IVec2::new(0, -6)),
+        );
+        assert_eq!(grid.find_path(a, b), Some(vec![b]));
+    }
+
+    #[test]
+    fn path_around_wall() {
+        let grid = wall();
+        let (from, to) = (
+            NavGrid::center(IVec2::ZERO),
+            NavGrid::center(IVec2::new(4, 0)),
+        );
+        let path = grid.find_path(from, to).unwrap();
+        assert!(path.len() > 1);
+        assert_eq!(*path.last().unwrap(), to);
+        let mut current = from;
+        for p in path {
+            assert!(grid.line_of_sight(current, p), "{current} to {p}");
+            current = p;
+        }
+    }
+
+    #[test]
+    fn blocked_goal() {
+        let grid = wall();
+        let from = NavGrid::center(IVec2::ZERO);
+        assert_eq!(
+            grid.find_path(from, NavGrid::center(IVec2::new(2, 0))),
+            None
+        );
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        // The goal is boxed in, the search gives up
+        let ring = (-1..=1)
+            .flat_map(|x| (-1..=1).map(move |y| (x + 10, y)))
+            .filter(|c| *c != (10, 0));
+        let grid = grid(ring);
+        let from = NavGrid::center(IVec2::ZERO);
+        assert_eq!(
+            grid.find_path(from, NavGrid::center(IVec2::new(10, 0))),
+            None
+        );
+    }
+
+    #[test]
+    fn smooth_skips_collinear_points() {
+        let grid = NavGrid::default();
+        let points: Vec<Vec2> = (1..=5).map(|x| NavGrid::center(IVec2::new(x, 0))).collect();
+        let from = NavGrid::center(IVec2::ZERO);
+        assert_eq!(grid.smooth(from, &points), vec![points[4]]);
+    }
+}
@@ -5,17 +5,21 @@ use bevy_rapier2d::prelude::{QueryFilter, RapierContext};
 use std::collections::HashMap;
 use std::time::Duration;
 
-use super::actions::action::Skill;
+use super::actions::action::{Skill, SkillRejectReason};
+use super::actions::setting::SkillCost;
+use super::actions::skill_data::SkillSettings;
 use super::actions::skill_id::SkillId;
 use super::animation::AnimationData;
+use super::experience::{Experience, LevelUpEvent, MAX_LEVEL};
 use super::interaction::Interacting;
 use super::item::{Equipment, InventoryUiRes, OpenInventoryEvent, SwitchEquipment};
-use super::save::{SaveTransform, SaveUnit};
+use super::save::saveable::SaveComponent;
 use super::team::Team;
 use super::unit_action::{UnitActions, UnitAnimation};
 use crate::plugins::animation::{AnimationSheet, AnimationState};
 use crate::plugins::unit::{self, SpawnUnit, Unit};
 use crate::plugins::units::unit_command::UnitCommand;
+use crate::plugins::units::unit_state::ChangeActionRejected;
 
 pub struct PlayerPlugin;
 
@@ -61,6 +65,7 @@ pub struct PlayerController {
     pub open_inventory: bool,
 }
 
+#[allow(dead_code)]
 fn cursor_position(
     windows: Res<Windows>,
     q_camera: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
@@ -193,7 +198,7 @@ pub fn update_command(
                 super::item::ItemKind::Weapon(w) => {
                     command.action_id = w.main_action_id;
                 }
-                super::item::ItemKind::Consume => (),
+                super::item::ItemKind::Consume(_) => (),
             }
         }
         if controller.special_attack {
@@ -202,7 +207,7 @@ pub fn update_command(
                 super::item::ItemKind::Weapon(w) => {
                     command.action_id = w.sub_action_id;
                 }
-                super::item::ItemKind::Consume => (),
+                super::item::ItemKind::Consume(_) => (),
             }
         }
     }
@@ -238,6 +243,10 @@ pub fn spawn_hero(
                 atk: 20,
                 movement_speed: 15.0,
                 stun: 0.0,
+                mp: 100.0,
+                mp_max: 100.0,
+                mp_regen: 2.0,
+                ..default()
             },
             team: Team::Player,
             translation: position,
@@ -340,12 +349,14 @@ pub fn spawn_hero(
     commands
         .entity(id)
         .insert(Hero::default())
+        .insert(Experience::default())
         // .insert(PlayerController::default())
         .insert(RtsController::default())
         .insert(Interacting { target: None })
         // Save
-        .insert(SaveUnit)
-        .insert(SaveTransform)
+        .insert(SaveComponent::<Unit>::default())
+        .insert(SaveComponent::<Transform>::default())
+        .insert(SaveComponent::<Experience>::default())
         //
         .insert(Name::new(format!("Hero ({id:?})")))
         .id()
@@ -449,12 +460,12 @@ fn mouse_button_input(
             uc.action_id = SkillId::MoveTo;
             uc.target_position = Some(controller.cursor_pos);
         }
-        if keys.just_pressed(KeyCode::A) || buttons.just_pressed(MouseButton::Right) {
-            if controller.pointing_unit.is_some() {
-                uc.action_id = SkillId::Attack;
-                uc.target_position = Some(controller.cursor_pos);
-                uc.target_unit = controller.pointing_unit;
-            }
+        if (keys.just_pressed(KeyCode::A) || buttons.just_pressed(MouseButton::Right))
+            && controller.pointing_unit.is_some()
+        {
+            uc.action_id = SkillId::Attack;
+            uc.target_position = Some(controller.cursor_pos);
+            uc.target_unit = controller.pointing_unit;
         }
 
         if keys.just_pressed(KeyCode::Q) {
@@ -484,11 +495,41 @@ fn mouse_button_input(
 
 fn ui_example_system(
     mut egui_context: ResMut<EguiContext>,
-    query: Query<(&Unit, &UnitActions, &Hero), With<Hero>>,
+    query: Query<(Entity, &Unit, &UnitActions, &Hero, &Experience), With<Hero>>,
     action_query: Query<&Skill>,
     asset_server: Res<AssetServer>,
+    mut rejected: EventReader<ChangeActionRejected>,
+    mut level_ups: EventReader<LevelUpEvent>,
+    // Why the last skill could not be used or a level up, shown for a moment
+    mut message: Local<Option<(String, Timer)>>,
+    time: Res<Time>,
+    settings: Res<SkillSettings>,
 ) {
-    if let Ok((unit, ua, hero)) = query.get_single() {
+    if let Ok((hero_id, unit, ua, hero, exp)) = query.get_single() {
+        for ev in rejected.iter().filter(|ev| ev.entity == hero_id) {
+            let name = match settings.get(&ev.action_id) {
+                Some(x) => &x.name,
+                None => continue,
+            };
+            let text = match ev.reason {
+                SkillRejectReason::Cooldown => format!("{name} is not ready"),
+                SkillRejectReason::Cost(SkillCost::Hp(hp)) => {
+                    format!("{name} needs more than {hp} Hp")
+                }
+                SkillRejectReason::Cost(SkillCost::Mp(mp)) => format!("{name} needs {mp} Mp"),
+            };
+            *message = Some((text, Timer::from_seconds(1.5, TimerMode::Once)));
+        }
+        for ev in level_ups.iter().filter(|ev| ev.unit == hero_id) {
+            let text = format!("Level up! Lv {}", ev.level);
+            *message = Some((text, Timer::from_seconds(1.5, TimerMode::Once)));
+        }
+        if let Some((_, timer)) = message.as_mut() {
+            if timer.tick(time.delta()).finished() {
+                *message = None;
+            }
+        }
+
         egui::Window::new("Hello")
             .title_bar(false)
             .resizable(false)
@@ -503,14 +544,31 @@ fn ui_example_system(
                 ui.add(progress_bar);
 
                 ui.label("Mp");
-                let progress = 0.0 / 100.0;
+                let progress = match unit.mp_max > 0.0 {
+                    true => unit.mp / unit.mp_max,
+                    false => 0.0,
+                };
                 let progress_bar = egui::ProgressBar::new(progress)
                     .desired_width(400.0)
-                    // .text("progress bar text")
-                    .show_percentage();
+                    .text(format!("{:.0} / {:.0}", unit.mp.floor(), unit.mp_max));
                 ui.add(progress_bar);
 
+                ui.label(format!("Lv {}", exp.level));
+                let progress_bar = match exp.level < MAX_LEVEL {
+                    true => {
+                        let next = Experience::exp_to_next(exp.level);
+                        egui::ProgressBar::new(exp.exp as f32 / next as f32)
+                            .text(format!("{} / {next}", exp.exp))
+                    }
+                    false => egui::ProgressBar::new(1.0).text("Max"),
+                };
+                ui.add(progress_bar.desired_width(400.0));
+                if exp.skill_points > 0 {
+                    ui.label(format!("Skill points: {}", exp.skill_points));
+                }
+
                 ui.label(format!("Atk: {}", unit.atk));
+                ui.label(format!("Armor: {}", unit.armor));
 
                 ui.label(format!("Gold: {}", hero.gold));
             });
@@ -539,15 +597,49 @@ fn ui_example_system(
             // Show tooltip when hover, show action name, desc, level, shortcut, cooldown(setting)
             match actions.get(i) {
                 Some(&&act) => {
-                    if let Ok(action) = action_query.get(act) {
-                        let setting = action.action_id.setting();
-
-                        let texture_handle = asset_server.load(setting.icon);
+                    if let Some((action, setting)) = action_query
+                        .get(act)
+                        .ok()
+                        .and_then(|a| Some((a, settings.get(&a.action_id)?)))
+                    {
+                        let texture_handle = asset_server.load(&*setting.icon);
                         let egui_id = egui_context.add_image(texture_handle.clone());
+                        let usage = &setting.usage;
+                        let cooldown = action.remaining_cooldown();
+                        let mut status = String::new();
+                        if usage.charges > 1 {
+                            status += &format!("{}/{} ", action.charges(usage), usage.charges);
+                        }
+                        if cooldown > 0.0 {
+                            status += &format!("{cooldown:.1}s");
+                        }
+                        let mut desc = setting.desc.to_string();
+                        if usage.cooldown > 0.0 {
+                            desc += &format!("\nCooldown: {}s", usage.cooldown);
+                        }
+                        match usage.cost {
+                            Some(SkillCost::Hp(hp)) => desc += &format!("\nCost: {hp} Hp"),
+                            Some(SkillCost::Mp(mp)) => desc += &format!("\nCost: {mp} Mp"),
+                            None => {}
+                        }
+                        // Current values, and what the next level changes them to
+                        let next = match action.level < setting.max_level {
+                            true => setting.base.level_values(action.level + 1),
+                            false => vec![],
+                        };
+                        for (i, (name, value)) in
+                            setting.base.level_values(action.level).iter().enumerate()
+                        {
+                            desc += &format!("\n{name}: {value}");
+                            if let Some((_, next)) = next.get(i).filter(|(_, n)| n != value) {
+                                desc += &format!(" -> {next}");
+                            }
+                        }
                         uis.push((
                             setting.name.to_string(),
-                            action.level.to_string(),
-                            setting.desc.to_string(),
+                            format!("{} / {}", action.level, setting.max_level),
+                            desc,
+                            status,
                             egui_id,
                         ));
                     } else {
@@ -556,7 +648,13 @@ fn ui_example_system(
                         let texture_handle = asset_server
                             .load("images/particlePack_1.1/PNG (Transparent)/circle_01.png");
                         let egui_id = egui_context.add_image(texture_handle.clone());
-                        uis.push((String::new(), String::new(), String::new(), egui_id));
+                        uis.push((
+                            String::new(),
+                            String::new(),
+                            String::new(),
+                            String::new(),
+                            egui_id,
+                        ));
                     }
                 }
                 None => {
@@ -564,7 +662,13 @@ fn ui_example_system(
                     let texture_handle = asset_server
                         .load("images/particlePack_1.1/PNG (Transparent)/circle_01.png");
                     let egui_id = egui_context.add_image(texture_handle.clone());
-                    uis.push((String::new(), String::new(), String::new(), egui_id));
+                    uis.push((
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        egui_id,
+                    ));
                 }
             }
         }
@@ -574,15 +678,21 @@ fn ui_example_system(
             .resizable(false)
             .anchor(egui::Align2::CENTER_BOTTOM, [0.0, 0.0])
             .show(egui_context.ctx_mut(), |ui| {
+                if let Some((text, _)) = message.as_ref() {
+                    ui.vertical_centered(|ui| {
+                        ui.colored_label(egui::Color32::LIGHT_RED, text);
+                    });
+                }
                 egui::Grid::new("id_source")
                     .spacing([40.0, 4.0])
                     .show(ui, |ui| {
-                        for (i, (name, level, desc, image)) in uis.iter().enumerate() {
+                        for (name, level, desc, status, image) in uis.iter() {
                             ui.vertical_centered(|ui| {
                                 ui.image(*image, [64.0, 64.0]).on_hover_ui(|ui| {
                                     ui.label(format!("{} Lv {} \n{}", name, level, desc));
                                 });
                                 ui.label(name);
+                                ui.label(status);
                             });
                         }
                     });
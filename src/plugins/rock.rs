@@ -41,6 +41,7 @@ pub fn spawn_rock(
                 atk: 1,
                 movement_speed: 0.0,
                 stun: 0.0,
+                ..Default::default()
             },
             team: Team::Enemy,
             translation: position,
@@ -4,7 +4,11 @@ use bevy_rapier2d::prelude::CollisionEvent;
 use rand::prelude::*;
 
 use crate::plugins::{
-    actions::{action::Skill, skill_id::SkillId},
+    actions::{
+        action::{find_skill, Skill},
+        skill_data::SkillSettings,
+        skill_id::SkillId,
+    },
     item::ItemId,
     player::Hero,
     unit_action::UnitActions,
@@ -33,17 +37,28 @@ pub struct SlotAction {
 }
 
 const ITEMS: [ItemId; 3] = [ItemId::Sword, ItemId::HpPotion, ItemId::MpPotion];
-const SKILLS: [SkillId; 9] = [
+const SKILLS: [SkillId; 20] = [
+    SkillId::Fireball,
+    SkillId::Explosion,
+    SkillId::Burning,
+    SkillId::Drone,
+    SkillId::FireBreath,
     SkillId::DeadFinger,
     SkillId::Thunder,
     SkillId::LifeDrain,
-    // SkillId::LifeSteal,
+    SkillId::SummonWolf,
+    SkillId::LifeSteal,
+    SkillId::CriticalHit,
     SkillId::Diffusion,
     SkillId::FrostBall,
     SkillId::SmashWave,
     SkillId::HealAura,
     SkillId::AttackAura,
     SkillId::SpeedAura,
+    // assets/data/skills
+    SkillId::Custom(1),
+    SkillId::Custom(2),
+    SkillId::Custom(3),
 ];
 
 #[derive(Debug, Clone, Default)]
@@ -97,10 +112,12 @@ pub fn slot_item_changed(mut items_q: Query<(&mut ShopSlot, &SlotItem), Changed<
     }
 }
 
-pub fn slot_action_changed(mut items_q: Query<(&mut ShopSlot, &SlotAction), Changed<SlotAction>>) {
+pub fn slot_action_changed(
+    mut items_q: Query<(&mut ShopSlot, &SlotAction), Changed<SlotAction>>,
+    settings: Res<SkillSettings>,
+) {
     for (mut slot, i) in items_q.iter_mut() {
-        if let Some(item) = i.action_id {
-            let s = item.setting();
+        if let Some(s) = i.action_id.and_then(|id| settings.get(&id)) {
             slot.name = s.name.to_string();
             slot.desc = s.desc.to_string();
             slot.icon = Some(s.icon.to_string());
@@ -186,10 +203,27 @@ pub fn buy_action(
     mut events: EventReader<ShopSlotBuyEvent>,
     mut query: Query<(&mut ShopSlot, &mut SlotAction)>,
     mut write_events: EventWriter<HeroGetAction>,
+    hero_q: Query<&UnitActions, With<Hero>>,
+    skill_q: Query<&Skill>,
+    settings: Res<SkillSettings>,
 ) {
     for ev in events.iter() {
         if let Ok((mut slot, mut action)) = query.get_mut(ev.slot) {
             if let Some(action_id) = action.action_id {
+                let max_level = match settings.get(&action_id) {
+                    Some(x) => x.max_level,
+                    None => continue,
+                };
+                // Keep the slot for later if the hero cannot level the skill any more
+                let maxed = hero_q
+                    .get_single()
+                    .ok()
+                    .and_then(|ua| find_skill(ua, action_id, &skill_q))
+                    .is_some_and(|s| s.level >= max_level);
+                if maxed {
+                    info!("{action_id:?} is already at max level");
+                    continue;
+                }
                 slot.name = "".to_string();
                 slot.desc = "".to_string();
                 slot.icon = None;
@@ -210,14 +244,19 @@ pub fn hero_get_action(
     mut commands: Commands,
     mut query: Query<(Entity, &mut UnitActions), With<Hero>>,
     mut action_q: Query<&mut Skill>,
+    settings: Res<SkillSettings>,
 ) {
     for ev in events.iter() {
+        let max_level = match settings.get(&ev.action_id) {
+            Some(x) => x.max_level,
+            None => continue,
+        };
         if let Ok((hero_id, mut ua)) = query.get_single_mut() {
             let mut found = false;
             for a in ua.actions.iter() {
                 if let Ok(mut action) = action_q.get_mut(*a) {
                     if action.action_id == ev.action_id {
-                        action.level += 1;
+                        action.level = (action.level + 1).min(max_level);
                         found = true;
                         break;
                     }
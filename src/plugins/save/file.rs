@@ -0,0 +1,278 @@
+use anyhow::{bail, Context};
+use bevy::prelude::*;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use super::{migration, Save};
+
+/// First line of every file written by [`write_atomic`], RON reads it as a comment.
+const CHECKSUM_PREFIX: &str = "// checksum: ";
+const BACKUP_TAG: &str = "auto";
+
+/// Encoding of the save file, `GAME_SAVE_FORMAT=bin` picks the compact one for large worlds.
+///
+/// Binary saves are deflated RON, so they are migrated the same way.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SaveFormat {
+    #[default]
+    Ron,
+    Binary,
+}
+
+impl SaveFormat {
+    pub const ALL: [SaveFormat; 2] = [SaveFormat::Ron, SaveFormat::Binary];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SaveFormat::Ron => "ron",
+            SaveFormat::Binary => "bin",
+        }
+    }
+
+    /// Format of a save, or of the save a backup was copied from.
+    pub fn from_path(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        // Backups are named `<save file>.<tag>-<timestamp>.bak`
+        let name = match name.strip_suffix(".bak") {
+            Some(x) => x.rsplit_once('.').map_or(x, |(save, _)| save),
+            None => name,
+        };
+        match Path::new(name).extension().and_then(|e| e.to_str()) {
+            Some("bin") => SaveFormat::Binary,
+            _ => SaveFormat::Ron,
+        }
+    }
+
+    pub fn encode(&self, save: &Save) -> anyhow::Result<Vec<u8>> {
+        Ok(match self {
+            SaveFormat::Ron => {
+                let pretty = ron::ser::PrettyConfig::new()
+                    // .depth_limit(2)
+                    .separate_tuple_members(true)
+                    .enumerate_arrays(true);
+                ron::ser::to_string_pretty(save, pretty)?.into_bytes()
+            }
+            SaveFormat::Binary => {
+                let doc = ron::ser::to_string(save)?;
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(doc.as_bytes())?;
+                encoder.finish()?
+            }
+        })
+    }
+
+    /// The RON document of an encoded save.
+    pub fn decode(&self, data: &[u8]) -> anyhow::Result<String> {
+        Ok(match self {
+            SaveFormat::Ron => String::from_utf8(data.to_vec())?,
+            SaveFormat::Binary => {
+                let mut doc = String::new();
+                DeflateDecoder::new(data)
+                    .read_to_string(&mut doc)
+                    .context("Failed to inflate binary save")?;
+                doc
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, Resource)]
+pub struct SaveConfig {
+    pub format: SaveFormat,
+    /// Number of timestamped backups kept next to each save.
+    pub keep_backups: usize,
+    /// Minimum seconds between two backups.
+    pub backup_interval: u64,
+}
+
+impl Default for SaveConfig {
+    fn default() -> Self {
+        let format = match std::env::var("GAME_SAVE_FORMAT").as_deref() {
+            Ok("bin") | Ok("binary") => SaveFormat::Binary,
+            _ => SaveFormat::Ron,
+        };
+        Self {
+            format,
+            keep_backups: 5,
+            backup_interval: 60,
+        }
+    }
+}
+
+/// FNV-1a, stable across builds unlike `DefaultHasher`.
+pub fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Write to a temp file and rename it over `path`, so a crash never leaves a half written file.
+pub fn write_atomic(path: &Path, data: &[u8]) -> anyhow::Result<()> {
+    let tmp = tmp_path(path)?;
+    {
+        let mut file =
+            std::fs::File::create(&tmp).with_context(|| format!("Failed to create {tmp:?}"))?;
+        writeln!(file, "{CHECKSUM_PREFIX}{:016x}", checksum(data))?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to rename {tmp:?} to {path:?}"))
+}
+
+/// `save.ron.tmp`, keeping the extension so saves in other formats get their own temp file.
+fn tmp_path(path: &Path) -> anyhow::Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .context("Invalid save file name")?;
+    Ok(path.with_file_name(format!("{file_name}.tmp")))
+}
+
+/// Check and strip the checksum line. Files without one are accepted as is.
+pub fn verify_checksum(data: &[u8]) -> anyhow::Result<&[u8]> {
+    let rest = match data.strip_prefix(CHECKSUM_PREFIX.as_bytes()) {
+        Some(x) => x,
+        None => return Ok(data),
+    };
+    let end = rest
+        .iter()
+        .position(|b| *b == b'\n')
+        .context("Missing body after checksum")?;
+    let line = std::str::from_utf8(&rest[..end]).context("Invalid checksum")?;
+    let body = &rest[end + 1..];
+    let expected = u64::from_str_radix(line.trim(), 16).context("Invalid checksum")?;
+    let actual = checksum(body);
+    if expected != actual {
+        bail!("Checksum mismatch, expected {expected:016x}, got {actual:016x}");
+    }
+    Ok(body)
+}
+
+/// Automatic backups of `path`, newest first.
+pub fn backups(path: &Path) -> anyhow::Result<Vec<(u64, PathBuf)>> {
+    let dir = path.parent().context("Save file has no parent directory")?;
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .context("Invalid save file name")?;
+    let prefix = format!("{file_name}.{BACKUP_TAG}-");
+    let mut backups = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let timestamp = name
+            .strip_prefix(&prefix)
+            .and_then(|s| s.strip_suffix(".bak"))
+            .and_then(|s| s.parse::<u64>().ok());
+        if let Some(timestamp) = timestamp {
+            backups.push((timestamp, entry.path()));
+        }
+    }
+    backups.sort_by_key(|b| std::cmp::Reverse(b.0));
+    Ok(backups)
+}
+
+/// Back up `path` if the newest backup is older than the interval, and drop the oldest ones.
+pub fn rotate_backups(path: &Path, config: &SaveConfig, now: u64) -> anyhow::Result<()> {
+    let mut backups = backups(path)?;
+    let due = match backups.first() {
+        Some((timestamp, _)) => now.saturating_sub(*timestamp) >= config.backup_interval,
+        None => true,
+    };
+    if !due {
+        return Ok(());
+    }
+    let backup = migration::backup(path, BACKUP_TAG)?;
+    debug!("Save backed up to {backup:?}");
+    backups = self::backups(path)?;
+    for (_, old) in backups.iter().skip(config.keep_backups) {
+        std::fs::remove_file(old).with_context(|| format!("Failed to remove {old:?}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::save::temp_dir;
+
+    fn config(keep_backups: usize) -> SaveConfig {
+        SaveConfig {
+            format: SaveFormat::Ron,
+            keep_backups,
+            backup_interval: 60,
+        }
+    }
+
+    /// A save with a fake backup at each timestamp.
+    fn save_with_backups(dir: &Path, timestamps: &[u64]) -> PathBuf {
+        let path = dir.join("save.ron");
+        std::fs::write(&path, "()").unwrap();
+        for timestamp in timestamps {
+            let backup = dir.join(format!("save.ron.{BACKUP_TAG}-{timestamp}.bak"));
+            std::fs::write(backup, "()").unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn checksum_round_trip() {
+        let dir = temp_dir("checksum");
+        let path = dir.join("save.ron");
+        write_atomic(&path, b"(version: 2)").unwrap();
+        let data = std::fs::read(&path).unwrap();
+        assert_eq!(verify_checksum(&data).unwrap(), b"(version: 2)");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn formats_use_their_own_temp_file() {
+        let ron = tmp_path(Path::new("slot/save.ron")).unwrap();
+        let bin = tmp_path(Path::new("slot/save.bin")).unwrap();
+        assert_eq!(ron, Path::new("slot/save.ron.tmp"));
+        assert_ne!(ron, bin);
+    }
+
+    #[test]
+    fn checksum_rejects_tampered_data() {
+        let data = format!(
+            "{CHECKSUM_PREFIX}{:016x}\n(gold: 10)",
+            checksum(b"(gold: 10)")
+        );
+        let tampered = data.replace("10", "99");
+        assert!(verify_checksum(tampered.as_bytes()).is_err());
+        assert!(verify_checksum(format!("{CHECKSUM_PREFIX}zz\n()").as_bytes()).is_err());
+        // Older saves have no checksum line
+        assert_eq!(verify_checksum(b"(gold: 10)").unwrap(), b"(gold: 10)");
+    }
+
+    #[test]
+    fn rotate_keeps_the_newest() {
+        let dir = temp_dir("rotate_keep");
+        let path = save_with_backups(&dir, &[1, 2, 3, 4, 5, 6]);
+        rotate_backups(&path, &config(3), u64::MAX).unwrap();
+        let kept = backups(&path).unwrap();
+        assert_eq!(kept.len(), 3);
+        // The one just made, then the newest old ones
+        assert!(kept[0].0 > 6);
+        assert_eq!([kept[1].0, kept[2].0], [6, 5]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rotate_waits_for_the_interval() {
+        let dir = temp_dir("rotate_interval");
+        let path = save_with_backups(&dir, &[1000]);
+        rotate_backups(&path, &config(5), 1059).unwrap();
+        assert_eq!(backups(&path).unwrap().len(), 1);
+        rotate_backups(&path, &config(5), 1060).unwrap();
+        assert_eq!(backups(&path).unwrap().len(), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
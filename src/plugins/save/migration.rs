@@ -0,0 +1,490 @@
+use anyhow::{bail, Context};
+use bevy::prelude::*;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{saveable::Saveables, Save};
+
+/// Save version written by this build.
+pub const SAVE_VERSION: u32 = 3;
+
+/// Upgrade a save document from `from` to `from + 1`.
+pub struct Migration {
+    pub from: u32,
+    pub desc: &'static str,
+    pub migrate: fn(String) -> anyhow::Result<String>,
+}
+
+/// All migration steps, one for each version bump.
+///
+/// Append a step and bump [`SAVE_VERSION`] whenever a saved type changes in a way
+/// `#[serde(default)]` cannot cover, e.g. a renamed field or enum variant.
+/// The `version` header is updated by [`migrate`], steps only touch the data.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        desc: "Add version header",
+        migrate: add_version_header,
+    },
+    Migration {
+        from: 1,
+        desc: "Key saved components by type name",
+        migrate: key_by_type_name,
+    },
+    Migration {
+        from: 2,
+        desc: "Give the hero the mana pool of saves without mana",
+        migrate: hero_mana,
+    },
+];
+
+fn add_version_header(doc: String) -> anyhow::Result<String> {
+    Ok(doc)
+}
+
+fn key_by_type_name(doc: String) -> anyhow::Result<String> {
+    const RENAMES: &[(&str, &str)] = &[
+        ("objects", "GameObjectType"),
+        ("units", "Unit"),
+        ("transforms", "Transform"),
+        ("resets", "ClearOnReset"),
+        ("blockers", "Blocker"),
+        ("areas", "Area"),
+        ("inventorys", "Inventory"),
+        ("equipments", "Equipment"),
+        ("chests", "Chest"),
+        ("enter_triggers", "EventTrigger_PlayerEnterEvent"),
+        ("die_triggers", "EventTrigger_UnitDieEvent"),
+        ("collision_groupss", "CollisionGroups"),
+        ("animation_states", "AnimationState"),
+    ];
+    rename_data_keys(&doc, RENAMES)
+}
+
+/// Saves without mana had a hero with a full pool, other units have none.
+fn hero_mana(doc: String) -> anyhow::Result<String> {
+    const LEGACY_MANA: &[(&str, &str)] =
+        &[("mp", "100.0"), ("mp_max", "100.0"), ("mp_regen", "2.0")];
+    let (types, units) = match (
+        data_value(&doc, "GameObjectType")?,
+        data_value(&doc, "Unit")?,
+    ) {
+        (Some(types), Some(units)) => (types, units),
+        _ => return Ok(doc),
+    };
+    let player = Regex::new(r#"\(\s*("(?:[^"\\]|\\.)*")\s*\)\s*:\s*Player\b"#)?;
+    let mut inserts = vec![];
+    for caps in player.captures_iter(&doc[types]) {
+        let entry = Regex::new(&format!(
+            r"\(\s*{}\s*\)\s*:\s*(?:\w+\s*)?\(",
+            regex::escape(&caps[1])
+        ))?;
+        let open = match entry.find(&doc[units.clone()]) {
+            Some(m) => units.start + m.end() - 1,
+            None => continue,
+        };
+        let fields = fields(&doc, open)?;
+        let missing = LEGACY_MANA
+            .iter()
+            .filter(|(field, _)| !fields.contains(field))
+            .map(|(field, value)| format!("{field}: {value}, "))
+            .collect::<String>();
+        inserts.push((open + 1, missing));
+    }
+    // Back to front so the offsets stay valid
+    inserts.sort_by_key(|(at, _)| std::cmp::Reverse(*at));
+    let mut doc = doc;
+    for (at, fields) in inserts {
+        doc.insert_str(at, &fields);
+    }
+    Ok(doc)
+}
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    #[serde(default)]
+    version: u32,
+}
+
+/// Read the `version` header without deserializing the whole save.
+///
+/// Saves written before versioning have no header and are version 0.
+pub fn version(doc: &str) -> anyhow::Result<u32> {
+    let header: SaveHeader = ron::de::from_str(doc).context("Failed to read save header")?;
+    Ok(header.version)
+}
+
+/// Run all migration steps needed and deserialize the result.
+pub fn migrate(mut doc: String, saveables: &Saveables) -> anyhow::Result<Save> {
+    let mut version = version(&doc)?;
+    if version > SAVE_VERSION {
+        bail!("Save version {version} is newer than supported version {SAVE_VERSION}");
+    }
+    while version < SAVE_VERSION {
+        let step = MIGRATIONS
+            .iter()
+            .find(|m| m.from == version)
+            .with_context(|| format!("No migration from save version {version}"))?;
+        info!("Migrate save from version {version}: {}", step.desc);
+        doc = (step.migrate)(doc)
+            .with_context(|| format!("Migration from save version {version} failed"))?;
+        version += 1;
+        doc = set_version(&doc, version)?;
+    }
+    Save::from_ron(&doc, saveables)
+        .with_context(|| format!("Failed to deserialize save version {version}"))
+}
+
+fn set_version(doc: &str, version: u32) -> anyhow::Result<String> {
+    let re = Regex::new(r"^\s*\w*\s*\(\s*version\s*:\s*\d+")?;
+    if let Some(m) = re.find(doc) {
+        let head = Regex::new(r"\d+$")?.replace(m.as_str(), version.to_string());
+        return Ok(format!("{head}{}", &doc[m.end()..]));
+    }
+    let re = Regex::new(r"^\s*\w*\s*\(")?;
+    let m = re.find(doc).context("Save is not a RON struct")?;
+    Ok(format!(
+        "{}\n    version: {version},{}",
+        &doc[..m.end()],
+        &doc[m.end()..]
+    ))
+}
+
+/// Strings, comments, brackets and identifiers with an optional `:`, the tokens of RON
+/// the data scanners below look at.
+fn token_regex() -> anyhow::Result<Regex> {
+    Ok(Regex::new(
+        r#""(?:[^"\\]|\\.)*"|//[^\n]*|[(\[{]|[)\]}]|\b([A-Za-z_][A-Za-z0-9_]*)\b(\s*:)?"#,
+    )?)
+}
+
+/// Rename the keys of the top level `data` map.
+///
+/// Nested fields with the same name and string literals are left untouched.
+fn rename_data_keys(doc: &str, renames: &[(&str, &str)]) -> anyhow::Result<String> {
+    let re = token_regex()?;
+    let mut depth = 0;
+    let mut in_data = false;
+    let mut data_depth = None;
+    let doc = re.replace_all(doc, |caps: &Captures| {
+        let token = &caps[0];
+        match token {
+            "(" | "[" | "{" => {
+                depth += 1;
+                if std::mem::take(&mut in_data) {
+                    data_depth = Some(depth);
+                }
+            }
+            ")" | "]" | "}" => {
+                if data_depth == Some(depth) {
+                    data_depth = None;
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+        let (ident, colon) = match (caps.get(1), caps.get(2)) {
+            (Some(ident), Some(colon)) => (ident.as_str(), colon.as_str()),
+            _ => return token.to_string(),
+        };
+        if depth == 1 && ident == "data" {
+            in_data = true;
+        } else if data_depth == Some(depth) {
+            if let Some((_, new)) = renames.iter().find(|(old, _)| *old == ident) {
+                return format!("{new}{colon}");
+            }
+        }
+        token.to_string()
+    });
+    Ok(doc.into_owned())
+}
+
+/// Bytes of the value of `key` in the top level `data` map, brackets included.
+fn data_value(doc: &str, key: &str) -> anyhow::Result<Option<Range<usize>>> {
+    let mut depth = 0;
+    let mut in_data = false;
+    let mut data_depth = None;
+    let mut in_key = false;
+    for caps in token_regex()?.captures_iter(doc) {
+        let token = caps.get(0).context("Empty token")?;
+        match token.as_str() {
+            "(" | "[" | "{" if in_key => {
+                return Ok(Some(token.start()..closing(doc, token.start())?));
+            }
+            "(" | "[" | "{" => {
+                depth += 1;
+                if std::mem::take(&mut in_data) {
+                    data_depth = Some(depth);
+                }
+            }
+            ")" | "]" | "}" => {
+                if data_depth == Some(depth) {
+                    return Ok(None);
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+        if let (Some(ident), Some(_)) = (caps.get(1), caps.get(2)) {
+            if depth == 1 && ident.as_str() == "data" {
+                in_data = true;
+            } else if data_depth == Some(depth) && ident.as_str() == key {
+                in_key = true;
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// End of the brackets opened at `open`, after the closing one.
+fn closing(doc: &str, open: usize) -> anyhow::Result<usize> {
+    let mut depth = 0;
+    for caps in token_regex()?.captures_iter(&doc[open..]) {
+        let token = caps.get(0).context("Empty token")?;
+        match token.as_str() {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => depth -= 1,
+            _ => continue,
+        }
+        if depth == 0 {
+            return Ok(open + token.end());
+        }
+    }
+    bail!("Unclosed bracket at {open}")
+}
+
+/// Names of the fields of the struct opened at `open`.
+fn fields(doc: &str, open: usize) -> anyhow::Result<Vec<&str>> {
+    let end = closing(doc, open)?;
+    let mut depth = 0;
+    let mut fields = vec![];
+    for caps in token_regex()?.captures_iter(&doc[open..end]) {
+        match &caps[0] {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => depth -= 1,
+            _ => {}
+        }
+        if let (1, Some(ident), Some(_)) = (depth, caps.get(1), caps.get(2)) {
+            fields.push(ident.as_str());
+        }
+    }
+    Ok(fields)
+}
+
+/// Copy the save file next to itself as `<name>.<tag>-<timestamp>.bak`.
+pub fn backup(path: &Path, tag: &str) -> anyhow::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .context("Invalid save file name")?;
+    let backup = path.with_file_name(format!("{file_name}.{tag}-{timestamp}.bak"));
+    std::fs::copy(path, &backup)
+        .with_context(|| format!("Failed to copy {path:?} to {backup:?}"))?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashSet;
+    use std::fmt::Debug;
+
+    use super::*;
+    use crate::plugins::{
+        animation::AnimationState,
+        area::{Area, PlayerEnterEvent},
+        blocker::Blocker,
+        chest::Chest,
+        game_world::{GameObjectId, GameObjectType, WorldChunk},
+        item::{Equipment, Inventory, ItemId},
+        save::{ClearOnReset, SaveablePlugin},
+        trigger::{EventTrigger, TriggerAction},
+        unit::{Unit, UnitDieEvent},
+    };
+
+    const V0: &str = include_str!("fixtures/save_v0.ron");
+    const V1: &str = include_str!("fixtures/save_v1.ron");
+    const V2: &str = include_str!("fixtures/save_v2.ron");
+    const V3: &str = include_str!("fixtures/save_v3.ron");
+
+    fn saveables() -> Saveables {
+        let mut app = App::new();
+        app.add_plugin(SaveablePlugin);
+        app.world.remove_resource::<Saveables>().unwrap()
+    }
+
+    fn id(s: &str) -> GameObjectId {
+        GameObjectId(s.to_string())
+    }
+
+    /// Every object with a `T` in one save has the same `T` in the other.
+    fn assert_same<T: PartialEq + Debug + 'static>(saveables: &Saveables, a: &Save, b: &Save) {
+        let key = saveables.key_of::<T>().unwrap();
+        let ids: HashSet<_> = a.data.ids(key).chain(b.data.ids(key)).collect();
+        assert!(!ids.is_empty(), "no {key} in the fixture");
+        for id in ids {
+            assert_eq!(
+                a.data.get::<T>(saveables, id),
+                b.data.get::<T>(saveables, id),
+                "{key} of {id:?}"
+            );
+        }
+    }
+
+    fn assert_same_save(saveables: &Saveables, a: &Save, b: &Save) {
+        assert_eq!(a.version, b.version);
+        assert_eq!(
+            a.data.keys().collect::<Vec<_>>(),
+            b.data.keys().collect::<Vec<_>>()
+        );
+        assert_same::<GameObjectType>(saveables, a, b);
+        assert_same::<Unit>(saveables, a, b);
+        assert_same::<ClearOnReset>(saveables, a, b);
+        assert_same::<Blocker>(saveables, a, b);
+        assert_same::<Area>(saveables, a, b);
+        assert_same::<Inventory>(saveables, a, b);
+        assert_same::<Equipment>(saveables, a, b);
+        assert_same::<Chest>(saveables, a, b);
+        assert_same::<EventTrigger<PlayerEnterEvent>>(saveables, a, b);
+        assert_same::<EventTrigger<UnitDieEvent>>(saveables, a, b);
+        assert_same::<AnimationState>(saveables, a, b);
+        for key in ["Transform", "CollisionGroups"] {
+            let ids: HashSet<_> = a.data.ids(key).chain(b.data.ids(key)).collect();
+            assert!(!ids.is_empty(), "no {key} in the fixture");
+            for id in ids {
+                assert_eq!(
+                    format!("{:?}", a.data.get_data(key, id)),
+                    format!("{:?}", b.data.get_data(key, id)),
+                    "{key} of {id:?}"
+                );
+            }
+        }
+        for object in ["hero", "wolf_1", "blockers", "area_1", "chest_1"] {
+            assert_eq!(a.map.get_chunk(&id(object)), b.map.get_chunk(&id(object)));
+        }
+    }
+
+    #[test]
+    fn fixture_versions() {
+        assert_eq!(version(V0).unwrap(), 0);
+        assert_eq!(version(V1).unwrap(), 1);
+        assert_eq!(version(V2).unwrap(), 2);
+        assert_eq!(version(V3).unwrap(), SAVE_VERSION);
+    }
+
+    #[test]
+    fn current_fixture() {
+        let saveables = saveables();
+        let save = migrate(V3.to_string(), &saveables).unwrap();
+        let hero = id("hero");
+        let unit = save.data.get::<Unit>(&saveables, &hero).unwrap();
+        assert_eq!((unit.hp, unit.hp_max, unit.atk), (80, 100, 12));
+        assert_eq!(
+            save.data.get::<Equipment>(&saveables, &hero),
+            Some(&Equipment {
+                current: 1,
+                weapons: [ItemId::Sword, ItemId::Spear, ItemId::None],
+            })
+        );
+        assert_eq!(
+            save.data.get::<Inventory>(&saveables, &hero).unwrap().items[&ItemId::HpPotion],
+            2
+        );
+        // String literals named like an old field are not renamed
+        assert_eq!(
+            save.data
+                .get::<AnimationState>(&saveables, &hero)
+                .unwrap()
+                .name,
+            "units"
+        );
+        assert_eq!(
+            save.data
+                .get::<EventTrigger<UnitDieEvent>>(&saveables, &id("wolf_1"))
+                .unwrap()
+                .actions,
+            vec![TriggerAction::HideBlocker(id("blockers"))]
+        );
+        assert_eq!(
+            save.data
+                .get::<Blocker>(&saveables, &id("blockers"))
+                .map(|b| b.blocking),
+            Some(true)
+        );
+        assert_eq!(save.map.get_chunk(&hero), Some(&WorldChunk { x: 0, y: -1 }));
+    }
+
+    #[test]
+    fn migrate_v0() {
+        let saveables = saveables();
+        let expected = Save::from_ron(V3, &saveables).unwrap();
+        let save = migrate(V0.to_string(), &saveables).unwrap();
+        assert_same_save(&saveables, &save, &expected);
+    }
+
+    #[test]
+    fn migrate_v1() {
+        let saveables = saveables();
+        let expected = Save::from_ron(V3, &saveables).unwrap();
+        let save = migrate(V1.to_string(), &saveables).unwrap();
+        assert_same_save(&saveables, &save, &expected);
+    }
+
+    #[test]
+    fn migrate_v2() {
+        let saveables = saveables();
+        let expected = Save::from_ron(V3, &saveables).unwrap();
+        let save = migrate(V2.to_string(), &saveables).unwrap();
+        assert_same_save(&saveables, &save, &expected);
+        let wolf = save.data.get::<Unit>(&saveables, &id("wolf_1")).unwrap();
+        assert_eq!((wolf.mp, wolf.mp_max, wolf.mp_regen), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn only_the_hero_gets_legacy_mana() {
+        let doc = r#"(data: (
+            GameObjectType: {("a"): Player, ("b"): Wolf},
+            Unit: {("b"): (hp: 1), ("a"): (hp: 2, mp_max: 50.0, stats: (mp: 1))},
+        ))"#;
+        assert_eq!(
+            hero_mana(doc.to_string()).unwrap(),
+            r#"(data: (
+            GameObjectType: {("a"): Player, ("b"): Wolf},
+            Unit: {("b"): (hp: 1), ("a"): (mp: 100.0, mp_regen: 2.0, hp: 2, mp_max: 50.0, stats: (mp: 1))},
+        ))"#
+        );
+        // No units to give mana to
+        let doc = r#"(data: (GameObjectType: {("a"): Player}))"#;
+        assert_eq!(hero_mana(doc.to_string()).unwrap(), doc);
+    }
+
+    #[test]
+    fn only_data_keys_are_renamed() {
+        let doc = r#"(data: (units: {("a"): (units: 1, chests: "units")}, chests: {}), units: 2)"#;
+        assert_eq!(
+            key_by_type_name(doc.to_string()).unwrap(),
+            r#"(data: (Unit: {("a"): (units: 1, chests: "units")}, Chest: {}), units: 2)"#
+        );
+    }
+
+    #[test]
+    fn set_version_header() {
+        assert_eq!(version(&set_version(V0, 1).unwrap()).unwrap(), 1);
+        assert_eq!(version(&set_version(V1, 2).unwrap()).unwrap(), 2);
+        assert!(set_version("[]", 1).is_err());
+    }
+
+    #[test]
+    fn newer_version_is_refused() {
+        let doc = set_version(V3, SAVE_VERSION + 1).unwrap();
+        assert!(migrate(doc, &saveables()).is_err());
+    }
+}
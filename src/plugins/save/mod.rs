@@ -0,0 +1,528 @@
+use bevy::prelude::*;
+use bevy::tasks::IoTaskPool;
+use bevy_rapier2d::prelude::CollisionGroups;
+use serde::{
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use std::{
+    fmt,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use crate::AppState;
+
+use self::{
+    file::{SaveConfig, SaveFormat},
+    migration::SAVE_VERSION,
+    paths::DataPaths,
+    saveable::{Ecs, EcsSeed, RegisterSaveable, SaveComponent, Saveables},
+    slot::{
+        delete_slot, load_slot, new_game, scan_slots, update_slot_meta, ActiveSlot, DeleteSlot,
+        LoadSlot, NewGame, SaveSlots,
+    },
+};
+
+use super::{
+    animation::AnimationState,
+    area::{Area, PlayerEnterEvent},
+    blocker::Blocker,
+    buff::Buffs,
+    chest::Chest,
+    experience::Experience,
+    game_world::{GameObjectId, GameObjectType, WorldChunk},
+    item::{Equipment, Inventory},
+    spatial_map::SpatialMap,
+    trigger::EventTrigger,
+    unit::{Unit, UnitDieEvent},
+};
+
+pub mod file;
+pub mod migration;
+pub mod paths;
+pub mod saveable;
+pub mod slot;
+pub mod tool;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //
+            .add_startup_system(load_save)
+            .init_resource::<SaveConfig>()
+            .init_resource::<SaveWriter>()
+            .add_system(track_save_generation)
+            // Slot
+            .init_resource::<SaveSlots>()
+            .init_resource::<ActiveSlot>()
+            .add_startup_system(scan_slots)
+            .add_event::<NewGame>()
+            .add_event::<LoadSlot>()
+            .add_event::<DeleteSlot>()
+            .add_system_set(
+                SystemSet::on_update(AppState::Title)
+                    .with_system(new_game)
+                    .with_system(load_slot)
+                    .with_system(delete_slot),
+            )
+            .add_system_set(SystemSet::on_update(AppState::Level).with_system(update_slot_meta))
+            .add_plugin(SaveablePlugin)
+            .add_event::<WriteSaveFile>()
+            .add_system(write_save_file)
+            .add_event::<ClearSave>()
+            .add_system(clear_save);
+    }
+}
+
+/// Components written to the save, also used by the `save_tool` binary to read saves.
+pub struct SaveablePlugin;
+
+impl Plugin for SaveablePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //
+            .register_saveable::<GameObjectType>()
+            .register_saveable::<Unit>()
+            .register_saveable::<Experience>()
+            .register_saveable::<Buffs>()
+            .register_saveable_as::<Transform, (Vec3, Quat, Vec3)>()
+            .add_system(save_spatial_map)
+            .register_type::<ClearOnReset>()
+            .register_saveable::<ClearOnReset>()
+            .register_saveable::<Blocker>()
+            .register_saveable::<Area>()
+            .register_saveable::<Inventory>()
+            .register_saveable::<Equipment>()
+            .register_saveable::<Chest>()
+            .register_saveable::<EventTrigger<PlayerEnterEvent>>()
+            .register_saveable::<EventTrigger<UnitDieEvent>>()
+            .register_saveable_as::<CollisionGroups, (u32, u32)>()
+            .register_saveable::<AnimationState>();
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Save {
+    pub version: u32,
+    pub data: Ecs,
+    pub map: SpatialMap,
+}
+
+impl Default for Save {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            data: Default::default(),
+            map: Default::default(),
+        }
+    }
+}
+
+impl Save {
+    pub fn from_ron(doc: &str, saveables: &Saveables) -> anyhow::Result<Save> {
+        Ok(ron::Options::default().from_str_seed(doc, SaveSeed(saveables))?)
+    }
+
+    /// Put every object with a saved position in the chunk of that position.
+    ///
+    /// Older saves rounded negative positions toward zero and have those objects in the wrong chunk.
+    pub fn reindex_map(&mut self, saveables: &Saveables) {
+        let key = match saveables.key_of::<Transform>() {
+            Some(x) => x,
+            None => return,
+        };
+        let positions = self
+            .data
+            .ids(key)
+            .filter_map(|id| {
+                let (translation, _, _) = self
+                    .data
+                    .get_as::<Transform, (Vec3, Quat, Vec3)>(saveables, id)?;
+                Some((id.clone(), translation.truncate()))
+            })
+            .collect::<Vec<_>>();
+        for (id, pos) in positions {
+            if self.map.get_chunk(&id) != Some(&WorldChunk::new(pos.x, pos.y)) {
+                self.map.update(id, pos.into());
+            }
+        }
+    }
+}
+
+/// Reads a [`Save`], the [`Ecs`] in it needs the [`Saveables`].
+pub struct SaveSeed<'a>(pub &'a Saveables);
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum SaveField {
+    Version,
+    Data,
+    Map,
+    #[serde(other)]
+    Other,
+}
+
+impl<'de> DeserializeSeed<'de> for SaveSeed<'_> {
+    type Value = Save;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Save, D::Error> {
+        deserializer.deserialize_struct("Save", &["version", "data", "map"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for SaveSeed<'_> {
+    type Value = Save;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a save")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Save, A::Error> {
+        let saveables = self.0;
+        let version = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let data = seq
+            .next_element_seed(EcsSeed(saveables))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let map = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        Ok(Save { version, data, map })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Save, A::Error> {
+        let (mut version, mut data, mut map) = (None, None, None);
+        while let Some(field) = access.next_key()? {
+            match field {
+                SaveField::Version => version = Some(access.next_value()?),
+                SaveField::Data => data = Some(access.next_value_seed(EcsSeed(self.0))?),
+                SaveField::Map => map = Some(access.next_value()?),
+                SaveField::Other => {
+                    access.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Save {
+            version: version.ok_or_else(|| de::Error::missing_field("version"))?,
+            data: data.ok_or_else(|| de::Error::missing_field("data"))?,
+            map: map.ok_or_else(|| de::Error::missing_field("map"))?,
+        })
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct SaveBuffer(pub Save);
+
+/// Tracks which generation of `SaveBuffer` is on disk and whether a write is running.
+#[derive(Debug, Default, Resource)]
+pub struct SaveWriter {
+    /// Bumped every frame `SaveBuffer` changed.
+    pub generation: u64,
+    written: Arc<AtomicU64>,
+    writing: Arc<AtomicBool>,
+}
+
+impl SaveWriter {
+    pub fn is_dirty(&self) -> bool {
+        self.written.load(Ordering::Acquire) != self.generation
+    }
+
+    pub fn is_writing(&self) -> bool {
+        self.writing.load(Ordering::Acquire)
+    }
+}
+
+fn track_save_generation(save: Res<SaveBuffer>, mut writer: ResMut<SaveWriter>) {
+    if save.is_changed() {
+        writer.generation += 1;
+    }
+}
+
+pub fn save_spatial_map(
+    query: Query<(&Transform, &GameObjectId), (With<SaveComponent<Transform>>, Changed<Transform>)>,
+    mut save: ResMut<SaveBuffer>,
+) {
+    for (transform, id) in query.iter() {
+        let pos = transform.translation;
+        if save.0.map.get_chunk(id) == Some(&WorldChunk::new(pos.x, pos.y)) {
+            continue;
+        }
+        save.0.map.update(id.clone(), pos.truncate().into())
+    }
+}
+
+pub fn write_ron<T: Serialize>(path: &Path, value: &T) -> anyhow::Result<()> {
+    let pretty = ron::ser::PrettyConfig::new()
+        // .depth_limit(2)
+        .separate_tuple_members(true)
+        .enumerate_arrays(true);
+    let s = ron::ser::to_string_pretty(value, pretty)?;
+    // info!("s: {s:?}");
+    file::write_atomic(path, s.as_bytes())
+}
+
+/// The save is loaded into `SaveBuffer` when a slot is picked on the title screen.
+pub fn load_save(mut commands: Commands, paths: Res<DataPaths>) {
+    if cfg!(debug_assertions) {
+        // Save template
+        let path = paths.assets.join("save/save_template.ron");
+        if let Err(e) = write_ron(&path, &Save::default()) {
+            error!("Failed to write save template: {e:?}");
+        }
+    }
+    if let Err(e) = std::fs::create_dir_all(&paths.save) {
+        error!("Failed to create save directory {:?}: {e:?}", paths.save);
+    }
+    commands.insert_resource(SaveBuffer(Save::default()));
+}
+
+/// Read a save, falling back to the newest valid backup if it is broken,
+/// and starting with a new one if nothing can be read.
+pub fn read_save_file(path: &Path, saveables: &Saveables) -> Save {
+    if path.exists() {
+        match read_save(path, saveables) {
+            Ok(x) => return x,
+            Err(e) => {
+                error!("Failed to load Save: {e:?}");
+                match migration::backup(path, "broken") {
+                    Ok(backup) => error!("Save backed up to {backup:?}"),
+                    Err(e) => error!("Failed to back up Save: {e:?}"),
+                }
+            }
+        }
+    } else {
+        info!("No save file at {path:?}");
+    }
+    let backups = match file::backups(path) {
+        Ok(x) => x,
+        Err(e) => {
+            error!("Failed to list backups: {e:?}");
+            vec![]
+        }
+    };
+    for (_, backup) in backups.iter() {
+        match read_save(backup, saveables) {
+            Ok(x) => {
+                info!("Restored Save from backup {backup:?}");
+                return x;
+            }
+            Err(e) => error!("Failed to load backup {backup:?}: {e:?}"),
+        }
+    }
+    info!("Start with a new save");
+    Save::default()
+}
+
+/// Read and migrate a save, without falling back to backups.
+///
+/// An older version is backed up first, the next write replaces it.
+pub fn read_save(path: &Path, saveables: &Saveables) -> anyhow::Result<Save> {
+    let s = read_save_doc(path)?;
+    let version = migration::version(&s)?;
+    if version < SAVE_VERSION {
+        let backup = migration::backup(path, &format!("v{version}"))?;
+        info!("Save version {version} backed up to {backup:?} before migration");
+    }
+    migrate_save(s, saveables)
+}
+
+/// Read and migrate a save in memory, no file is written.
+pub fn inspect_save(path: &Path, saveables: &Saveables) -> anyhow::Result<Save> {
+    migrate_save(read_save_doc(path)?, saveables)
+}
+
+fn read_save_doc(path: &Path) -> anyhow::Result<String> {
+    let data = std::fs::read(path)?;
+    let body = file::verify_checksum(&data)?;
+    SaveFormat::from_path(path).decode(body)
+}
+
+fn migrate_save(doc: String, saveables: &Saveables) -> anyhow::Result<Save> {
+    let mut save = migration::migrate(doc, saveables)?;
+    save.reindex_map(saveables);
+    Ok(save)
+}
+
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Component, Reflect,
+)]
+pub struct ClearOnReset;
+pub struct ClearSave;
+fn clear_save(
+    mut events: EventReader<ClearSave>,
+    query: Query<&GameObjectId, With<ClearOnReset>>,
+    mut cache: ResMut<SaveBuffer>,
+    mut write_events: EventWriter<WriteSaveFile>,
+    saveables: Res<Saveables>,
+) {
+    for _ in events.iter() {
+        info!("Clear Save");
+        let mut ids = query.iter().cloned().collect::<Vec<_>>();
+        if let Some(key) = saveables.key_of::<ClearOnReset>() {
+            ids.extend(cache.0.data.ids(key).cloned());
+        }
+        for id in ids.iter() {
+            cache.0.data.remove_object(id);
+            cache.0.map.remove(id);
+        }
+        write_events.send(WriteSaveFile);
+    }
+}
+
+pub struct WriteSaveFile;
+/// Serialize and write the save on the IO task pool, only when `SaveBuffer` changed
+/// since the last write and no other write is running.
+pub fn write_save_file(
+    mut events: EventReader<WriteSaveFile>,
+    cache: Res<SaveBuffer>,
+    mut active: ResMut<ActiveSlot>,
+    mut slots: ResMut<SaveSlots>,
+    config: Res<SaveConfig>,
+    writer: Res<SaveWriter>,
+) {
+    if events.iter().count() == 0 || !writer.is_dirty() || writer.is_writing() {
+        return;
+    }
+    let slot = match active.0.as_mut() {
+        Some(x) => x,
+        // Not playing in any slot, e.g. still on the title screen
+        None => return,
+    };
+    // info!("Write save to file...");
+    slot.meta.timestamp = slot::now();
+    // The title screen shows the slot as it was last written
+    slots.update(slot);
+    let save = cache.0.clone();
+    let meta = slot.meta.clone();
+    let meta_path = slot.meta_path();
+    let format = config.format;
+    let path = slot.save_path(format);
+    let config = config.clone();
+    let generation = writer.generation;
+    let written = writer.written.clone();
+    let writing = writer.writing.clone();
+    writing.store(true, Ordering::Release);
+    IoTaskPool::get()
+        .spawn(async move {
+            let result = format
+                .encode(&save)
+                .and_then(|data| file::write_atomic(&path, &data));
+            match result {
+                Ok(_) => {
+                    written.store(generation, Ordering::Release);
+                    if let Err(e) = file::rotate_backups(&path, &config, meta.timestamp) {
+                        error!("Failed to back up Save: {e:?}");
+                    }
+                    if let Err(e) = write_ron(&meta_path, &meta) {
+                        error!("Failed to write slot meta: {e:?}");
+                    }
+                    // Only keep the save in the current format
+                    for other in SaveFormat::ALL.iter().filter(|f| **f != format) {
+                        let other = path.with_extension(other.extension());
+                        if other.exists() {
+                            if let Err(e) = std::fs::remove_file(&other) {
+                                error!("Failed to remove {other:?}: {e:?}");
+                            }
+                        }
+                    }
+                }
+                Err(e) => error!("Failed to write Save: {e:?}"),
+            }
+            writing.store(false, Ordering::Release);
+        })
+        .detach();
+}
+
+/// An empty directory for a test, `name` keeps tests running in parallel apart.
+#[cfg(test)]
+pub(crate) fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("bevy_rpg_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::unit::Unit;
+    use flate2::{write::DeflateEncoder, Compression};
+    use std::io::Write;
+
+    #[test]
+    fn restore_binary_backup() {
+        let dir = temp_dir("binary_backup");
+        let path = dir.join("save.bin");
+        let id = GameObjectId("chest".into());
+        let mut save = Save::default();
+        save.map.update(id.clone(), (-100.0, 30.0));
+        let data = SaveFormat::Binary.encode(&save).unwrap();
+        file::write_atomic(&path, &data).unwrap();
+        file::rotate_backups(&path, &SaveConfig::default(), 0).unwrap();
+
+        let backups = file::backups(&path).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(SaveFormat::from_path(&backups[0].1), SaveFormat::Binary);
+
+        // Flip a byte of the body so the checksum fails
+        let mut broken = std::fs::read(&path).unwrap();
+        *broken.last_mut().unwrap() ^= 0xff;
+        std::fs::write(&path, broken).unwrap();
+        let saveables = Saveables::default();
+        assert!(read_save(&path, &saveables).is_err());
+
+        let restored = read_save_file(&path, &saveables);
+        assert_eq!(restored.version, SAVE_VERSION);
+        assert_eq!(
+            restored.map.get_chunk(&id),
+            Some(&WorldChunk::new(-100.0, 30.0))
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn inspect_makes_no_backup() {
+        let dir = temp_dir("inspect");
+        let path = dir.join("save.ron");
+        std::fs::write(&path, include_str!("fixtures/save_v1.ron")).unwrap();
+        let mut app = App::new();
+        app.add_plugin(SaveablePlugin);
+        let saveables = app.world.resource::<Saveables>();
+
+        let save = inspect_save(&path, saveables).unwrap();
+        assert_eq!(save.version, SAVE_VERSION);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        read_save(&path, saveables).unwrap();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn migrate_binary_save() {
+        let dir = temp_dir("binary_migrate");
+        let path = dir.join("save.bin");
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(include_bytes!("fixtures/save_v1.ron"))
+            .unwrap();
+        file::write_atomic(&path, &encoder.finish().unwrap()).unwrap();
+
+        let mut app = App::new();
+        app.add_plugin(SaveablePlugin);
+        let saveables = app.world.resource::<Saveables>();
+        let save = read_save(&path, saveables).unwrap();
+        assert_eq!(save.version, SAVE_VERSION);
+        let unit = save
+            .data
+            .get::<Unit>(saveables, &GameObjectId("hero".into()));
+        assert_eq!(unit.map(|u| u.hp), Some(80));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
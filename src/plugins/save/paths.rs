@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+/// CLI flag for the save directory, `--save-dir <path>` or `--save-dir=<path>`.
+pub const SAVE_DIR_FLAG: &str = "--save-dir";
+/// Environment variable for the save directory, used when the CLI flag is not given.
+pub const SAVE_DIR_ENV: &str = "GAME_SAVE_DIR";
+const APP_NAME: &str = "bevy_rpg";
+
+/// Where game data is read from and user data is written to.
+///
+/// `assets` ships with the game and is only read at runtime,
+/// everything the player creates goes to `save`.
+#[derive(Debug, Clone, Resource)]
+pub struct DataPaths {
+    pub assets: PathBuf,
+    pub save: PathBuf,
+}
+
+impl DataPaths {
+    pub fn resolve() -> Self {
+        let paths = Self {
+            assets: Self::asset_dir(),
+            save: Self::save_dir(),
+        };
+        info!("Data paths: {paths:?}");
+        paths
+    }
+
+    /// Same lookup as the `AssetServer`: `BEVY_ASSET_ROOT`, `CARGO_MANIFEST_DIR`,
+    /// then the directory of the executable.
+    pub fn asset_dir() -> PathBuf {
+        std::env::var_os("BEVY_ASSET_ROOT")
+            .or_else(|| std::env::var_os("CARGO_MANIFEST_DIR"))
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::current_exe()
+                    .ok()
+                    .and_then(|exe| exe.parent().map(PathBuf::from))
+            })
+            .unwrap_or_default()
+            .join("assets")
+    }
+
+    /// `--save-dir`, then `GAME_SAVE_DIR`, then the platform data directory.
+    fn save_dir() -> PathBuf {
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == SAVE_DIR_FLAG {
+                match args.next() {
+                    Some(dir) => return PathBuf::from(dir),
+                    None => warn!("{SAVE_DIR_FLAG} needs a path"),
+                }
+            } else if let Some(dir) = arg
+                .strip_prefix(SAVE_DIR_FLAG)
+                .and_then(|s| s.strip_prefix('='))
+            {
+                return PathBuf::from(dir);
+            }
+        }
+        if let Some(dir) = std::env::var_os(SAVE_DIR_ENV) {
+            return PathBuf::from(dir);
+        }
+        match dirs::data_dir() {
+            Some(dir) => dir.join(APP_NAME).join("save"),
+            None => {
+                warn!("No platform data directory, save next to the game");
+                Self::asset_dir().with_file_name("save")
+            }
+        }
+    }
+}
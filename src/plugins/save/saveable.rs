@@ -0,0 +1,469 @@
+use bevy::{ecs::system::EntityCommands, prelude::*, utils::get_short_name};
+use bevy_rapier2d::prelude::{CollisionGroups, Group};
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    any::{Any, TypeId},
+    collections::{BTreeMap, HashMap},
+    fmt,
+    marker::PhantomData,
+};
+
+use crate::plugins::game_world::GameObjectId;
+
+use super::SaveBuffer;
+
+pub trait RegisterSaveable {
+    /// Save component `T` of entities with [`SaveComponent<T>`] and restore it in `load_object`.
+    fn register_saveable<T>(&mut self) -> &mut Self
+    where
+        T: Component + Serialize + DeserializeOwned + Clone + PartialEq + fmt::Debug;
+
+    /// Same as [`RegisterSaveable::register_saveable`], for components stored as `D`.
+    fn register_saveable_as<T: Component, D: SaveAs<T>>(&mut self) -> &mut Self;
+}
+
+impl RegisterSaveable for App {
+    fn register_saveable<T>(&mut self) -> &mut Self
+    where
+        T: Component + Serialize + DeserializeOwned + Clone + PartialEq + fmt::Debug,
+    {
+        self.register_saveable_as::<T, T>()
+    }
+
+    fn register_saveable_as<T: Component, D: SaveAs<T>>(&mut self) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(Saveables::default)
+            .register::<T, D>();
+        self.add_system(save_component::<T, D>)
+    }
+}
+
+/// Marks component `T` of a [`GameObjectId`] entity to be written to `SaveBuffer`.
+#[derive(Component)]
+pub struct SaveComponent<T: Component>(PhantomData<T>);
+
+impl<T: Component> Default for SaveComponent<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// How component `T` is stored in the save.
+pub trait SaveAs<T>:
+    Serialize + DeserializeOwned + Clone + PartialEq + fmt::Debug + Send + Sync + 'static
+{
+    fn from_component(component: &T) -> Self;
+    fn into_component(self) -> T;
+}
+
+impl<T> SaveAs<T> for T
+where
+    T: Component + Serialize + DeserializeOwned + Clone + PartialEq + fmt::Debug,
+{
+    fn from_component(component: &T) -> Self {
+        component.clone()
+    }
+    fn into_component(self) -> T {
+        self
+    }
+}
+
+impl SaveAs<Transform> for (Vec3, Quat, Vec3) {
+    fn from_component(t: &Transform) -> Self {
+        (t.translation, t.rotation, t.scale)
+    }
+    fn into_component(self) -> Transform {
+        Transform {
+            translation: self.0,
+            rotation: self.1,
+            scale: self.2,
+        }
+    }
+}
+
+impl SaveAs<CollisionGroups> for (u32, u32) {
+    fn from_component(c: &CollisionGroups) -> Self {
+        (c.memberships.bits(), c.filters.bits())
+    }
+    fn into_component(self) -> CollisionGroups {
+        CollisionGroups::new(
+            Group::from_bits_truncate(self.0),
+            Group::from_bits_truncate(self.1),
+        )
+    }
+}
+
+/// Type erased component data stored in [`Ecs`].
+pub trait SaveData: erased_serde::Serialize + fmt::Debug + Send + Sync + 'static {
+    fn clone_box(&self) -> Box<dyn SaveData>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+// `PartialEq` keeps `Box<dyn SaveData>` itself out of this impl
+impl<T> SaveData for T
+where
+    T: Serialize + Clone + PartialEq + fmt::Debug + Send + Sync + 'static,
+{
+    fn clone_box(&self) -> Box<dyn SaveData> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+erased_serde::serialize_trait_object!(SaveData);
+
+impl Clone for Box<dyn SaveData> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+type DeserializeFn =
+    fn(&mut dyn erased_serde::Deserializer) -> Result<Box<dyn SaveData>, erased_serde::Error>;
+type RestoreFn = fn(&mut EntityCommands, &dyn SaveData);
+
+#[derive(Debug, Clone, Copy)]
+pub struct SaveableInfo {
+    /// Short type name, e.g. `Unit` or `EventTrigger_UnitDieEvent`.
+    ///
+    /// Used as the field name in the save and the file name in `assets/editor/objects/<id>/`.
+    pub key: &'static str,
+    component: TypeId,
+    deserialize: DeserializeFn,
+    restore: RestoreFn,
+}
+
+impl SaveableInfo {
+    pub fn deserialize(
+        &self,
+        deserializer: &mut dyn erased_serde::Deserializer,
+    ) -> Result<Box<dyn SaveData>, erased_serde::Error> {
+        (self.deserialize)(deserializer)
+    }
+
+    /// Insert the component stored in `data` into the entity.
+    pub fn restore(&self, commands: &mut EntityCommands, data: &dyn SaveData) {
+        (self.restore)(commands, data)
+    }
+}
+
+/// Components registered with [`RegisterSaveable`], needed to read an [`Ecs`] back.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct Saveables(Vec<SaveableInfo>);
+
+impl Saveables {
+    pub fn iter(&self) -> impl Iterator<Item = &SaveableInfo> {
+        self.0.iter()
+    }
+
+    pub fn find(&self, key: &str) -> Option<&SaveableInfo> {
+        self.0.iter().find(|info| info.key == key)
+    }
+
+    pub fn key_of<T: 'static>(&self) -> Option<&'static str> {
+        self.0
+            .iter()
+            .find(|info| info.component == TypeId::of::<T>())
+            .map(|info| info.key)
+    }
+
+    fn register<T: Component, D: SaveAs<T>>(&mut self) {
+        if self.key_of::<T>().is_some() {
+            return;
+        }
+        // EventTrigger<UnitDieEvent> -> EventTrigger_UnitDieEvent
+        let key = get_short_name(std::any::type_name::<T>())
+            .replace('<', "_")
+            .replace(['>', ' '], "");
+        self.0.push(SaveableInfo {
+            key: Box::leak(key.into_boxed_str()),
+            component: TypeId::of::<T>(),
+            deserialize: deserialize_data::<D>,
+            restore: restore_component::<T, D>,
+        });
+    }
+}
+
+fn deserialize_data<D: SaveData + DeserializeOwned>(
+    deserializer: &mut dyn erased_serde::Deserializer,
+) -> Result<Box<dyn SaveData>, erased_serde::Error> {
+    Ok(Box::new(erased_serde::deserialize::<D>(deserializer)?))
+}
+
+fn restore_component<T: Component, D: SaveAs<T>>(
+    commands: &mut EntityCommands,
+    data: &dyn SaveData,
+) {
+    if let Some(data) = data.as_any().downcast_ref::<D>() {
+        commands.insert(data.clone().into_component());
+    }
+}
+
+fn save_component<T: Component, D: SaveAs<T>>(
+    query: Query<(&T, &GameObjectId), (With<SaveComponent<T>>, Changed<T>)>,
+    mut save: ResMut<SaveBuffer>,
+    saveables: Res<Saveables>,
+) {
+    for (component, id) in query.iter() {
+        let data = D::from_component(component);
+        if save.0.data.get_as::<T, D>(&saveables, id) == Some(&data) {
+            continue;
+        }
+        save.0.data.insert_as::<T, D>(&saveables, id.clone(), data);
+    }
+}
+
+/// Saved components of all game objects, keyed by [`SaveableInfo::key`].
+#[derive(Debug, Default, Clone, Reflect)]
+#[reflect_value()]
+pub struct Ecs(BTreeMap<&'static str, HashMap<GameObjectId, Box<dyn SaveData>>>);
+
+impl Ecs {
+    pub fn get_data(&self, key: &str, id: &GameObjectId) -> Option<&dyn SaveData> {
+        self.0.get(key)?.get(id).map(|data| data.as_ref())
+    }
+
+    pub fn insert_data(&mut self, key: &'static str, id: GameObjectId, data: Box<dyn SaveData>) {
+        self.0.entry(key).or_default().insert(id, data);
+    }
+
+    pub fn get_as<T: 'static, D: 'static>(
+        &self,
+        saveables: &Saveables,
+        id: &GameObjectId,
+    ) -> Option<&D> {
+        self.get_data(saveables.key_of::<T>()?, id)?
+            .as_any()
+            .downcast_ref()
+    }
+
+    pub fn get_as_mut<T: 'static, D: 'static>(
+        &mut self,
+        saveables: &Saveables,
+        id: &GameObjectId,
+    ) -> Option<&mut D> {
+        self.0
+            .get_mut(saveables.key_of::<T>()?)?
+            .get_mut(id)?
+            .as_any_mut()
+            .downcast_mut()
+    }
+
+    pub fn insert_as<T: 'static, D: SaveData>(
+        &mut self,
+        saveables: &Saveables,
+        id: GameObjectId,
+        data: D,
+    ) {
+        match saveables.key_of::<T>() {
+            Some(key) => self.insert_data(key, id, Box::new(data)),
+            None => error!("{} is not saveable", std::any::type_name::<T>()),
+        }
+    }
+
+    pub fn get<T: 'static>(&self, saveables: &Saveables, id: &GameObjectId) -> Option<&T> {
+        self.get_as::<T, T>(saveables, id)
+    }
+
+    pub fn get_mut<T: 'static>(
+        &mut self,
+        saveables: &Saveables,
+        id: &GameObjectId,
+    ) -> Option<&mut T> {
+        self.get_as_mut::<T, T>(saveables, id)
+    }
+
+    pub fn insert<T: SaveData>(&mut self, saveables: &Saveables, id: GameObjectId, data: T) {
+        self.insert_as::<T, T>(saveables, id, data)
+    }
+
+    /// Ids of all objects with a saved `key` component.
+    pub fn ids(&self, key: &str) -> impl Iterator<Item = &GameObjectId> {
+        self.0.get(key).into_iter().flat_map(|map| map.keys())
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.0.keys().copied()
+    }
+
+    /// Remove all saved components of an object.
+    pub fn remove_object(&mut self, id: &GameObjectId) {
+        for map in self.0.values_mut() {
+            map.remove(id);
+        }
+    }
+
+    pub fn remove_data(&mut self, key: &str, id: &GameObjectId) -> Option<Box<dyn SaveData>> {
+        self.0.get_mut(key)?.remove(id)
+    }
+}
+
+impl Serialize for Ecs {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let maps = self
+            .0
+            .iter()
+            .filter(|(_, map)| !map.is_empty())
+            .collect::<Vec<_>>();
+        // Keys are identifiers, so in RON it reads like a struct: `(Unit: {..}, Chest: {..})`
+        let mut s = serializer.serialize_struct("Ecs", maps.len())?;
+        for (key, map) in maps {
+            s.serialize_field(key, map)?;
+        }
+        s.end()
+    }
+}
+
+/// Reads an [`Ecs`], whose components are only known to the [`Saveables`].
+#[derive(Clone, Copy)]
+pub struct EcsSeed<'a>(pub &'a Saveables);
+
+impl<'de> DeserializeSeed<'de> for EcsSeed<'_> {
+    type Value = Ecs;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Ecs, D::Error> {
+        deserializer.deserialize_struct("Ecs", &[], EcsVisitor(self.0))
+    }
+}
+
+struct Identifier(String);
+
+impl<'de> Deserialize<'de> for Identifier {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IdentifierVisitor;
+        impl<'de> Visitor<'de> for IdentifierVisitor {
+            type Value = Identifier;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a component name")
+            }
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Identifier, E> {
+                Ok(Identifier(v.to_string()))
+            }
+        }
+        deserializer.deserialize_identifier(IdentifierVisitor)
+    }
+}
+
+struct EcsVisitor<'a>(&'a Saveables);
+
+impl<'de> Visitor<'de> for EcsVisitor<'_> {
+    type Value = Ecs;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("saved components by name")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Ecs, A::Error> {
+        let mut ecs = Ecs::default();
+        while let Some(Identifier(key)) = map.next_key()? {
+            match self.0.find(&key) {
+                Some(info) => {
+                    let data = map.next_value_seed(DataMapSeed(info.deserialize))?;
+                    ecs.0.insert(info.key, data);
+                }
+                None => {
+                    warn!("Unknown saved component {key:?}, dropped");
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(ecs)
+    }
+}
+
+struct DataMapSeed(DeserializeFn);
+
+impl<'de> DeserializeSeed<'de> for DataMapSeed {
+    type Value = HashMap<GameObjectId, Box<dyn SaveData>>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for DataMapSeed {
+    type Value = HashMap<GameObjectId, Box<dyn SaveData>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("saved components by object id")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut data = HashMap::new();
+        while let Some(id) = map.next_key::<GameObjectId>()? {
+            let value = map.next_value_seed(DataSeed(self.0))?;
+            data.insert(id, value);
+        }
+        Ok(data)
+    }
+}
+
+struct DataSeed(DeserializeFn);
+
+impl<'de> DeserializeSeed<'de> for DataSeed {
+    type Value = Box<dyn SaveData>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let mut erased = <dyn erased_serde::Deserializer>::erase(deserializer);
+        (self.0)(&mut erased).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::save::{file::SaveFormat, Save};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component)]
+    struct Kept(i32);
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component)]
+    struct Dropped(String, Vec<u8>);
+
+    fn save(saveables: &Saveables) -> Save {
+        let mut save = Save::default();
+        for i in 0..3 {
+            let id = GameObjectId(format!("object_{i}"));
+            save.data
+                .insert(saveables, id.clone(), Dropped(format!("{i}"), vec![1; i]));
+            save.data.insert(saveables, id, Kept(i as i32));
+        }
+        save
+    }
+
+    fn check_unknown_dropped(format: SaveFormat) {
+        let mut writer = Saveables::default();
+        writer.register::<Dropped, Dropped>();
+        writer.register::<Kept, Kept>();
+        let mut reader = Saveables::default();
+        reader.register::<Kept, Kept>();
+
+        let data = format.encode(&save(&writer)).unwrap();
+        let save = Save::from_ron(&format.decode(&data).unwrap(), &reader).unwrap();
+        assert_eq!(save.data.keys().collect::<Vec<_>>(), vec!["Kept"]);
+        for i in 0..3 {
+            let id = GameObjectId(format!("object_{i}"));
+            assert_eq!(save.data.get::<Kept>(&reader, &id), Some(&Kept(i)));
+        }
+    }
+
+    #[test]
+    fn ron_skips_unknown_components() {
+        check_unknown_dropped(SaveFormat::Ron);
+    }
+
+    #[test]
+    fn binary_skips_unknown_components() {
+        check_unknown_dropped(SaveFormat::Binary);
+    }
+}
@@ -0,0 +1,349 @@
+use anyhow::Context;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    plugins::{
+        experience::Experience, game_world::GameObjectId, ldtk::systems::CurrentLevel, player::Hero,
+    },
+    AppState,
+};
+
+use super::{
+    file::SaveFormat, paths::DataPaths, read_save_file, saveable::Saveables, write_ron, Save,
+    SaveBuffer,
+};
+
+pub const MAX_SLOTS: usize = 5;
+
+const SAVE_FILE: &str = "save";
+const META_FILE: &str = "meta.ron";
+
+/// Summary of a slot shown on the title screen, stored next to the save.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlotMeta {
+    pub name: String,
+    /// Seconds played in this slot.
+    pub playtime: f32,
+    pub gold: i32,
+    /// Level of the hero.
+    pub level: i32,
+    /// Identifier of the LDtk level the hero was in.
+    pub ldtk_level: Option<String>,
+    /// Unix time of the last write.
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SaveSlot {
+    /// Directory name under `slots`.
+    pub id: String,
+    pub dir: PathBuf,
+    pub meta: SlotMeta,
+}
+
+impl SaveSlot {
+    pub fn save_path(&self, format: SaveFormat) -> PathBuf {
+        self.dir.join(SAVE_FILE).with_extension(format.extension())
+    }
+
+    /// The save file in whichever format it was last written.
+    pub fn existing_save_path(&self) -> PathBuf {
+        SaveFormat::ALL
+            .iter()
+            .map(|f| self.save_path(*f))
+            .find(|p| p.exists())
+            .unwrap_or_else(|| self.save_path(SaveFormat::Ron))
+    }
+
+    pub fn meta_path(&self) -> PathBuf {
+        self.dir.join(META_FILE)
+    }
+}
+
+/// All slots on disk, most recently saved first.
+#[derive(Debug, Default, Resource)]
+pub struct SaveSlots(pub Vec<SaveSlot>);
+
+impl SaveSlots {
+    pub fn latest(&self) -> Option<&SaveSlot> {
+        self.0.first()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.0.len() >= MAX_SLOTS
+    }
+
+    /// Copy the metadata of a written slot, keeping the most recent first.
+    pub fn update(&mut self, slot: &SaveSlot) {
+        match self.0.iter_mut().find(|s| s.id == slot.id) {
+            Some(s) => s.meta = slot.meta.clone(),
+            None => self.0.push(slot.clone()),
+        }
+        self.0.sort_by_key(|s| std::cmp::Reverse(s.meta.timestamp));
+    }
+
+    fn next_id(&self) -> Option<String> {
+        (1..=MAX_SLOTS)
+            .map(|i| format!("slot_{i}"))
+            .find(|id| self.0.iter().all(|s| &s.id != id))
+    }
+}
+
+/// The slot `SaveBuffer` is loaded from and written to.
+#[derive(Debug, Default, Resource)]
+pub struct ActiveSlot(pub Option<SaveSlot>);
+
+pub struct NewGame {
+    pub name: String,
+}
+pub struct LoadSlot(pub String);
+pub struct DeleteSlot(pub String);
+
+fn slots_dir(paths: &DataPaths) -> PathBuf {
+    paths.save.join("slots")
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn scan_slots(mut slots: ResMut<SaveSlots>, paths: Res<DataPaths>) {
+    if let Err(e) = import_legacy_save(&paths) {
+        error!("Failed to import legacy save: {e:?}");
+    }
+    slots.0 = match read_slots(&paths) {
+        Ok(x) => x,
+        Err(e) => {
+            error!("Failed to read save slots: {e:?}");
+            vec![]
+        }
+    };
+    info!("Found {} save slots", slots.0.len());
+}
+
+fn read_slots(paths: &DataPaths) -> anyhow::Result<Vec<SaveSlot>> {
+    let dir = slots_dir(paths);
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut slots = vec![];
+    for entry in std::fs::read_dir(&dir).with_context(|| format!("Failed to read {dir:?}"))? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let id = entry.file_name().to_string_lossy().to_string();
+        let meta_path = entry.path().join(META_FILE);
+        let meta = match std::fs::read_to_string(&meta_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|s| Ok(ron::de::from_str::<SlotMeta>(&s)?))
+        {
+            Ok(x) => x,
+            Err(e) => {
+                warn!("Failed to read slot meta {meta_path:?}: {e:?}");
+                SlotMeta {
+                    name: id.clone(),
+                    ..Default::default()
+                }
+            }
+        };
+        slots.push(SaveSlot {
+            id,
+            dir: entry.path(),
+            meta,
+        });
+    }
+    slots.sort_by_key(|s| std::cmp::Reverse(s.meta.timestamp));
+    Ok(slots)
+}
+
+/// Copy the single `assets/save/save.ron` from before slots existed into the first slot.
+fn import_legacy_save(paths: &DataPaths) -> anyhow::Result<()> {
+    let legacy = paths.assets.join("save/save.ron");
+    if !legacy.exists() || slots_dir(paths).exists() {
+        return Ok(());
+    }
+    let id = "slot_1".to_string();
+    let slot = SaveSlot {
+        dir: slots_dir(paths).join(&id),
+        id,
+        meta: SlotMeta {
+            name: "Slot 1".to_string(),
+            timestamp: now(),
+            ..Default::default()
+        },
+    };
+    std::fs::create_dir_all(&slot.dir)?;
+    std::fs::copy(&legacy, slot.save_path(SaveFormat::Ron))
+        .with_context(|| format!("Failed to copy {legacy:?}"))?;
+    write_ron(&slot.meta_path(), &slot.meta)?;
+    info!("Imported {legacy:?} into {:?}", slot.id);
+    Ok(())
+}
+
+pub fn new_game(
+    mut events: EventReader<NewGame>,
+    mut slots: ResMut<SaveSlots>,
+    mut active: ResMut<ActiveSlot>,
+    mut save: ResMut<SaveBuffer>,
+    mut app_state: ResMut<State<AppState>>,
+    paths: Res<DataPaths>,
+) {
+    // Only the last request counts, the state can change once
+    let e = match events.iter().last() {
+        Some(x) => x,
+        None => return,
+    };
+    let id = match slots.next_id() {
+        Some(x) => x,
+        None => {
+            error!("All {MAX_SLOTS} save slots are used");
+            return;
+        }
+    };
+    let name = match e.name.trim() {
+        "" => id.replace("slot_", "Slot "),
+        name => name.to_string(),
+    };
+    let slot = SaveSlot {
+        dir: slots_dir(&paths).join(&id),
+        id,
+        meta: SlotMeta {
+            name,
+            timestamp: now(),
+            ..Default::default()
+        },
+    };
+    // The save itself is written by the next auto save
+    save.0 = Save::default();
+    let result = std::fs::create_dir_all(&slot.dir)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| write_ron(&slot.meta_path(), &slot.meta));
+    if let Err(e) = result {
+        error!("Failed to create save slot {:?}: {e:?}", slot.id);
+        return;
+    }
+    info!("New game in slot {:?}", slot.id);
+    slots.0.insert(0, slot.clone());
+    active.0 = Some(slot);
+    app_state.set(AppState::Level).unwrap();
+}
+
+pub fn load_slot(
+    mut events: EventReader<LoadSlot>,
+    slots: Res<SaveSlots>,
+    mut active: ResMut<ActiveSlot>,
+    mut save: ResMut<SaveBuffer>,
+    saveables: Res<Saveables>,
+    mut app_state: ResMut<State<AppState>>,
+) {
+    let id = match events.iter().last() {
+        Some(LoadSlot(x)) => x,
+        None => return,
+    };
+    let slot = match slots.0.iter().find(|s| &s.id == id) {
+        Some(x) => x,
+        None => {
+            error!("Save slot {id:?} not found");
+            return;
+        }
+    };
+    info!("Load slot {id:?}");
+    save.0 = read_save_file(&slot.existing_save_path(), &saveables);
+    active.0 = Some(slot.clone());
+    app_state.set(AppState::Level).unwrap();
+}
+
+pub fn delete_slot(
+    mut events: EventReader<DeleteSlot>,
+    mut slots: ResMut<SaveSlots>,
+    mut active: ResMut<ActiveSlot>,
+) {
+    for DeleteSlot(id) in events.iter() {
+        let slot = match slots.0.iter().position(|s| &s.id == id) {
+            Some(index) => slots.0.remove(index),
+            None => continue,
+        };
+        info!("Delete slot {id:?}");
+        // Stop auto saves from writing into the deleted slot
+        if active.0.as_ref().map(|s| &s.id) == Some(id) {
+            active.0 = None;
+        }
+        if let Err(e) = std::fs::remove_dir_all(&slot.dir) {
+            error!("Failed to delete save slot {id:?}: {e:?}");
+        }
+    }
+}
+
+/// Keep the active slot's metadata up to date while playing.
+pub fn update_slot_meta(
+    time: Res<Time>,
+    mut active: ResMut<ActiveSlot>,
+    hero_q: Query<(&Hero, Option<&GameObjectId>)>,
+    save: Res<SaveBuffer>,
+    saveables: Res<Saveables>,
+    current_level: Res<CurrentLevel>,
+    level_q: Query<&Handle<LdtkLevel>>,
+    ldtk_levels: Res<Assets<LdtkLevel>>,
+) {
+    let meta = match active.0.as_mut() {
+        Some(slot) => &mut slot.meta,
+        None => return,
+    };
+    meta.playtime += time.delta_seconds();
+    if let Ok((hero, id)) = hero_q.get_single() {
+        meta.gold = hero.gold;
+        // Same level as the saved hero
+        let exp = id.and_then(|id| save.0.data.get::<Experience>(&saveables, id));
+        if let Some(exp) = exp {
+            meta.level = exp.level;
+        }
+    }
+    for handle in level_q.iter() {
+        if let Some(ldtk_level) = ldtk_levels.get(handle) {
+            let level = &ldtk_level.level;
+            if current_level.0.is_match(&0, level)
+                && meta.ldtk_level.as_ref() != Some(&level.identifier)
+            {
+                meta.ldtk_level = Some(level.identifier.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(id: &str, timestamp: u64) -> SaveSlot {
+        SaveSlot {
+            id: id.to_string(),
+            dir: PathBuf::from(id),
+            meta: SlotMeta {
+                timestamp,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn update_moves_the_written_slot_first() {
+        let mut slots = SaveSlots(vec![slot("slot_1", 20), slot("slot_2", 10)]);
+        let mut written = slot("slot_2", 30);
+        written.meta.gold = 50;
+        slots.update(&written);
+        assert_eq!(slots.0.len(), 2);
+        assert_eq!(slots.latest().unwrap().id, "slot_2");
+        assert_eq!(slots.latest().unwrap().meta.gold, 50);
+    }
+}
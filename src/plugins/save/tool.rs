@@ -0,0 +1,383 @@
+//! Commands of the `save_tool` binary.
+//!
+//! Saves are only read with [`inspect_save`], so nothing is written next to them unless a
+//! command stores its result.
+
+use anyhow::{bail, Context};
+use bevy::prelude::*;
+use std::{
+    collections::BTreeSet,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::plugins::{
+    game_world::{GameObjectId, GameObjectType},
+    scene_editor::scene_loader::SceneRes,
+};
+
+use super::{
+    file::{write_atomic, SaveFormat},
+    inspect_save, migration,
+    saveable::{SaveData, Saveables},
+    Save,
+};
+
+fn load(saveables: &Saveables, path: &Path) -> anyhow::Result<Save> {
+    inspect_save(path, saveables).with_context(|| format!("Failed to read {path:?}"))
+}
+
+/// Write to `out`, or back up and overwrite `path` if there is no `out`.
+fn store(
+    out: &mut dyn Write,
+    save: &Save,
+    path: &Path,
+    save_out: Option<&Path>,
+) -> anyhow::Result<PathBuf> {
+    let save_out = match save_out {
+        Some(x) => x.to_path_buf(),
+        None => {
+            let backup = migration::backup(path, "save_tool")?;
+            writeln!(out, "Backed up to {backup:?}")?;
+            path.to_path_buf()
+        }
+    };
+    let data = SaveFormat::from_path(&save_out).encode(save)?;
+    write_atomic(&save_out, &data)?;
+    Ok(save_out)
+}
+
+/// All object ids with any saved component, sorted.
+fn object_ids(save: &Save) -> BTreeSet<String> {
+    save.data
+        .keys()
+        .flat_map(|key| save.data.ids(key))
+        .chain(save.map.ids())
+        .map(|id| id.0.clone())
+        .collect()
+}
+
+fn to_ron(data: &dyn SaveData) -> String {
+    ron::to_string(data).unwrap_or_else(|e| format!("<{e}>"))
+}
+
+pub fn list(
+    out: &mut dyn Write,
+    saveables: &Saveables,
+    path: &Path,
+    ty: Option<&str>,
+) -> anyhow::Result<()> {
+    let save = load(saveables, path)?;
+    writeln!(out, "Save version {}", save.version)?;
+    let mut count = 0;
+    for id in object_ids(&save) {
+        let id = GameObjectId(id);
+        let object_type = save.data.get::<GameObjectType>(saveables, &id);
+        let type_name = object_type.map_or("-".to_string(), |t| t.to_string());
+        if ty.is_some_and(|ty| ty != type_name) {
+            continue;
+        }
+        let chunk = match save.map.get_chunk(&id) {
+            Some(c) => format!("({}, {})", c.x, c.y),
+            None => "-".to_string(),
+        };
+        let keys = save
+            .data
+            .keys()
+            .filter(|key| save.data.get_data(key, &id).is_some())
+            .collect::<Vec<_>>();
+        writeln!(
+            out,
+            "{:<40} {type_name:<12} {chunk:<10} {}",
+            id.0,
+            keys.join(", ")
+        )?;
+        count += 1;
+    }
+    writeln!(out, "{count} objects")?;
+    Ok(())
+}
+
+pub fn diff(
+    out: &mut dyn Write,
+    saveables: &Saveables,
+    path: &Path,
+    other: &Path,
+) -> anyhow::Result<()> {
+    let a = load(saveables, path)?;
+    let b = load(saveables, other)?;
+    let keys = a.data.keys().chain(b.data.keys()).collect::<BTreeSet<_>>();
+    let mut ids = object_ids(&a);
+    ids.extend(object_ids(&b));
+    let mut count = 0;
+    for id in ids {
+        let id = GameObjectId(id);
+        for key in keys.iter() {
+            match (a.data.get_data(key, &id), b.data.get_data(key, &id)) {
+                (Some(x), Some(y)) => {
+                    let (x, y) = (to_ron(x), to_ron(y));
+                    if x != y {
+                        writeln!(out, "~ {} {key}: {x} -> {y}", id.0)?;
+                        count += 1;
+                    }
+                }
+                (Some(x), None) => {
+                    writeln!(out, "- {} {key}: {}", id.0, to_ron(x))?;
+                    count += 1;
+                }
+                (None, Some(y)) => {
+                    writeln!(out, "+ {} {key}: {}", id.0, to_ron(y))?;
+                    count += 1;
+                }
+                (None, None) => {}
+            }
+        }
+        let (x, y) = (a.map.get_chunk(&id), b.map.get_chunk(&id));
+        if x != y {
+            writeln!(out, "~ {} chunk: {x:?} -> {y:?}", id.0)?;
+            count += 1;
+        }
+    }
+    writeln!(out, "{count} differences")?;
+    Ok(())
+}
+
+/// `editor` holds the default objects, see [`read_ldtk_objects`](crate::plugins::ldtk::entities::read_ldtk_objects).
+pub fn clean(
+    out: &mut dyn Write,
+    saveables: &Saveables,
+    editor: &SceneRes,
+    path: &Path,
+    save_out: Option<&Path>,
+) -> anyhow::Result<()> {
+    let mut save = load(saveables, path)?;
+    let transform = saveables
+        .key_of::<Transform>()
+        .context("Transform is not saveable")?;
+    // Positions of objects that cannot be spawned, the type is in neither the save nor the editor
+    let orphans = save
+        .data
+        .ids(transform)
+        .filter(|id| {
+            save.data.get::<GameObjectType>(saveables, id).is_none()
+                && editor.ecs.get::<GameObjectType>(saveables, id).is_none()
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    for id in orphans.iter() {
+        writeln!(out, "Drop orphaned transform of {}", id.0)?;
+        save.data.remove_data(transform, id);
+    }
+    // Map entries without a saved position
+    let unplaced = save
+        .map
+        .ids()
+        .filter(|id| save.data.get_data(transform, id).is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+    for id in unplaced.iter() {
+        writeln!(out, "Drop map entry of {}", id.0)?;
+        save.map.remove(id);
+    }
+    let mismatches = save.map.repair();
+    if mismatches > 0 {
+        writeln!(out, "Repaired {mismatches} mismatched map entries")?;
+    }
+    if orphans.is_empty() && unplaced.is_empty() && mismatches == 0 {
+        writeln!(out, "Nothing to clean")?;
+        return Ok(());
+    }
+    let save_out = store(out, &save, path, save_out)?;
+    writeln!(out, "Written to {save_out:?}")?;
+    Ok(())
+}
+
+/// Drop everything saved about an object, the game then spawns it from its `editor` defaults.
+pub fn reset(
+    out: &mut dyn Write,
+    saveables: &Saveables,
+    editor: &SceneRes,
+    path: &Path,
+    id: &str,
+    save_out: Option<&Path>,
+) -> anyhow::Result<()> {
+    let mut save = load(saveables, path)?;
+    let id = GameObjectId(id.to_string());
+    if editor
+        .ecs
+        .keys()
+        .all(|key| editor.ecs.get_data(key, &id).is_none())
+    {
+        bail!("{:?} is not an object of the LDtk project", id.0);
+    }
+    save.data.remove_object(&id);
+    save.map.remove(&id);
+    let save_out = store(out, &save, path, save_out)?;
+    writeln!(out, "Reset {:?}, written to {save_out:?}", id.0)?;
+    Ok(())
+}
+
+pub fn convert(
+    out: &mut dyn Write,
+    saveables: &Saveables,
+    path: &Path,
+    save_out: &Path,
+) -> anyhow::Result<()> {
+    if path == save_out {
+        bail!("Output is the input file");
+    }
+    let save = load(saveables, path)?;
+    store(out, &save, path, Some(save_out))?;
+    writeln!(
+        out,
+        "Converted {path:?} to {save_out:?}, remove the old file so the game loads the new one"
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::{
+        ldtk::{entities::read_ldtk_objects, LDTK_PROJECT},
+        save::{file, temp_dir, SaveablePlugin, SAVE_VERSION},
+        unit::Unit,
+    };
+
+    const V1: &str = include_str!("fixtures/save_v1.ron");
+    const V3: &str = include_str!("fixtures/save_v3.ron");
+    /// The Fox of the LDtk project.
+    const FOX: &str = "275302f0-5110-11ed-9c1f-a3e6552dd7f5";
+
+    fn saveables() -> Saveables {
+        let mut app = App::new();
+        app.add_plugin(SaveablePlugin);
+        app.world.remove_resource::<Saveables>().unwrap()
+    }
+
+    fn editor(saveables: &Saveables) -> SceneRes {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join(LDTK_PROJECT);
+        read_ldtk_objects(&path, saveables).unwrap()
+    }
+
+    fn run(f: impl FnOnce(&mut dyn Write) -> anyhow::Result<()>) -> String {
+        let mut out = vec![];
+        f(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn id(s: &str) -> GameObjectId {
+        GameObjectId(s.to_string())
+    }
+
+    #[test]
+    fn list_filters_by_type() {
+        let dir = temp_dir("tool_list");
+        let path = dir.join("save.ron");
+        std::fs::write(&path, V1).unwrap();
+        let saveables = saveables();
+        let all = run(|out| list(out, &saveables, &path, None));
+        assert!(all.starts_with(&format!("Save version {SAVE_VERSION}")));
+        assert!(all.ends_with("5 objects\n"));
+        let wolves = run(|out| list(out, &saveables, &path, Some("Wolf")));
+        assert!(wolves.contains("wolf_1"));
+        assert!(wolves.ends_with("1 objects\n"));
+        // Migrated in memory only
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn diff_shows_changed_components() {
+        let dir = temp_dir("tool_diff");
+        let (a, b) = (dir.join("a.ron"), dir.join("b.ron"));
+        std::fs::write(&a, V1).unwrap();
+        std::fs::write(&b, V3.replace("hp: 80,", "hp: 70,")).unwrap();
+        let saveables = saveables();
+        let same = run(|out| diff(out, &saveables, &a, &a));
+        assert_eq!(same, "0 differences\n");
+        let changed = run(|out| diff(out, &saveables, &a, &b));
+        assert!(changed.contains("~ hero Unit:"));
+        assert!(changed.ends_with("1 differences\n"));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn clean_drops_orphans() {
+        let dir = temp_dir("tool_clean");
+        let path = dir.join("save.ron");
+        let saveables = saveables();
+        let editor = editor(&saveables);
+        let mut save = Save::from_ron(V3, &saveables).unwrap();
+        let position = (Vec3::new(10.0, 20.0, 0.0), Quat::IDENTITY, Vec3::ONE);
+        // Known to the LDtk project, kept
+        save.data
+            .insert_as::<Transform, _>(&saveables, id(FOX), position);
+        save.map.update(id(FOX), (10.0, 20.0));
+        // Known to nothing
+        save.data
+            .insert_as::<Transform, _>(&saveables, id("gone"), position);
+        save.map.update(id("unplaced"), (10.0, 20.0));
+        store(&mut vec![], &save, &path, Some(&path)).unwrap();
+
+        let output = run(|out| clean(out, &saveables, &editor, &path, None));
+        assert!(output.contains("Drop orphaned transform of gone"));
+        assert!(output.contains("Drop map entry of unplaced"));
+        assert!(!output.contains(FOX));
+        let cleaned = inspect_save(&path, &saveables).unwrap();
+        let transform = saveables.key_of::<Transform>().unwrap();
+        assert!(cleaned.data.get_data(transform, &id("gone")).is_none());
+        assert!(cleaned.data.get_data(transform, &id(FOX)).is_some());
+        assert_eq!(cleaned.map.get_chunk(&id("unplaced")), None);
+        assert_eq!(file::backups(&path).unwrap().len(), 0);
+        assert_eq!(
+            run(|out| clean(out, &saveables, &editor, &path, None)),
+            "Nothing to clean\n"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reset_needs_an_ldtk_object() {
+        let dir = temp_dir("tool_reset");
+        let (path, out_path) = (dir.join("save.ron"), dir.join("out.ron"));
+        let saveables = saveables();
+        let editor = editor(&saveables);
+        let mut save = Save::from_ron(V3, &saveables).unwrap();
+        save.data.insert(&saveables, id(FOX), Unit::default());
+        save.map.update(id(FOX), (10.0, 20.0));
+        store(&mut vec![], &save, &path, Some(&path)).unwrap();
+
+        let mut out = vec![];
+        assert!(reset(&mut out, &saveables, &editor, &path, "wolf_1", None).is_err());
+        run(|out| reset(out, &saveables, &editor, &path, FOX, Some(&out_path)));
+        let reset = inspect_save(&out_path, &saveables).unwrap();
+        assert!(reset.data.get::<Unit>(&saveables, &id(FOX)).is_none());
+        assert_eq!(reset.map.get_chunk(&id(FOX)), None);
+        // Everything else is kept
+        assert!(reset.data.get::<Unit>(&saveables, &id("hero")).is_some());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn convert_between_formats() {
+        let dir = temp_dir("tool_convert");
+        let (ron, bin) = (dir.join("save.ron"), dir.join("save.bin"));
+        std::fs::write(&ron, V1).unwrap();
+        let saveables = saveables();
+        assert!(convert(&mut vec![], &saveables, &ron, &ron).is_err());
+        run(|out| convert(out, &saveables, &ron, &bin));
+        assert!(std::fs::metadata(&bin).unwrap().len() < std::fs::metadata(&ron).unwrap().len());
+        let expected = inspect_save(&ron, &saveables).unwrap();
+        let converted = inspect_save(&bin, &saveables).unwrap();
+        assert_eq!(
+            run(|out| diff(out, &saveables, &ron, &bin)),
+            "0 differences\n"
+        );
+        assert_eq!(converted.version, expected.version);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
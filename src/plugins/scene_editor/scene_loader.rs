@@ -1,8 +1,13 @@
+use anyhow::Context;
 use bevy::prelude::*;
-use std::{fs::File, path::PathBuf};
+use std::path::Path;
 
 use crate::plugins::{
-    game_world::{Ecs, GameObjectId},
+    game_world::GameObjectId,
+    save::{
+        paths::DataPaths,
+        saveable::{Ecs, Saveables},
+    },
     spatial_map::SpatialMap,
 };
 
@@ -22,174 +27,53 @@ pub struct SceneRes {
     pub map: SpatialMap,
 }
 
-fn load_scene(mut commands: Commands) {
+fn load_scene(mut commands: Commands, paths: Res<DataPaths>, saveables: Res<Saveables>) {
+    match read_scene(&paths.assets.join("editor/objects/"), &saveables) {
+        Ok(res) => commands.insert_resource(res),
+        Err(e) => {
+            println!("{e:?}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Read the editor objects, one directory per object with one file per component.
+pub fn read_scene(path: &Path, saveables: &Saveables) -> anyhow::Result<SceneRes> {
     let mut res = SceneRes::default();
-    let path =
-        PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("assets/editor/objects/");
-    for entry in path.read_dir().unwrap() {
-        let entry = entry.unwrap();
+    for entry in path.read_dir()? {
+        let entry = entry?;
         let path = entry.path();
         if path.is_dir() {
-            let object_id = GameObjectId(entry.file_name().into_string().unwrap());
-            for entry in path.read_dir().unwrap() {
-                let entry = entry.unwrap();
+            let object_id = GameObjectId(entry.file_name().to_string_lossy().to_string());
+            for entry in path.read_dir()? {
+                let entry = entry?;
                 let compoment_type = entry.file_name();
 
                 let path = entry.path();
-                let f = File::open(&path).expect("Failed opening file");
-
-                match compoment_type.to_str().unwrap() {
-                    "Transform.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.transforms.insert(object_id.clone(), x);
-                                res.map.update(object_id.clone(), x.0.truncate().into())
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "GameObjectType.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.objects.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "Unit.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.units.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "ClearOnReset.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.resets.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "Blocker.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.blockers.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "Area.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.areas.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "Inventory.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.inventorys.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "Equipment.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.equipments.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "Chest.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.chests.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "EventTrigger_PlayerEnterEvent.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.enter_triggers.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "EventTrigger_UnitDieEvent.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.die_triggers.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "CollisionGroups.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.collision_groupss.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    "AnimationState.ron" => {
-                        match ron::de::from_reader(f) {
-                            Ok(x) => {
-                                res.ecs.animation_states.insert(object_id.clone(), x);
-                            }
-                            Err(e) => {
-                                println!("Failed to load Component({compoment_type:?}): {e}");
-                                std::process::exit(1);
-                            }
-                        };
-                    }
-                    _ => {
+                let key = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or_default();
+                let info = match saveables.find(key) {
+                    Some(x) => x,
+                    None => {
                         error!("Unknown type: {compoment_type:?}");
+                        continue;
                     }
+                };
+                let s = std::fs::read_to_string(&path)?;
+                let mut de = ron::Deserializer::from_str(&s)?;
+                let data = info
+                    .deserialize(&mut <dyn erased_serde::Deserializer>::erase(&mut de))
+                    .with_context(|| {
+                        format!("Failed to load Component({compoment_type:?}) of {object_id:?}")
+                    })?;
+                if let Some(x) = data.as_any().downcast_ref::<(Vec3, Quat, Vec3)>() {
+                    res.map.update(object_id.clone(), x.0.truncate().into())
                 }
+                res.ecs.insert_data(info.key, object_id.clone(), data);
             }
         }
     }
-
-    commands.insert_resource(res);
+    Ok(res)
 }
@@ -2,7 +2,7 @@ use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::game_world::GameObjectId;
+use super::game_world::{GameObjectId, WorldChunk};
 
 /// Tile height and width
 pub const TILE_SIZE: i32 = 64;
@@ -12,18 +12,47 @@ pub const CHUNK_SIZE: i32 = 8;
 #[derive(Debug, Default, Clone, Serialize, Deserialize, Reflect)]
 #[reflect_value()]
 pub struct SpatialMap {
-    map: HashMap<(i32, i32), Vec<GameObjectId>>,
-    r_map: HashMap<GameObjectId, (i32, i32)>,
+    map: HashMap<WorldChunk, Vec<GameObjectId>>,
+    r_map: HashMap<GameObjectId, WorldChunk>,
 }
 
 impl SpatialMap {
-    pub fn get_objects(&self, chunk: &(i32, i32)) -> Option<&Vec<GameObjectId>> {
+    pub fn get_objects(&self, chunk: &WorldChunk) -> Option<&Vec<GameObjectId>> {
         self.map.get(chunk)
     }
-    pub fn get_chunk(&self, id: &GameObjectId) -> Option<&(i32, i32)> {
+    pub fn get_chunk(&self, id: &GameObjectId) -> Option<&WorldChunk> {
         self.r_map.get(id)
     }
 
+    /// Objects in the chunks from `min` to `max`, both inclusive.
+    pub fn get_objects_in_range(
+        &self,
+        min: WorldChunk,
+        max: WorldChunk,
+    ) -> impl Iterator<Item = (&WorldChunk, &GameObjectId)> {
+        self.map
+            .iter()
+            .filter(move |(c, _)| min.x <= c.x && c.x <= max.x && min.y <= c.y && c.y <= max.y)
+            .flat_map(|(c, ids)| ids.iter().map(move |id| (c, id)))
+    }
+
+    /// Objects in the chunks at most `radius` chunks away from `center`.
+    pub fn get_objects_in_radius(
+        &self,
+        center: WorldChunk,
+        radius: i32,
+    ) -> impl Iterator<Item = (&WorldChunk, &GameObjectId)> {
+        let min = WorldChunk {
+            x: center.x - radius,
+            y: center.y - radius,
+        };
+        let max = WorldChunk {
+            x: center.x + radius,
+            y: center.y + radius,
+        };
+        self.get_objects_in_range(min, max)
+    }
+
     pub fn update(&mut self, id: GameObjectId, pos: (f32, f32)) {
         if let Some(map_key) = self.r_map.remove(&id) {
             if let Some(v) = self.map.get_mut(&map_key) {
@@ -31,9 +60,7 @@ impl SpatialMap {
             }
         }
 
-        let x = pos.0 as i32 / TILE_SIZE / CHUNK_SIZE;
-        let y = pos.1 as i32 / TILE_SIZE / CHUNK_SIZE;
-        let key = (x, y);
+        let key = WorldChunk::new(pos.0, pos.1);
         let vec = self.map.entry(key).or_default();
         vec.push(id.clone());
         self.r_map.insert(id.clone(), key);
@@ -46,4 +73,124 @@ impl SpatialMap {
             }
         }
     }
+
+    /// Ids of all objects in the map.
+    pub fn ids(&self) -> impl Iterator<Item = &GameObjectId> {
+        self.r_map.keys()
+    }
+
+    /// Rebuild the chunk lists from the id lookup, which is what `update` and `remove` trust.
+    ///
+    /// Returns the number of entries that did not match.
+    pub fn repair(&mut self) -> usize {
+        let mut mismatches = 0;
+        for (key, ids) in self.map.iter() {
+            mismatches += ids
+                .iter()
+                .filter(|id| self.r_map.get(*id) != Some(key))
+                .count();
+        }
+        for (id, key) in self.r_map.iter() {
+            if !self.map.get(key).is_some_and(|ids| ids.contains(id)) {
+                mismatches += 1;
+            }
+        }
+        self.map.clear();
+        for (id, key) in self.r_map.iter() {
+            self.map.entry(*key).or_default().push(id.clone());
+        }
+        mismatches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::game_world::WorldTile;
+    use proptest::prelude::*;
+
+    /// Positions a few chunks around the origin, so both signs and the boundaries are hit.
+    const EXTENT: f32 = (TILE_SIZE * CHUNK_SIZE * 4) as f32;
+
+    fn position() -> impl Strategy<Value = (f32, f32)> {
+        (-EXTENT..EXTENT, -EXTENT..EXTENT)
+    }
+
+    /// Positions on or next to a chunk boundary.
+    fn boundary_position() -> impl Strategy<Value = (f32, f32)> {
+        let edge = (-4..=4i32, -1.0..=1.0f32)
+            .prop_map(|(chunk, offset)| (chunk * TILE_SIZE * CHUNK_SIZE) as f32 + offset);
+        (edge.clone(), edge)
+    }
+
+    fn chunk_of(pos: (f32, f32)) -> WorldChunk {
+        WorldChunk {
+            x: (pos.0.floor() as i32).div_euclid(TILE_SIZE * CHUNK_SIZE),
+            y: (pos.1.floor() as i32).div_euclid(TILE_SIZE * CHUNK_SIZE),
+        }
+    }
+
+    fn id(i: usize) -> GameObjectId {
+        GameObjectId(i.to_string())
+    }
+
+    proptest! {
+        #[test]
+        fn from_tile_floors(x in -1000..1000i32, y in -1000..1000i32) {
+            let chunk = WorldChunk::from_tile(WorldTile::new(x, y));
+            prop_assert_eq!(chunk.x, x.div_euclid(CHUNK_SIZE));
+            prop_assert_eq!(chunk.y, y.div_euclid(CHUNK_SIZE));
+            prop_assert!(chunk.x * CHUNK_SIZE <= x && x < (chunk.x + 1) * CHUNK_SIZE);
+            prop_assert!(chunk.y * CHUNK_SIZE <= y && y < (chunk.y + 1) * CHUNK_SIZE);
+        }
+
+        #[test]
+        fn from_position_floors(pos in position()) {
+            let tile = WorldTile::from_position(pos.0, pos.1);
+            prop_assert_eq!(tile.x, (pos.0.floor() as i32).div_euclid(TILE_SIZE));
+            prop_assert_eq!(tile.y, (pos.1.floor() as i32).div_euclid(TILE_SIZE));
+            prop_assert_eq!(WorldChunk::new(pos.0, pos.1), chunk_of(pos));
+        }
+
+        #[test]
+        fn inserted_object_in_own_chunk(pos in prop_oneof![position(), boundary_position()]) {
+            let mut map = SpatialMap::default();
+            map.update(id(0), pos);
+            let chunk = WorldChunk::new(pos.0, pos.1);
+            prop_assert_eq!(map.get_chunk(&id(0)), Some(&chunk));
+            let expected = id(0);
+            let found: Vec<_> = map.get_objects_in_range(chunk, chunk).collect();
+            prop_assert_eq!(found, vec![(&chunk, &expected)]);
+        }
+
+        #[test]
+        fn radius_matches_brute_force(
+            positions in prop::collection::vec(prop_oneof![position(), boundary_position()], 1..32),
+            center in boundary_position(),
+            radius in 0..3i32,
+        ) {
+            let mut map = SpatialMap::default();
+            for (i, pos) in positions.iter().enumerate() {
+                map.update(id(i), *pos);
+            }
+            let center = WorldChunk::new(center.0, center.1);
+
+            let mut found: Vec<_> = map
+                .get_objects_in_radius(center, radius)
+                .map(|(_, id)| id.clone())
+                .collect();
+            found.sort_by(|a, b| a.0.cmp(&b.0));
+            let mut expected: Vec<_> = positions
+                .iter()
+                .enumerate()
+                .filter(|(_, pos)| {
+                    let chunk = chunk_of(**pos);
+                    (chunk.x - center.x).abs() <= radius && (chunk.y - center.y).abs() <= radius
+                })
+                .map(|(i, _)| id(i))
+                .collect();
+            expected.sort_by(|a, b| a.0.cmp(&b.0));
+            prop_assert_eq!(found, expected);
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-#[derive(Debug, PartialEq, Eq, Component, Reflect)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component, Reflect)]
 pub enum Team {
     Player,
     Enemy,
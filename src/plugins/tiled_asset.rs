@@ -6,9 +6,11 @@ use bevy::{
     utils::BoxedFuture,
 };
 use regex::Regex;
-use std::{path::PathBuf, time::Instant};
+use std::time::Instant;
 use tiled::Loader;
 
+use super::save::paths::DataPaths;
+
 pub struct TiledAssetPlugin;
 impl Plugin for TiledAssetPlugin {
     fn build(&self, app: &mut App) {
@@ -38,9 +40,7 @@ impl AssetLoader for TiledAssetLoader {
             let start = Instant::now();
 
             let mut loader = Loader::new();
-            let path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap())
-                .join("assets")
-                .join(load_context.path());
+            let path = DataPaths::asset_dir().join(load_context.path());
             let map = loader.load_tmx_map_from(bytes, path)?;
 
             let re = Regex::new(r"map_x0*(-?\d+)_y0*(-?\d+)\.tmx")?;
@@ -7,7 +7,8 @@ use super::{
     area::{Area, PlayerEnterEvent},
     blocker::Blocker,
     game_world::GameObjectId,
-    save::SaveBuffer,
+    save::{saveable::Saveables, SaveBuffer},
+    scene_editor::scene_loader::SceneRes,
     unit::UnitDieEvent,
 };
 
@@ -27,9 +28,10 @@ impl Plugin for TriggerPlugin {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect, Default)]
 #[reflect_value()]
 pub enum TriggerAction {
+    #[default]
     None,
     ShowBlocker(GameObjectId),
     HideBlocker(GameObjectId),
@@ -49,14 +51,11 @@ impl TriggerAction {
     }
 }
 
-impl Default for TriggerAction {
-    fn default() -> Self {
-        Self::None
-    }
-}
 fn action(
     mut ev: EventReader<TriggerAction>,
     mut save: ResMut<SaveBuffer>,
+    editor: Res<SceneRes>,
+    saveables: Res<Saveables>,
     mut blocker_query: Query<(&mut Blocker, &GameObjectId)>,
     mut area_query: Query<(&mut Area, &GameObjectId)>,
 ) {
@@ -70,17 +69,19 @@ fn action(
                         blocker.blocking = true;
                     }
                 }
-                match save.0.data.blockers.get_mut(target) {
+                match save.0.data.get_mut::<Blocker>(&saveables, target) {
                     Some(blocker) => {
                         blocker.blocking = true;
                     }
                     None => {
-                        save.0.data.blockers.insert(
+                        // Not loaded yet, keep the size from the editor
+                        let blocker = editor.ecs.get::<Blocker>(&saveables, target).cloned();
+                        save.0.data.insert(
+                            &saveables,
                             target.clone(),
                             Blocker {
                                 blocking: true,
-                                hx: 0.0,
-                                hy: 0.0,
+                                ..blocker.unwrap_or_default()
                             },
                         );
                     }
@@ -92,17 +93,19 @@ fn action(
                         blocker.blocking = false;
                     }
                 }
-                match save.0.data.blockers.get_mut(target) {
+                match save.0.data.get_mut::<Blocker>(&saveables, target) {
                     Some(blocker) => {
                         blocker.blocking = false;
                     }
                     None => {
-                        save.0.data.blockers.insert(
+                        // Not loaded yet, keep the size from the editor
+                        let blocker = editor.ecs.get::<Blocker>(&saveables, target).cloned();
+                        save.0.data.insert(
+                            &saveables,
                             target.clone(),
                             Blocker {
                                 blocking: false,
-                                hx: 0.0,
-                                hy: 0.0,
+                                ..blocker.unwrap_or_default()
                             },
                         );
                     }
@@ -114,17 +117,18 @@ fn action(
                         area.disable = true;
                     }
                 }
-                match save.0.data.areas.get_mut(target) {
+                match save.0.data.get_mut::<Area>(&saveables, target) {
                     Some(area) => {
                         area.disable = true;
                     }
                     None => {
-                        save.0.data.areas.insert(
+                        let area = editor.ecs.get::<Area>(&saveables, target).cloned();
+                        save.0.data.insert(
+                            &saveables,
                             target.clone(),
                             Area {
-                                hx: 0.0,
-                                hy: 0.0,
                                 disable: true,
+                                ..area.unwrap_or_default()
                             },
                         );
                     }
@@ -153,7 +157,7 @@ fn event_action<T: bevy::ecs::event::Event + TriggerEvent + Debug + Reflect + Cl
         let entity = e.entity();
         if let Ok(t) = query.get(entity) {
             // debug!("{e:?}, actions: {:?}", t.actions.clone().into_iter());
-            action_ev.send_batch(t.actions.clone().into_iter());
+            action_ev.send_batch(t.actions.clone());
         }
     }
 }
@@ -0,0 +1,235 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use super::unit::Unit;
+
+/// Unit stats as a base value plus modifiers, written into [`Unit`] when they change.
+///
+/// Auras, buffs and levels add [`AttributeModifier`]s through [`AttributeModifierEvent`]
+/// instead of changing `Unit` directly, so removing one restores the stat.
+pub struct AttributePlugin;
+impl Plugin for AttributePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Attribute>()
+            .add_event::<AttributeModifierEvent>()
+            .add_system(attribute_modifier)
+            .add_system(apply_attributes.after(attribute_modifier));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
+pub enum Attribute {
+    HpMax,
+    MpMax,
+    MpRegen,
+    Atk,
+    MovementSpeed,
+    /// Subtracted from every damage taken.
+    Armor,
+    /// Multiplies how fast the basic attack plays, 1.0 is normal.
+    AttackSpeed,
+    /// Percentage.
+    CritChance,
+}
+
+impl Attribute {
+    pub const ALL: [Attribute; 8] = [
+        Attribute::HpMax,
+        Attribute::MpMax,
+        Attribute::MpRegen,
+        Attribute::Atk,
+        Attribute::MovementSpeed,
+        Attribute::Armor,
+        Attribute::AttackSpeed,
+        Attribute::CritChance,
+    ];
+
+    fn of(&self, unit: &Unit) -> f32 {
+        match self {
+            Attribute::HpMax => unit.hp_max as f32,
+            Attribute::MpMax => unit.mp_max,
+            Attribute::MpRegen => unit.mp_regen,
+            Attribute::Atk => unit.atk as f32,
+            Attribute::MovementSpeed => unit.movement_speed,
+            Attribute::Armor => unit.armor as f32,
+            Attribute::AttackSpeed => unit.attack_speed,
+            Attribute::CritChance => unit.crit_chance as f32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeModifier {
+    pub source: Entity,
+    pub attribute: Attribute,
+    pub amount: f32,
+    /// Of the base value.
+    pub percentage: i32,
+}
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct Attributes {
+    base: HashMap<Attribute, f32>,
+    modifiers: Vec<AttributeModifier>,
+}
+
+impl Attributes {
+    /// Use the stats of a newly spawned unit as the base.
+    pub fn from_unit(unit: &Unit) -> Self {
+        Self {
+            base: Attribute::ALL.iter().map(|a| (*a, a.of(unit))).collect(),
+            modifiers: vec![],
+        }
+    }
+
+    pub fn base(&self, attribute: Attribute) -> f32 {
+        self.base.get(&attribute).copied().unwrap_or_default()
+    }
+
+    pub fn get(&self, attribute: Attribute) -> f32 {
+        let base = self.base(attribute);
+        let (amount, percentage) = self
+            .modifiers
+            .iter()
+            .filter(|m| m.attribute == attribute)
+            .fold((0.0, 0), |acc, m| (acc.0 + m.amount, acc.1 + m.percentage));
+        // Stacked debuffs can take more than the whole base
+        (base + amount + base * percentage as f32 / 100.0).max(0.0)
+    }
+
+    pub fn modifiers(&self) -> &[AttributeModifier] {
+        &self.modifiers
+    }
+
+    /// Replaces the modifier of the same source and attribute.
+    fn add(&mut self, modifier: AttributeModifier) {
+        self.modifiers
+            .retain(|m| m.source != modifier.source || m.attribute != modifier.attribute);
+        self.modifiers.push(modifier);
+    }
+
+    fn remove(&mut self, source: Entity) {
+        self.modifiers.retain(|m| m.source != source);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AttributeModifierEvent {
+    Add(Entity, AttributeModifier),
+    /// Remove every modifier of the source (second) from the unit (first).
+    Remove(Entity, Entity),
+}
+fn attribute_modifier(
+    mut events: EventReader<AttributeModifierEvent>,
+    mut query: Query<&mut Attributes>,
+) {
+    for ev in events.iter() {
+        match ev {
+            AttributeModifierEvent::Add(unit, modifier) => {
+                if let Ok(mut attrs) = query.get_mut(*unit) {
+                    attrs.add(modifier.clone());
+                }
+            }
+            AttributeModifierEvent::Remove(unit, source) => {
+                if let Ok(mut attrs) = query.get_mut(*unit) {
+                    attrs.remove(*source);
+                }
+            }
+        }
+    }
+}
+
+/// A loaded `Unit` keeps the stats it was saved with until its attributes change again.
+fn apply_attributes(mut query: Query<(&Attributes, &mut Unit), Changed<Attributes>>) {
+    for (attrs, mut unit) in query.iter_mut() {
+        let mut new = unit.clone();
+        new.hp_max = attrs.get(Attribute::HpMax).round() as i32;
+        new.mp_max = attrs.get(Attribute::MpMax);
+        new.mp_regen = attrs.get(Attribute::MpRegen);
+        new.atk = attrs.get(Attribute::Atk).round() as i32;
+        new.movement_speed = attrs.get(Attribute::MovementSpeed);
+        new.armor = attrs.get(Attribute::Armor).round() as i32;
+        new.attack_speed = attrs.get(Attribute::AttackSpeed);
+        new.crit_chance = attrs.get(Attribute::CritChance).round() as i32;
+        // Raising the max also fills the pool by as much, lowering it only caps it
+        if new.hp_max > unit.hp_max {
+            new.hp += new.hp_max - unit.hp_max;
+        }
+        if new.mp_max > unit.mp_max {
+            new.mp += new.mp_max - unit.mp_max;
+        }
+        new.hp = new.hp.min(new.hp_max);
+        new.mp = new.mp.min(new.mp_max);
+        if *unit != new {
+            *unit = new;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modifier(
+        source: u32,
+        attribute: Attribute,
+        amount: f32,
+        percentage: i32,
+    ) -> AttributeModifier {
+        AttributeModifier {
+            source: Entity::from_raw(source),
+            attribute,
+            amount,
+            percentage,
+        }
+    }
+
+    fn attributes() -> Attributes {
+        Attributes::from_unit(&Unit {
+            hp_max: 100,
+            atk: 10,
+            ..default()
+        })
+    }
+
+    #[test]
+    fn get_base() {
+        let attrs = attributes();
+        assert_eq!(attrs.get(Attribute::HpMax), 100.0);
+        assert_eq!(attrs.get(Attribute::Atk), 10.0);
+        assert_eq!(attrs.get(Attribute::AttackSpeed), 1.0);
+    }
+
+    #[test]
+    fn get_adds_amounts_and_percentages_of_the_base() {
+        let mut attrs = attributes();
+        attrs.add(modifier(1, Attribute::Atk, 5.0, 0));
+        attrs.add(modifier(2, Attribute::Atk, 0.0, 50));
+        attrs.add(modifier(3, Attribute::Atk, 1.0, 20));
+        // 10 + 5 + 1 + 10 * 70%
+        assert_eq!(attrs.get(Attribute::Atk), 23.0);
+        assert_eq!(attrs.get(Attribute::HpMax), 100.0);
+    }
+
+    #[test]
+    fn get_never_goes_below_zero() {
+        let mut attrs = attributes();
+        attrs.add(modifier(1, Attribute::Atk, 0.0, -80));
+        attrs.add(modifier(2, Attribute::Atk, 0.0, -80));
+        assert_eq!(attrs.get(Attribute::Atk), 0.0);
+    }
+
+    #[test]
+    fn add_replaces_the_same_source() {
+        let mut attrs = attributes();
+        attrs.add(modifier(1, Attribute::Atk, 5.0, 0));
+        attrs.add(modifier(1, Attribute::Atk, 2.0, 0));
+        attrs.add(modifier(1, Attribute::HpMax, 10.0, 0));
+        assert_eq!(attrs.get(Attribute::Atk), 12.0);
+        assert_eq!(attrs.get(Attribute::HpMax), 110.0);
+
+        attrs.remove(Entity::from_raw(1));
+        assert!(attrs.modifiers().is_empty());
+        assert_eq!(attrs.get(Attribute::Atk), 10.0);
+    }
+}
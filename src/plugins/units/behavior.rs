@@ -0,0 +1,533 @@
+use anyhow::ensure;
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    plugins::{
+        actions::{
+            action::Skill, setting::TargetSetting, skill_data::SkillSettings, skill_id::SkillId,
+        },
+        team::Team,
+    },
+    res::GameWorldConfig,
+    RAPIER_SCALE,
+};
+
+use super::{
+    threat::{Threat, ThreatEvent, PULL_THREAT},
+    unit::Unit,
+    unit_action::UnitActions,
+    unit_command::UnitCommand,
+    unit_state::UnitState,
+};
+
+const BEHAVIOR_DIR: &str = "data/behaviors";
+/// Meters a fleeing unit runs before deciding again.
+const FLEE_DISTANCE: f32 = 3.0;
+
+/// Units with a [`Brain`] are driven by a behavior tree from `assets/data/behaviors/*.behavior.ron`.
+///
+/// The tree is evaluated from the root every frame the unit is idle or moving, and its actions
+/// write the [`UnitCommand`], so skills are used through the same planner as the player's.
+/// F3 shows the action each unit is running.
+pub struct BehaviorPlugin;
+impl Plugin for BehaviorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<BehaviorTree>()
+            .init_asset_loader::<BehaviorTreeLoader>()
+            .init_resource::<BehaviorTrees>()
+            .init_resource::<BehaviorDebug>()
+            .register_type::<Brain>()
+            .register_type::<BrainText>()
+            .add_startup_system(load_behavior_files)
+            .add_system(update_behavior_trees)
+            .add_system(think.after(update_behavior_trees))
+            .add_system(toggle_debug)
+            .add_system(attach_text)
+            .add_system(update_text.after(think).after(toggle_debug));
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TypeUuid)]
+#[uuid = "c3e1f6a2-7d84-4b95-8e0a-2f5b9d4c7a13"]
+pub struct BehaviorTree {
+    /// Referred to by [`Brain::tree`].
+    pub name: String,
+    pub root: Node,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Node {
+    /// Runs the children in order until one succeeds.
+    Selector(Vec<Node>),
+    /// Runs the children in order until one fails.
+    Sequence(Vec<Node>),
+    /// Succeeds when the child fails.
+    Not(Box<Node>),
+    Condition(Condition),
+    Action(BehaviorAction),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Condition {
+    HasTarget,
+    /// Meters to the target.
+    TargetInRange(f32),
+    /// Percentage of max hp.
+    HpBelow(i32),
+    /// Percentage of max hp of the target.
+    TargetHpBelow(i32),
+    /// The unit has the skill and can pay for it now.
+    SkillReady(SkillId),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BehaviorAction {
+    /// Target the unit with the most threat, or else the nearest living enemy within meters
+    /// and in the leash. The current target is kept while in range.
+    FindTarget(f32),
+    Idle,
+    /// Walk toward the target.
+    Chase,
+    /// Walk away from the target.
+    Flee,
+    /// Use the skill on the target, fails when it is not ready.
+    UseSkill(SkillId),
+    /// Walk back home after losing every target, fails otherwise.
+    ReturnHome,
+}
+
+#[derive(Debug, Clone, Default, Component, Reflect)]
+pub struct Brain {
+    /// Name of the [`BehaviorTree`].
+    pub tree: String,
+    pub target: Option<Entity>,
+    /// The last action that succeeded, shown by the debug overlay.
+    pub active: String,
+}
+
+impl Brain {
+    pub fn new(tree: &str) -> Self {
+        Self {
+            tree: tree.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default)]
+struct BehaviorTreeLoader;
+impl AssetLoader for BehaviorTreeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let tree = ron::de::from_bytes::<BehaviorTree>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(tree));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["behavior.ron"]
+    }
+}
+
+/// Trees by name, and which name each file set for when it is removed.
+#[derive(Debug, Default, Resource)]
+pub struct BehaviorTrees {
+    handles: Vec<HandleUntyped>,
+    names: HashMap<Handle<BehaviorTree>, String>,
+    trees: HashMap<String, BehaviorTree>,
+}
+
+impl BehaviorTrees {
+    pub fn get(&self, name: &str) -> Option<&BehaviorTree> {
+        self.trees.get(name)
+    }
+}
+
+fn load_behavior_files(asset_server: Res<AssetServer>, mut trees: ResMut<BehaviorTrees>) {
+    match asset_server.load_folder(BEHAVIOR_DIR) {
+        Ok(handles) => trees.handles = handles,
+        Err(e) => error!("Failed to load behavior trees in {BEHAVIOR_DIR:?}: {e:?}"),
+    }
+}
+
+fn update_behavior_trees(
+    mut events: EventReader<AssetEvent<BehaviorTree>>,
+    assets: Res<Assets<BehaviorTree>>,
+    asset_server: Res<AssetServer>,
+    mut trees: ResMut<BehaviorTrees>,
+) {
+    for ev in events.iter() {
+        match ev {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                let path = asset_server.get_handle_path(handle);
+                let path = path.as_ref().map(|p| p.path());
+                let tree = match assets.get(handle) {
+                    Some(x) => x,
+                    None => continue,
+                };
+                if let Err(e) = validate(&tree.root) {
+                    error!("Invalid behavior tree {path:?}: {e:?}");
+                    continue;
+                }
+                if let Some(old) = trees.names.insert(handle.clone_weak(), tree.name.clone()) {
+                    if old != tree.name {
+                        trees.trees.remove(&old);
+                    }
+                }
+                info!("Loaded behavior tree {:?} from {path:?}", tree.name);
+                trees.trees.insert(tree.name.clone(), tree.clone());
+            }
+            AssetEvent::Removed { handle } => {
+                if let Some(name) = trees.names.remove(handle) {
+                    info!("Behavior tree {name:?} removed");
+                    trees.trees.remove(&name);
+                }
+            }
+        }
+    }
+}
+
+fn validate(node: &Node) -> anyhow::Result<()> {
+    match node {
+        Node::Selector(children) | Node::Sequence(children) => {
+            ensure!(!children.is_empty(), "{node:?} has no children");
+            for child in children {
+                validate(child)?;
+            }
+        }
+        Node::Not(child) => validate(child)?,
+        Node::Condition(Condition::TargetInRange(range))
+        | Node::Action(BehaviorAction::FindTarget(range)) => {
+            ensure!(*range > 0.0, "{node:?} must have a positive range");
+        }
+        Node::Condition(Condition::HpBelow(p)) | Node::Condition(Condition::TargetHpBelow(p)) => {
+            ensure!((0..=100).contains(p), "{node:?} must be a percentage");
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// A unit the tree can target, gathered once per frame.
+struct Other {
+    entity: Entity,
+    unit: Unit,
+    team: Team,
+    pos: Vec2,
+}
+
+/// What the nodes of one unit's tree can see and change.
+struct Context<'a> {
+    unit: &'a Unit,
+    team: &'a Team,
+    pos: Vec2,
+    /// Skills the unit has and can pay for now.
+    ready: Vec<SkillId>,
+    others: &'a [Other],
+    settings: &'a SkillSettings,
+    threat: Option<&'a Threat>,
+    target: Option<Entity>,
+    /// A target spotted rather than taken from the threat table.
+    pulled: Option<Entity>,
+    command: UnitCommand,
+    active: Option<String>,
+}
+
+impl<'a> Context<'a> {
+    fn run(&mut self, node: &Node) -> bool {
+        match node {
+            Node::Selector(children) => children.iter().any(|n| self.run(n)),
+            Node::Sequence(children) => children.iter().all(|n| self.run(n)),
+            Node::Not(child) => !self.run(child),
+            Node::Condition(condition) => self.check(condition),
+            Node::Action(action) => {
+                let success = self.act(action);
+                if success {
+                    self.active = Some(format!("{action:?}"));
+                }
+                success
+            }
+        }
+    }
+
+    fn check(&self, condition: &Condition) -> bool {
+        match condition {
+            Condition::HasTarget => self.target.is_some(),
+            Condition::TargetInRange(range) => self
+                .target()
+                .is_some_and(|t| t.pos.distance(self.pos) <= range * RAPIER_SCALE),
+            Condition::HpBelow(p) => hp_below(self.unit, *p),
+            Condition::TargetHpBelow(p) => self.target().is_some_and(|t| hp_below(&t.unit, *p)),
+            Condition::SkillReady(id) => self.ready.contains(id),
+        }
+    }
+
+    fn act(&mut self, action: &BehaviorAction) -> bool {
+        match action {
+            BehaviorAction::FindTarget(range) => {
+                if let Some(threat) = self.threat {
+                    if threat.returning {
+                        self.target = None;
+                        return false;
+                    }
+                    if let Some(target) = threat.target() {
+                        self.target = Some(target);
+                        return true;
+                    }
+                }
+                let range = range * RAPIER_SCALE;
+                if self
+                    .target()
+                    .is_some_and(|t| t.pos.distance(self.pos) <= range)
+                {
+                    return true;
+                }
+                let threat = self.threat;
+                self.target = self
+                    .others
+                    .iter()
+                    .filter(|o| self.team.is_enemy(&o.team) && !o.unit.dead)
+                    .filter(|o| threat.is_none_or(|t| t.in_leash(o.pos)))
+                    .map(|o| (o.entity, o.pos.distance(self.pos)))
+                    .filter(|(_, distance)| *distance <= range)
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(e, _)| e);
+                self.pulled = self.target;
+                self.target.is_some()
+            }
+            BehaviorAction::ReturnHome => {
+                let home = match self.threat {
+                    Some(threat) if threat.returning => threat.home,
+                    _ => return false,
+                };
+                let dir = (home - self.pos).normalize_or_zero();
+                self.command = UnitCommand {
+                    action_id: SkillId::MoveTo,
+                    movement_direction: dir,
+                    target_position: Some(home),
+                    target_direction: Some(dir),
+                    ..Default::default()
+                };
+                true
+            }
+            BehaviorAction::Idle => {
+                self.command = UnitCommand::default();
+                true
+            }
+            BehaviorAction::Chase | BehaviorAction::Flee => {
+                let target_pos = match self.target() {
+                    Some(t) => t.pos,
+                    None => return false,
+                };
+                // `move_to` paths to the position around walls
+                let (dir, goal) = match action {
+                    BehaviorAction::Flee => {
+                        let dir = (self.pos - target_pos).normalize_or_zero();
+                        (dir, self.pos + dir * FLEE_DISTANCE * RAPIER_SCALE)
+                    }
+                    _ => ((target_pos - self.pos).normalize_or_zero(), target_pos),
+                };
+                self.command = UnitCommand {
+                    action_id: SkillId::MoveTo,
+                    movement_direction: dir,
+                    target_position: Some(goal),
+                    target_direction: Some(dir),
+                    ..Default::default()
+                };
+                true
+            }
+            BehaviorAction::UseSkill(id) => {
+                if !self.ready.contains(id) {
+                    return false;
+                }
+                let target_pos = self.target().map(|t| t.pos);
+                let needs_target = self
+                    .settings
+                    .get(id)
+                    .is_some_and(|s| s.target != TargetSetting::None);
+                if target_pos.is_none() && needs_target {
+                    return false;
+                }
+                self.command = UnitCommand {
+                    action_id: *id,
+                    movement_direction: Vec2::ZERO,
+                    target_unit: self.target,
+                    target_position: target_pos,
+                    target_direction: target_pos.map(|p| (p - self.pos).normalize_or_zero()),
+                };
+                true
+            }
+        }
+    }
+
+    fn target(&self) -> Option<&Other> {
+        self.target
+            .and_then(|e| self.others.iter().find(|o| o.entity == e))
+    }
+}
+
+fn hp_below(unit: &Unit, percentage: i32) -> bool {
+    unit.hp * 100 < unit.hp_max * percentage
+}
+
+fn think(
+    mut brain_q: Query<(
+        Entity,
+        &mut Brain,
+        &Unit,
+        &Team,
+        &GlobalTransform,
+        &UnitState,
+        &UnitActions,
+        &mut UnitCommand,
+        Option<&Threat>,
+    )>,
+    unit_q: Query<(Entity, &Unit, &Team, &GlobalTransform)>,
+    skill_q: Query<&Skill>,
+    mut threat_events: EventWriter<ThreatEvent>,
+    trees: Res<BehaviorTrees>,
+    config: Res<GameWorldConfig>,
+    settings: Res<SkillSettings>,
+) {
+    if !config.active {
+        return;
+    }
+    let others: Vec<Other> = unit_q
+        .iter()
+        .map(|(entity, unit, team, gt)| Other {
+            entity,
+            unit: unit.clone(),
+            team: *team,
+            pos: gt.translation().truncate(),
+        })
+        .collect();
+    for (entity, mut brain, unit, team, gt, us, ua, mut command, threat) in brain_q.iter_mut() {
+        if unit.dead || !matches!(us.action_id, SkillId::Idle | SkillId::MoveTo) {
+            continue;
+        }
+        let tree = match trees.get(&brain.tree) {
+            Some(x) => x,
+            None => continue,
+        };
+        let ready = ua
+            .actions
+            .iter()
+            .flat_map(|e| skill_q.get(*e))
+            .filter(|s| {
+                settings
+                    .get(&s.action_id)
+                    .is_some_and(|x| s.check(unit, &x.usage).is_ok())
+            })
+            .map(|s| s.action_id)
+            .collect();
+        let mut ctx = Context {
+            unit,
+            team,
+            pos: gt.translation().truncate(),
+            ready,
+            others: &others,
+            settings: &settings,
+            threat,
+            // Forget targets that died or despawned
+            target: brain
+                .target
+                .filter(|e| others.iter().any(|o| o.entity == *e && !o.unit.dead)),
+            pulled: None,
+            command: command.clone(),
+            active: None,
+        };
+        ctx.run(&tree.root);
+
+        if let Some(pulled) = ctx.pulled.filter(|e| Some(*e) == ctx.target) {
+            threat_events.send(ThreatEvent {
+                unit: entity,
+                source: pulled,
+                threat: PULL_THREAT,
+            });
+        }
+        if *command != ctx.command {
+            *command = ctx.command;
+        }
+        let active = ctx.active.unwrap_or_default();
+        if brain.target != ctx.target || brain.active != active {
+            brain.target = ctx.target;
+            brain.active = active;
+        }
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct BehaviorDebug {
+    pub show: bool,
+}
+
+fn toggle_debug(keys: Res<Input<KeyCode>>, mut debug: ResMut<BehaviorDebug>) {
+    if keys.just_pressed(KeyCode::F3) {
+        debug.show = !debug.show;
+    }
+}
+
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct BrainText(pub Entity);
+
+fn attach_text(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    brain_q: Query<Entity, Added<Brain>>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    for entity in brain_q.iter() {
+        commands.entity(entity).with_children(|builder| {
+            builder
+                .spawn(Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 16.0,
+                            color: Color::YELLOW,
+                        },
+                    )
+                    .with_alignment(TextAlignment::BOTTOM_CENTER),
+                    transform: Transform::from_xyz(0.0, 40.0, 1.0),
+                    visibility: Visibility::INVISIBLE,
+                    ..Default::default()
+                })
+                .insert(BrainText(entity));
+        });
+    }
+}
+
+fn update_text(
+    debug: Res<BehaviorDebug>,
+    brain_q: Query<&Brain>,
+    mut text_q: Query<(&mut Text, &mut Visibility, &BrainText)>,
+) {
+    for (mut text, mut visibility, brain_text) in text_q.iter_mut() {
+        if visibility.is_visible != debug.show {
+            visibility.is_visible = debug.show;
+        }
+        if !debug.show {
+            continue;
+        }
+        if let Ok(brain) = brain_q.get(brain_text.0) {
+            let value = format!("{}: {}", brain.tree, brain.active);
+            if let Some(section) = text.sections.get_mut(0) {
+                if section.value != value {
+                    section.value = value;
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,19 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::plugins::visual_effect::{VisualEffect, VisualEffectMarker};
+use crate::{
+    plugins::{actions::skill_id::SkillId, damage::DamageEvent, unit_state::ChangeActionRequest},
+    res::GameWorldConfig,
+};
+
+use super::{
+    attribute::{Attribute, AttributeModifier, AttributeModifierEvent},
+    unit::Unit,
+};
+
+const ICON_SIZE: f32 = 16.0;
+/// Height of the icon row above the unit, the Hp text hangs below it.
+const ICON_OFFSET: f32 = 40.0;
 
 pub struct BuffPlugin;
 impl Plugin for BuffPlugin {
@@ -10,9 +22,14 @@ impl Plugin for BuffPlugin {
             //
             .register_type::<Buff>()
             .register_type::<BuffId>()
-            .register_type::<CreateBuff>()
+            .register_type::<BuffIcon>()
             .add_event::<CreateBuff>()
-            .add_system(create_buff);
+            .add_system(create_buff)
+            .add_event::<DispelBuff>()
+            .add_system(dispel_buff)
+            .add_system(tick_buffs.after(create_buff).after(dispel_buff))
+            .add_system(sync_buffs.after(tick_buffs))
+            .add_system_to_stage(CoreStage::Last, place_icons);
     }
 }
 
@@ -20,39 +37,468 @@ impl Plugin for BuffPlugin {
 pub struct Buff {
     pub level: i32,
     pub duration: Option<f32>,
-    // pub source: Entity,
 }
 
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
 pub enum BuffId {
     AttackUp,
+    Slow,
+    Poison,
+    Burn,
+    Stun,
+    Shield,
+    Haste,
 }
 
-#[derive(Debug, Clone, Reflect)]
+/// What applying the same buff again does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackRule {
+    /// Reset the duration and keep the higher level.
+    Refresh,
+    /// Add a stack up to the max and reset the duration.
+    Stack(u32),
+}
+
+pub struct BuffSetting {
+    pub name: &'static str,
+    pub icon: &'static str,
+    pub color: Color,
+    pub debuff: bool,
+    pub stack: StackRule,
+    /// Percentage per level and stack.
+    pub modifiers: &'static [(Attribute, i32)],
+    /// Seconds between ticks, and damage per level and stack.
+    pub tick: Option<(f32, i32)>,
+    /// Damage absorbed per level.
+    pub shield: i32,
+}
+
+impl BuffId {
+    pub fn setting(&self) -> BuffSetting {
+        match self {
+            BuffId::AttackUp => BuffSetting {
+                name: "Attack Up",
+                icon: "images/particlePack_1.1/PNG (Transparent)/scratch_01.png",
+                color: Color::ORANGE_RED,
+                debuff: false,
+                stack: StackRule::Refresh,
+                modifiers: &[(Attribute::Atk, 20)],
+                tick: None,
+                shield: 0,
+            },
+            BuffId::Slow => BuffSetting {
+                name: "Slow",
+                icon: "images/particlePack_1.1/PNG (Transparent)/dirt_01.png",
+                color: Color::CYAN,
+                debuff: true,
+                stack: StackRule::Refresh,
+                modifiers: &[(Attribute::MovementSpeed, -30)],
+                tick: None,
+                shield: 0,
+            },
+            BuffId::Poison => BuffSetting {
+                name: "Poison",
+                icon: "images/particlePack_1.1/PNG (Transparent)/magic_01.png",
+                color: Color::LIME_GREEN,
+                debuff: true,
+                stack: StackRule::Stack(5),
+                modifiers: &[],
+                tick: Some((1.0, 2)),
+                shield: 0,
+            },
+            BuffId::Burn => BuffSetting {
+                name: "Burn",
+                icon: "images/particlePack_1.1/PNG (Transparent)/fire_01.png",
+                color: Color::ORANGE,
+                debuff: true,
+                stack: StackRule::Refresh,
+                modifiers: &[],
+                tick: Some((0.5, 3)),
+                shield: 0,
+            },
+            BuffId::Stun => BuffSetting {
+                name: "Stun",
+                icon: "images/particlePack_1.1/PNG (Transparent)/star_01.png",
+                color: Color::YELLOW,
+                debuff: true,
+                stack: StackRule::Refresh,
+                modifiers: &[],
+                tick: None,
+                shield: 0,
+            },
+            BuffId::Shield => BuffSetting {
+                name: "Shield",
+                icon: "images/particlePack_1.1/PNG (Transparent)/circle_01.png",
+                color: Color::ALICE_BLUE,
+                debuff: false,
+                stack: StackRule::Refresh,
+                modifiers: &[],
+                tick: None,
+                shield: 20,
+            },
+            BuffId::Haste => BuffSetting {
+                name: "Haste",
+                icon: "images/particlePack_1.1/PNG (Transparent)/light_01.png",
+                color: Color::WHITE,
+                debuff: false,
+                stack: StackRule::Refresh,
+                modifiers: &[(Attribute::MovementSpeed, 30), (Attribute::AttackSpeed, 30)],
+                tick: None,
+                shield: 0,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveBuff {
+    pub id: BuffId,
+    pub level: i32,
+    pub stacks: u32,
+    /// Seconds left, `None` lasts until dispelled.
+    pub remaining: Option<f32>,
+    /// Seconds until the next tick.
+    pub tick: f32,
+    /// Damage the shield can still absorb.
+    pub shield: i32,
+    /// Credited with tick damage.
+    #[serde(skip)]
+    pub source: Option<Entity>,
+    /// The icon, also the source of the modifiers, spawned by `sync_buffs`.
+    #[serde(skip)]
+    pub entity: Option<Entity>,
+    /// Level and stacks the modifiers were sent for.
+    #[serde(skip)]
+    applied: Option<(i32, u32)>,
+}
+
+impl ActiveBuff {
+    fn expired(&self) -> bool {
+        self.remaining.is_some_and(|t| t <= 0.0)
+            || (self.id.setting().shield > 0 && self.shield <= 0)
+    }
+}
+
+/// Buffs and debuffs on a unit.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Component)]
+pub struct Buffs(pub Vec<ActiveBuff>);
+
+impl Buffs {
+    pub fn has(&self, id: BuffId) -> bool {
+        self.0.iter().any(|b| b.id == id && !b.expired())
+    }
+
+    /// Apply a buff, or stack and refresh it following its [`StackRule`] if the unit has it.
+    pub fn add(&mut self, id: BuffId, buff: &Buff, source: Option<Entity>) {
+        let setting = id.setting();
+        match self.0.iter_mut().find(|b| b.id == id) {
+            Some(active) => {
+                if let StackRule::Stack(max) = setting.stack {
+                    active.stacks = (active.stacks + 1).min(max);
+                }
+                active.level = active.level.max(buff.level);
+                active.remaining = match (active.remaining, buff.duration) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    _ => None,
+                };
+                active.shield = active.shield.max(setting.shield * active.level);
+                active.source = source.or(active.source);
+            }
+            None => self.0.push(ActiveBuff {
+                id,
+                level: buff.level,
+                stacks: 1,
+                remaining: buff.duration,
+                tick: setting.tick.map_or(0.0, |(interval, _)| interval),
+                shield: setting.shield * buff.level,
+                source,
+                entity: None,
+                applied: None,
+            }),
+        }
+    }
+
+    /// Let shields take the damage first, returns what is left.
+    pub fn absorb(&mut self, mut damage: i32) -> i32 {
+        for buff in self.0.iter_mut().filter(|b| b.shield > 0) {
+            let absorbed = buff.shield.min(damage);
+            buff.shield -= absorbed;
+            damage -= absorbed;
+        }
+        damage
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct CreateBuff {
     pub unit: Entity,
     pub buff_id: BuffId,
     pub buff: Buff,
+    pub source: Option<Entity>,
 }
-fn create_buff(mut events: EventReader<CreateBuff>, mut commands: Commands) {
+fn create_buff(
+    mut events: EventReader<CreateBuff>,
+    mut query: Query<(&mut Buffs, &mut Unit)>,
+    mut change_events: EventWriter<ChangeActionRequest>,
+) {
     for ev in events.iter() {
-        // debug!("ev: {ev:?}");
-        let visual_effect_marker = match ev.buff_id {
-            BuffId::AttackUp => VisualEffectMarker {
-                visual_effect: VisualEffect::DeadFinger,
-                pos: Default::default(),
-                size: Vec2 { x: 50.0, y: 50.0 },
-                repeat: true,
-                auto_despawn: false,
-                duration: None,
-            },
+        let (mut buffs, mut unit) = match query.get_mut(ev.unit) {
+            Ok(x) => x,
+            Err(_) => continue,
         };
+        if unit.dead {
+            continue;
+        }
+        debug!("{ev:?}");
+        buffs.add(ev.buff_id, &ev.buff, ev.source);
+        if ev.buff_id == BuffId::Stun {
+            unit.stun = unit.stun.max(ev.buff.duration.unwrap_or(f32::MAX));
+            change_events.send(ChangeActionRequest {
+                action_id: SkillId::Stun,
+                command: default(),
+                entity: ev.unit,
+            });
+        }
+    }
+}
 
-        let id = commands.spawn(visual_effect_marker).id();
-        commands
-            .entity(id)
-            .insert(Name::new(format!("Buff {:?} ({id:?})", ev.buff_id)));
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dispel {
+    All,
+    Buffs,
+    Debuffs,
+    Id(BuffId),
+}
+
+#[derive(Debug, Clone)]
+pub struct DispelBuff {
+    pub unit: Entity,
+    pub dispel: Dispel,
+}
+fn dispel_buff(mut events: EventReader<DispelBuff>, mut query: Query<(&mut Buffs, &mut Unit)>) {
+    for ev in events.iter() {
+        if let Ok((mut buffs, mut unit)) = query.get_mut(ev.unit) {
+            for buff in buffs.0.iter_mut() {
+                let debuff = buff.id.setting().debuff;
+                let matched = match ev.dispel {
+                    Dispel::All => true,
+                    Dispel::Buffs => !debuff,
+                    Dispel::Debuffs => debuff,
+                    Dispel::Id(id) => buff.id == id,
+                };
+                if matched {
+                    // Removed by `tick_buffs`
+                    buff.remaining = Some(0.0);
+                    if buff.id == BuffId::Stun {
+                        unit.stun = 0.0;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn tick_buffs(
+    mut query: Query<(Entity, &mut Buffs, &Unit)>,
+    time: Res<Time>,
+    config: Res<GameWorldConfig>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut modifier_events: EventWriter<AttributeModifierEvent>,
+    mut commands: Commands,
+) {
+    if !config.active {
+        return;
+    }
+    let delta = time.delta_seconds();
+    for (entity, mut buffs, unit) in query.iter_mut() {
+        if buffs.0.is_empty() {
+            continue;
+        }
+        // Counting down is not a change worth saving or syncing, a tick or an expiry is
+        let mut changed = false;
+        let list = &mut buffs.bypass_change_detection().0;
+        for buff in list.iter_mut() {
+            if unit.dead {
+                buff.remaining = Some(0.0);
+            }
+            if let Some(remaining) = buff.remaining.as_mut() {
+                *remaining -= delta;
+            }
+            if let Some((interval, damage)) = buff.id.setting().tick {
+                buff.tick -= delta;
+                while buff.tick <= 0.0 && !buff.expired() {
+                    buff.tick += interval;
+                    changed = true;
+                    damage_events.send(DamageEvent {
+                        unit: entity,
+                        source_unit: buff.source,
+                        damage: damage * buff.level * buff.stacks as i32,
+                    });
+                }
+            }
+        }
+        list.retain(|buff| {
+            if !buff.expired() {
+                return true;
+            }
+            changed = true;
+            if let Some(icon) = buff.entity {
+                modifier_events.send(AttributeModifierEvent::Remove(entity, icon));
+                commands.entity(icon).despawn_recursive();
+            }
+            false
+        });
+        if changed {
+            buffs.set_changed();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct BuffIcon {
+    pub unit: Entity,
+    pub index: usize,
+}
+
+/// Spawn icons for new buffs, including ones loaded from a save, and send their modifiers.
+fn sync_buffs(
+    mut query: Query<(Entity, &mut Buffs), Changed<Buffs>>,
+    mut icon_q: Query<(Entity, &mut BuffIcon)>,
+    mut modifier_events: EventWriter<AttributeModifierEvent>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    for (unit, mut buffs) in query.iter_mut() {
+        // Icons of buffs that were replaced as a whole, e.g. by loading
+        for (icon, _) in icon_q.iter().filter(|(icon, b)| {
+            b.unit == unit && buffs.0.iter().all(|buff| buff.entity != Some(*icon))
+        }) {
+            modifier_events.send(AttributeModifierEvent::Remove(unit, icon));
+            commands.entity(icon).despawn_recursive();
+        }
+        for (index, buff) in buffs.0.iter_mut().enumerate() {
+            let setting = buff.id.setting();
+            let icon = match buff.entity {
+                Some(x) => x,
+                None => {
+                    let icon = commands
+                        .spawn(SpriteBundle {
+                            texture: asset_server.load(setting.icon),
+                            sprite: Sprite {
+                                color: setting.color,
+                                custom_size: Some(Vec2::splat(ICON_SIZE)),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .insert(BuffIcon { unit, index })
+                        .insert(Name::new(format!("Buff {:?}", buff.id)))
+                        .id();
+                    commands.entity(unit).add_child(icon);
+                    buff.entity = Some(icon);
+                    icon
+                }
+            };
+            if let Ok((_, mut b)) = icon_q.get_mut(icon) {
+                if b.index != index {
+                    b.index = index;
+                }
+            }
+            if buff.applied == Some((buff.level, buff.stacks)) {
+                continue;
+            }
+            buff.applied = Some((buff.level, buff.stacks));
+            for (attribute, percentage) in setting.modifiers {
+                modifier_events.send(AttributeModifierEvent::Add(
+                    unit,
+                    AttributeModifier {
+                        source: icon,
+                        attribute: *attribute,
+                        amount: 0.0,
+                        percentage: percentage * buff.level * buff.stacks as i32,
+                    },
+                ));
+            }
+        }
+    }
+}
+
+/// Keep the row upright and centered above the unit, like the Hp text.
+fn place_icons(
+    mut icon_q: Query<(&mut GlobalTransform, &BuffIcon)>,
+    unit_q: Query<(&GlobalTransform, &Buffs), Without<BuffIcon>>,
+) {
+    for (mut transform, icon) in icon_q.iter_mut() {
+        if let Ok((unit_transform, buffs)) = unit_q.get(icon.unit) {
+            let count = buffs.0.len() as f32;
+            let x = (icon.index as f32 - (count - 1.0) / 2.0) * (ICON_SIZE + 2.0);
+            let translation = unit_transform.translation() + Vec3::new(x, ICON_OFFSET, 1.0);
+            *transform = GlobalTransform::from_translation(translation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buff(level: i32, duration: Option<f32>) -> Buff {
+        Buff { level, duration }
+    }
+
+    #[test]
+    fn stack_up_to_the_max() {
+        let mut buffs = Buffs::default();
+        for _ in 0..7 {
+            buffs.add(BuffId::Poison, &buff(1, Some(3.0)), None);
+        }
+        assert_eq!(buffs.0.len(), 1);
+        assert_eq!(buffs.0[0].stacks, 5);
+    }
+
+    #[test]
+    fn refresh_keeps_the_higher_level_and_duration() {
+        let mut buffs = Buffs::default();
+        buffs.add(BuffId::Slow, &buff(2, Some(5.0)), None);
+        buffs.add(BuffId::Slow, &buff(1, Some(2.0)), None);
+        assert_eq!(buffs.0.len(), 1);
+        let slow = &buffs.0[0];
+        assert_eq!((slow.level, slow.stacks, slow.remaining), (2, 1, Some(5.0)));
+
+        // Without a duration it lasts until dispelled
+        buffs.add(BuffId::Slow, &buff(1, None), None);
+        assert_eq!(buffs.0[0].remaining, None);
+    }
+
+    #[test]
+    fn different_buffs_do_not_stack() {
+        let mut buffs = Buffs::default();
+        buffs.add(BuffId::Slow, &buff(1, Some(1.0)), None);
+        buffs.add(BuffId::Burn, &buff(1, Some(1.0)), None);
+        assert!(buffs.has(BuffId::Slow) && buffs.has(BuffId::Burn));
+        assert!(!buffs.has(BuffId::Poison));
+    }
+
+    #[test]
+    fn absorb_uses_up_shields() {
+        let mut buffs = Buffs::default();
+        buffs.add(BuffId::Shield, &buff(1, None), None);
+        let shield = buffs.0[0].shield;
+        assert!(shield > 0);
+
+        assert_eq!(buffs.absorb(shield - 1), 0);
+        assert!(buffs.has(BuffId::Shield));
+        assert_eq!(buffs.absorb(10), 9);
+        assert_eq!(buffs.0[0].shield, 0);
+        // A used up shield expires
+        assert!(!buffs.has(BuffId::Shield));
+        assert_eq!(buffs.absorb(10), 10);
+    }
 
-        commands.entity(ev.unit).add_child(id);
+    #[test]
+    fn absorb_without_shield() {
+        let mut buffs = Buffs::default();
+        buffs.add(BuffId::AttackUp, &buff(1, None), None);
+        assert_eq!(buffs.absorb(7), 7);
     }
 }
@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::{
+    player::Hero,
+    visual_effect::{VisualEffect, VisualEffectMarker},
+};
+
+use super::{
+    attribute::{Attribute, AttributeModifier, AttributeModifierEvent},
+    summon::Owner,
+    unit::{KillReward, UnitKilledEvent},
+};
+
+pub const MAX_LEVEL: i32 = 30;
+
+/// Stats gained on every level up.
+const GROWTH: [(Attribute, f32); 3] = [
+    (Attribute::HpMax, 10.0),
+    (Attribute::MpMax, 5.0),
+    (Attribute::Atk, 1.0),
+];
+
+pub struct ExperiencePlugin;
+impl Plugin for ExperiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Experience>()
+            .add_event::<LevelUpEvent>()
+            .add_system(pay_kill_reward)
+            .add_system(level_attributes.after(pay_kill_reward))
+            .add_system(level_up_effect);
+    }
+}
+
+/// Units with this component gain exp from the [`KillReward`] of units they kill.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Component, Reflect)]
+#[serde(default)]
+pub struct Experience {
+    pub level: i32,
+    /// Exp gained since the last level up.
+    pub exp: i32,
+    /// Unspent points for leveling skills.
+    pub skill_points: i32,
+}
+impl Default for Experience {
+    fn default() -> Self {
+        Self {
+            level: 1,
+            exp: 0,
+            skill_points: 0,
+        }
+    }
+}
+
+impl Experience {
+    /// Exp needed to go from `level` to the next one.
+    pub fn exp_to_next(level: i32) -> i32 {
+        10 * level * (level + 1)
+    }
+
+    /// Add exp and return the number of levels gained.
+    fn gain(&mut self, exp: i32) -> i32 {
+        if self.level >= MAX_LEVEL {
+            return 0;
+        }
+        self.exp += exp;
+        let mut levels = 0;
+        while self.level < MAX_LEVEL && self.exp >= Self::exp_to_next(self.level) {
+            self.exp -= Self::exp_to_next(self.level);
+            self.level += 1;
+            self.skill_points += 1;
+            levels += 1;
+        }
+        if self.level >= MAX_LEVEL {
+            self.exp = 0;
+        }
+        levels
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LevelUpEvent {
+    pub unit: Entity,
+    pub level: i32,
+}
+
+fn pay_kill_reward(
+    mut events: EventReader<UnitKilledEvent>,
+    reward_q: Query<&KillReward>,
+    owner_q: Query<&Owner>,
+    mut killer_q: Query<(Option<&mut Experience>, Option<&mut Hero>)>,
+    mut level_up_events: EventWriter<LevelUpEvent>,
+) {
+    for ev in events.iter() {
+        // Summons earn for their owner
+        let killer = match ev.killer {
+            Some(x) => owner_q.get(x).map_or(x, |owner| owner.0),
+            None => continue,
+        };
+        if killer == ev.unit {
+            continue;
+        }
+        let reward = match reward_q.get(ev.unit) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        let (exp, hero) = match killer_q.get_mut(killer) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        if let Some(mut hero) = hero {
+            hero.gold += reward.money;
+        }
+        if let Some(mut exp) = exp {
+            let levels = exp.gain(reward.exp);
+            if levels > 0 {
+                info!("{killer:?} reached level {}", exp.level);
+                level_up_events.send(LevelUpEvent {
+                    unit: killer,
+                    level: exp.level,
+                });
+            }
+        }
+    }
+}
+
+/// The growth is a modifier, so it is reapplied when the level is loaded from a save.
+fn level_attributes(
+    query: Query<(Entity, &Experience), Changed<Experience>>,
+    mut events: EventWriter<AttributeModifierEvent>,
+) {
+    for (entity, exp) in query.iter() {
+        for (attribute, growth) in GROWTH {
+            events.send(AttributeModifierEvent::Add(
+                entity,
+                AttributeModifier {
+                    source: entity,
+                    attribute,
+                    amount: growth * (exp.level - 1) as f32,
+                    percentage: 0,
+                },
+            ));
+        }
+    }
+}
+
+fn level_up_effect(
+    mut events: EventReader<LevelUpEvent>,
+    query: Query<&GlobalTransform>,
+    mut commands: Commands,
+) {
+    for ev in events.iter() {
+        if let Ok(gt) = query.get(ev.unit) {
+            commands.spawn(VisualEffectMarker {
+                visual_effect: VisualEffect::Heal,
+                duration: Some(0.5),
+                repeat: false,
+                size: Vec2 { x: 80.0, y: 80.0 },
+                auto_despawn: false,
+                pos: gt.translation().truncate(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exp_to_next_grows() {
+        assert_eq!(Experience::exp_to_next(1), 20);
+        assert_eq!(Experience::exp_to_next(2), 60);
+        for level in 1..MAX_LEVEL {
+            assert!(Experience::exp_to_next(level + 1) > Experience::exp_to_next(level));
+        }
+    }
+
+    #[test]
+    fn gain_keeps_the_rest() {
+        let mut exp = Experience::default();
+        assert_eq!(exp.gain(19), 0);
+        assert_eq!((exp.level, exp.exp), (1, 19));
+        assert_eq!(exp.gain(6), 1);
+        assert_eq!((exp.level, exp.exp, exp.skill_points), (2, 5, 1));
+    }
+
+    #[test]
+    fn gain_several_levels() {
+        let mut exp = Experience::default();
+        // 20 to level 2, 60 to level 3, 120 to level 4
+        assert_eq!(exp.gain(20 + 60 + 10), 2);
+        assert_eq!((exp.level, exp.exp, exp.skill_points), (3, 10, 2));
+    }
+
+    #[test]
+    fn gain_stops_at_max_level() {
+        let mut exp = Experience {
+            level: MAX_LEVEL - 1,
+            ..default()
+        };
+        assert_eq!(exp.gain(i32::MAX / 2), 1);
+        assert_eq!((exp.level, exp.exp), (MAX_LEVEL, 0));
+        assert_eq!(exp.gain(100), 0);
+        assert_eq!((exp.level, exp.exp), (MAX_LEVEL, 0));
+    }
+}
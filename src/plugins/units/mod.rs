@@ -1,5 +1,10 @@
+pub mod attribute;
+pub mod behavior;
 pub mod buff;
+pub mod experience;
 mod hp_text;
+pub mod summon;
+pub mod threat;
 pub mod unit;
 pub mod unit_action;
 pub mod unit_command;
@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+
+use crate::{
+    plugins::{
+        actions::{base::SummonUnit, skill_id::SkillId},
+        save::ClearOnReset,
+        team::Team,
+        wolf,
+    },
+    res::GameWorldConfig,
+    RAPIER_SCALE,
+};
+
+use super::{
+    unit::{self, Unit, UnitDieEvent},
+    unit_command::UnitCommand,
+    unit_state::UnitState,
+};
+
+/// Owners further than this, in meters, are followed.
+const FOLLOW_RANGE: f32 = 2.0;
+/// Enemies this close to the owner, in meters, are attacked.
+const ASSIST_RANGE: f32 = 6.0;
+
+/// Units spawned by skills of another unit, on its team and gone with it.
+///
+/// They are not saved, loading or resetting the world dismisses them.
+pub struct SummonPlugin;
+impl Plugin for SummonPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Owner>()
+            .register_type::<Summoned>()
+            .add_event::<SummonEvent>()
+            .add_system(summon)
+            .add_system(dismiss.after(summon))
+            .add_system(leash)
+            .add_system(summon_ai);
+    }
+}
+
+/// The unit this one belongs to, kills and assists are credited to it.
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+pub struct Owner(pub Entity);
+
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct Summoned {
+    /// Seconds until dismissed.
+    pub remaining: f32,
+    /// Meters from the owner before it is pulled back.
+    pub leash: f32,
+    /// When it was summoned, the oldest is dismissed first.
+    pub since: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SummonEvent {
+    pub owner: Entity,
+    pub unit: SummonUnit,
+    /// Summons of the owner alive at once.
+    pub max: i32,
+    pub lifetime: f32,
+    pub leash: f32,
+}
+
+fn summon(
+    mut events: EventReader<SummonEvent>,
+    owner_q: Query<(&GlobalTransform, &Team)>,
+    summon_q: Query<(Entity, &Owner, &Summoned)>,
+    time: Res<Time>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    for ev in events.iter() {
+        let (gt, team) = match owner_q.get(ev.owner) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        let mut summons: Vec<_> = summon_q
+            .iter()
+            .filter(|(_, owner, _)| owner.0 == ev.owner)
+            .map(|(entity, _, summoned)| (summoned.since, entity))
+            .collect();
+        summons.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let over = summons.len() as i32 - ev.max.max(1) + 1;
+        for (_, entity) in summons.iter().take(over.max(0) as usize) {
+            commands.entity(*entity).despawn_recursive();
+        }
+
+        // Next to the owner, away from its collider
+        let offset = Vec2::from_angle(summons.len() as f32) * RAPIER_SCALE;
+        let position = gt.translation().truncate() + offset;
+        let spawn = match ev.unit {
+            SummonUnit::Wolf => wolf::wolf_unit(
+                position,
+                *team,
+                vec![
+                    SkillId::Idle,
+                    SkillId::MoveTo,
+                    SkillId::Attack,
+                    SkillId::Stun,
+                    SkillId::Dead,
+                ],
+            ),
+        };
+        let id = unit::spawn_unit(spawn, &mut commands, &asset_server, &mut texture_atlases);
+        commands
+            .entity(id)
+            .insert(Owner(ev.owner))
+            .insert(Summoned {
+                remaining: ev.lifetime,
+                leash: ev.leash,
+                since: time.elapsed_seconds(),
+            })
+            .insert(ClearOnReset);
+        debug!("{:?} summoned {:?} ({id:?})", ev.owner, ev.unit);
+    }
+}
+
+/// Dismiss summons that died, ran out of time or lost their owner.
+fn dismiss(
+    mut events: EventReader<UnitDieEvent>,
+    mut summon_q: Query<(Entity, &Owner, &mut Summoned)>,
+    owner_q: Query<&Unit>,
+    time: Res<Time>,
+    config: Res<GameWorldConfig>,
+    mut commands: Commands,
+) {
+    let died: Vec<Entity> = events.iter().map(|ev| ev.0).collect();
+    for (entity, owner, mut summoned) in summon_q.iter_mut() {
+        if config.active {
+            summoned.remaining -= time.delta_seconds();
+        }
+        let owner_gone = owner_q.get(owner.0).map_or(true, |unit| unit.dead);
+        if summoned.remaining <= 0.0 || owner_gone || died.contains(&entity) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Pull summons that strayed too far back to the owner.
+fn leash(
+    mut summon_q: Query<(&Owner, &Summoned, &mut Transform)>,
+    owner_q: Query<&GlobalTransform, Without<Summoned>>,
+) {
+    for (owner, summoned, mut transform) in summon_q.iter_mut() {
+        if let Ok(owner_gt) = owner_q.get(owner.0) {
+            let owner_pos = owner_gt.translation().truncate();
+            if transform.translation.truncate().distance(owner_pos) > summoned.leash * RAPIER_SCALE
+            {
+                let pos = owner_pos + Vec2::X * FOLLOW_RANGE * RAPIER_SCALE;
+                transform.translation = pos.extend(transform.translation.z);
+            }
+        }
+    }
+}
+
+/// Attack what the owner attacks, or enemies near it, and follow it otherwise.
+fn summon_ai(
+    mut summon_q: Query<
+        (
+            &Owner,
+            &GlobalTransform,
+            &Unit,
+            &Team,
+            &UnitState,
+            &mut UnitCommand,
+        ),
+        With<Summoned>,
+    >,
+    unit_q: Query<(Entity, &Unit, &Team, &GlobalTransform, Option<&UnitState>)>,
+    config: Res<GameWorldConfig>,
+) {
+    if !config.active {
+        return;
+    }
+    for (owner, gt, unit, team, us, mut command) in summon_q.iter_mut() {
+        if unit.dead || !matches!(us.action_id, SkillId::Idle | SkillId::MoveTo) {
+            continue;
+        }
+        let (owner_pos, owner_state) = match unit_q.get(owner.0) {
+            Ok((_, _, _, owner_gt, us)) => (owner_gt.translation().truncate(), us),
+            Err(_) => continue,
+        };
+        let is_target = |entity: Entity| {
+            unit_q
+                .get(entity)
+                .is_ok_and(|(_, u, t, ..)| team.is_enemy(t) && !u.dead)
+        };
+        let owner_target = owner_state
+            .and_then(|us| us.command.as_ref())
+            .and_then(|c| c.target_unit)
+            .filter(|e| is_target(*e));
+        let target = owner_target.or_else(|| {
+            unit_q
+                .iter()
+                .filter(|(e, ..)| is_target(*e))
+                .map(|(e, _, _, target_gt, _)| (e, target_gt.translation().truncate()))
+                .filter(|(_, p)| p.distance(owner_pos) <= ASSIST_RANGE * RAPIER_SCALE)
+                .min_by(|a, b| a.1.distance(owner_pos).total_cmp(&b.1.distance(owner_pos)))
+                .map(|(e, _)| e)
+        });
+
+        let pos = gt.translation().truncate();
+        if let Some(target) = target {
+            // The planner walks into range
+            let target_pos = unit_q
+                .get(target)
+                .ok()
+                .map(|t| t.3.translation().truncate());
+            command.action_id = SkillId::Attack;
+            command.target_unit = Some(target);
+            command.target_position = target_pos;
+            command.movement_direction = Vec2::ZERO;
+            command.target_direction = None;
+        } else if pos.distance(owner_pos) > FOLLOW_RANGE * RAPIER_SCALE {
+            let dir = (owner_pos - pos).normalize_or_zero();
+            command.action_id = SkillId::MoveTo;
+            command.movement_direction = dir;
+            command.target_direction = Some(dir);
+            command.target_unit = None;
+            command.target_position = Some(owner_pos);
+        } else {
+            command.action_id = SkillId::Idle;
+            command.movement_direction = Vec2::ZERO;
+            command.target_direction = None;
+            command.target_unit = None;
+            command.target_position = None;
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use bevy::prelude::*;
+
+use crate::{
+    plugins::{
+        damage::{DamageEvent, HealEvent, HitDamageEvent},
+        team::Team,
+    },
+    RAPIER_SCALE,
+};
+
+use super::{behavior::Brain, unit::Unit};
+
+/// Meters from home a unit chases its targets before giving up on them.
+const LEASH: f32 = 12.0;
+/// Meters around a pulled unit its team joins the fight.
+const PACK_RANGE: f32 = 5.0;
+/// Threat of a target spotted, or passed on to the pack.
+pub const PULL_THREAT: f32 = 1.0;
+/// Threat per point healed on a unit already in the table.
+const HEAL_THREAT: f32 = 0.5;
+/// Meters from home that count as back.
+const HOME_REACHED: f32 = 0.5;
+
+/// Units with a [`Brain`] keep a table of threat, the damage dealt to them and the healing done
+/// to their targets, and fight whoever has the most.
+///
+/// Once the last target is out of the leash or dead, the unit walks back home and takes no threat
+/// on the way.
+pub struct ThreatPlugin;
+impl Plugin for ThreatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ThreatEvent>()
+            .add_system(attach)
+            .add_system(threat_events)
+            .add_system(gain_threat.after(threat_events))
+            .add_system(update_threat.after(gain_threat));
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct Threat {
+    /// Where the unit was spawned and returns to.
+    pub home: Vec2,
+    table: Vec<(Entity, f32)>,
+    pub returning: bool,
+}
+
+impl Threat {
+    pub fn new(home: Vec2) -> Self {
+        Self {
+            home,
+            table: vec![],
+            returning: false,
+        }
+    }
+
+    pub fn get(&self, unit: Entity) -> f32 {
+        self.table
+            .iter()
+            .find(|(e, _)| *e == unit)
+            .map_or(0.0, |(_, threat)| *threat)
+    }
+
+    /// The unit with the most threat.
+    pub fn target(&self) -> Option<Entity> {
+        self.table
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(e, _)| *e)
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        !self.table.is_empty()
+    }
+
+    /// Whether a unit at `pos` is close enough to home to be fought.
+    pub fn in_leash(&self, pos: Vec2) -> bool {
+        pos.distance(self.home) <= LEASH * RAPIER_SCALE
+    }
+
+    fn add(&mut self, unit: Entity, threat: f32) {
+        match self.table.iter_mut().find(|(e, _)| *e == unit) {
+            Some((_, x)) => *x += threat,
+            None => self.table.push((unit, threat)),
+        }
+    }
+}
+
+/// `source` gains threat on `unit`.
+#[derive(Debug, Clone)]
+pub struct ThreatEvent {
+    pub unit: Entity,
+    pub source: Entity,
+    pub threat: f32,
+}
+
+fn attach(mut commands: Commands, query: Query<(Entity, &Transform), Added<Brain>>) {
+    for (entity, transform) in query.iter() {
+        commands
+            .entity(entity)
+            .insert(Threat::new(transform.translation.truncate()));
+    }
+}
+
+fn threat_events(
+    mut hit_events: EventReader<HitDamageEvent>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut heal_events: EventReader<HealEvent>,
+    threat_q: Query<(Entity, &Threat)>,
+    mut events: EventWriter<ThreatEvent>,
+) {
+    for ev in hit_events.iter() {
+        events.send(ThreatEvent {
+            unit: ev.victim,
+            source: ev.source,
+            threat: ev.damage as f32,
+        });
+    }
+    for ev in damage_events.iter() {
+        if let Some(source) = ev.source_unit {
+            events.send(ThreatEvent {
+                unit: ev.unit,
+                source,
+                threat: ev.damage as f32,
+            });
+        }
+    }
+    for ev in heal_events.iter() {
+        let source = match ev.source_unit {
+            Some(x) => x,
+            None => continue,
+        };
+        // Healing a target angers whoever fights it
+        for (entity, threat) in threat_q.iter() {
+            if threat.get(ev.unit) > 0.0 {
+                events.send(ThreatEvent {
+                    unit: entity,
+                    source,
+                    threat: ev.heal as f32 * HEAL_THREAT,
+                });
+            }
+        }
+    }
+}
+
+fn gain_threat(
+    mut events: EventReader<ThreatEvent>,
+    mut threat_q: Query<(Entity, &mut Threat, &Team, &GlobalTransform, &Unit)>,
+    source_q: Query<(&Team, &GlobalTransform, &Unit)>,
+) {
+    for ev in events.iter() {
+        let (team, pos) = match threat_q.get(ev.unit) {
+            Ok((_, threat, team, gt, unit)) if !threat.returning && !unit.dead => {
+                let valid =
+                    source_q
+                        .get(ev.source)
+                        .is_ok_and(|(source_team, source_gt, source)| {
+                            team.is_enemy(source_team)
+                                && !source.dead
+                                && threat.in_leash(source_gt.translation().truncate())
+                        });
+                if !valid {
+                    continue;
+                }
+                (*team, gt.translation().truncate())
+            }
+            _ => continue,
+        };
+
+        let pulled = match threat_q.get_mut(ev.unit) {
+            Ok((_, mut threat, ..)) => {
+                let pulled = !threat.is_engaged();
+                threat.add(ev.source, ev.threat);
+                pulled
+            }
+            Err(_) => continue,
+        };
+        if !pulled {
+            continue;
+        }
+        // The pack joins in when one of them is pulled
+        for (entity, mut threat, pack_team, gt, unit) in threat_q.iter_mut() {
+            if entity == ev.unit
+                || unit.dead
+                || threat.returning
+                || !pack_team.is_ally(&team)
+                || gt.translation().truncate().distance(pos) > PACK_RANGE * RAPIER_SCALE
+                || threat.get(ev.source) > 0.0
+            {
+                continue;
+            }
+            threat.add(ev.source, PULL_THREAT);
+        }
+    }
+}
+
+/// Drop targets that died or left the leash, and go home when none is left.
+fn update_threat(
+    mut threat_q: Query<(&mut Threat, &GlobalTransform, &Unit)>,
+    target_q: Query<(&GlobalTransform, &Unit)>,
+) {
+    for (mut threat, gt, unit) in threat_q.iter_mut() {
+        if unit.dead {
+            if threat.is_engaged() {
+                threat.table.clear();
+            }
+            continue;
+        }
+        if threat.is_engaged() {
+            let home = threat.home;
+            threat.table.retain(|(e, _)| {
+                target_q.get(*e).is_ok_and(|(target_gt, target)| {
+                    !target.dead
+                        && target_gt.translation().truncate().distance(home) <= LEASH * RAPIER_SCALE
+                })
+            });
+            if !threat.is_engaged() {
+                threat.returning = true;
+            }
+        }
+        if threat.returning
+            && gt.translation().truncate().distance(threat.home) <= HOME_REACHED * RAPIER_SCALE
+        {
+            threat.returning = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_accumulates() {
+        let a = Entity::from_raw(1);
+        let mut threat = Threat::new(Vec2::ZERO);
+        assert!(!threat.is_engaged());
+        threat.add(a, 2.0);
+        threat.add(a, 3.5);
+        assert_eq!(threat.get(a), 5.5);
+        assert_eq!(threat.get(Entity::from_raw(2)), 0.0);
+        assert!(threat.is_engaged());
+    }
+
+    #[test]
+    fn target_has_the_most_threat() {
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+        let mut threat = Threat::new(Vec2::ZERO);
+        assert_eq!(threat.target(), None);
+        threat.add(a, 4.0);
+        threat.add(b, 3.0);
+        assert_eq!(threat.target(), Some(a));
+        threat.add(b, 2.0);
+        assert_eq!(threat.target(), Some(b));
+    }
+
+    #[test]
+    fn leash_from_home() {
+        let home = Vec2::new(100.0, -50.0);
+        let threat = Threat::new(home);
+        let leash = LEASH * RAPIER_SCALE;
+        assert!(threat.in_leash(home));
+        assert!(threat.in_leash(home + Vec2::X * leash));
+        assert!(!threat.in_leash(home + Vec2::Y * (leash + 1.0)));
+    }
+}
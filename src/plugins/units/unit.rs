@@ -7,13 +7,12 @@ use crate::{
         actions::{action::Skill, skill_id::SkillId},
         animation::{AnimationSheet, AnimationState},
         damage::HitDamageEvent,
+        game_world::GameObjectType,
         item::{Equipment, Inventory},
         knockback::KnockbackVec,
         movement::Movement,
-        save::{
-            SaveAnimationState, SaveCollisionGroups, SaveEquipment, SaveGameObjectType,
-            SaveInventory, SaveTransform, SaveUnit,
-        },
+        navigation::NavPath,
+        save::saveable::SaveComponent,
         team::Team,
         unit_action::UnitActions,
         unit_state::{ActionState, ChangeActionRequest},
@@ -23,7 +22,9 @@ use crate::{
     ALL_GROUP, RAPIER_SCALE, UNIT_GROUP,
 };
 
-use super::{unit_command::UnitActionPlanner, unit_state::UnitState};
+use super::{
+    attribute::Attributes, buff::Buffs, unit_command::UnitActionPlanner, unit_state::UnitState,
+};
 
 pub struct UnitPlugin;
 
@@ -34,11 +35,10 @@ impl Plugin for UnitPlugin {
             .register_type::<Unit>()
             .register_type::<KillReward>()
             .add_system(unit_update)
+            .add_system(regen_mp)
             .add_system(on_unit_hit)
             .add_event::<UnitDieEvent>()
-            // Attack Attribute
-            .add_event::<AttackModifierEvent>()
-            .add_system(attack_modifier)
+            .add_event::<UnitKilledEvent>()
             // Hp
             .add_system(super::hp_text::attach_text)
             .add_system(super::hp_text::update_hp_text)
@@ -49,7 +49,8 @@ impl Plugin for UnitPlugin {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component, Reflect, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component, Reflect)]
+#[serde(default)]
 pub struct Unit {
     pub hp: i32,
     pub hp_max: i32,
@@ -59,56 +60,53 @@ pub struct Unit {
     pub dead: bool,
     // Status Effect
     pub stun: f32,
+    pub mp: f32,
+    pub mp_max: f32,
+    /// Mp per second.
+    pub mp_regen: f32,
+    pub armor: i32,
+    pub attack_speed: f32,
+    /// Percentage.
+    pub crit_chance: i32,
 }
-
-#[derive(Debug, Clone, Serialize, Deserialize, Reflect)]
-pub struct AttributeModifier {
-    pub source: Entity,
-    pub amount: i32,
-    pub percentage: i32,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, Component, Default)]
-pub struct AttackAttribute {
-    pub base: i32,
-    pub modifiers: Vec<AttributeModifier>,
+impl Default for Unit {
+    fn default() -> Self {
+        Self {
+            hp: 0,
+            hp_max: 0,
+            movement_speed: 0.0,
+            atk: 0,
+            dead: false,
+            stun: 0.0,
+            mp: 0.0,
+            mp_max: 0.0,
+            mp_regen: 0.0,
+            armor: 0,
+            attack_speed: 1.0,
+            crit_chance: 0,
+        }
+    }
 }
 
-impl AttackAttribute {
-    fn get_attr(&self) -> i32 {
-        let (amount, percentage) = self
-            .modifiers
-            .iter()
-            .fold((0, 0), |acc, x| (acc.0 + x.amount, acc.1 + x.percentage));
-        self.base + amount + self.base * percentage / 100
+impl Unit {
+    /// Damage left after armor, a hit always does at least 1.
+    pub fn damage_taken(&self, damage: i32) -> i32 {
+        match damage > 0 {
+            true => (damage - self.armor).max(1),
+            false => damage,
+        }
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum AttackModifierEvent {
-    Add(Entity, AttributeModifier),
-    Remove(Entity, Entity),
-}
-fn attack_modifier(
-    mut events: EventReader<AttackModifierEvent>,
-    mut query: Query<(&mut AttackAttribute, &mut Unit)>,
-) {
-    for ev in events.iter() {
-        match ev {
-            AttackModifierEvent::Add(unit, modifier) => {
-                if let Ok((mut attr, mut unit)) = query.get_mut(*unit) {
-                    attr.modifiers.retain(|m| m.source != modifier.source);
-                    attr.modifiers.push(modifier.clone());
-                    unit.atk = attr.get_attr();
-                }
-            }
-            AttackModifierEvent::Remove(unit, entity) => {
-                if let Ok((mut attr, mut unit)) = query.get_mut(*unit) {
-                    attr.modifiers.retain(|m| &m.source != entity);
-                    unit.atk = attr.get_attr();
-                }
-            }
+fn regen_mp(mut query: Query<&mut Unit>, time: Res<Time>, config: Res<GameWorldConfig>) {
+    if !config.active {
+        return;
+    }
+    for mut unit in query.iter_mut() {
+        if unit.dead || unit.mp >= unit.mp_max {
+            continue;
         }
+        unit.mp = (unit.mp + unit.mp_regen * time.delta_seconds()).min(unit.mp_max);
     }
 }
 
@@ -174,21 +172,21 @@ pub fn spawn_unit(
             ..Default::default()
         })
         .insert(Name::new(s.name))
-        .insert(AttackAttribute {
-            base: s.unit.atk,
-            ..Default::default()
-        })
+        .insert(Attributes::from_unit(&s.unit))
+        .insert(Buffs::default())
         .insert(s.unit)
         .insert(UnitState {
             action_id: SkillId::Idle,
             action_entity: None,
             action_state: ActionState::Active,
             action_time: None,
+            channel_tick: None,
             command: None,
         })
         .insert(UnitCommand::default())
         .insert(UnitActionPlanner::default())
         .insert(Movement::default())
+        .insert(NavPath::default())
         .insert(KnockbackVec::default())
         .push_children(&actions)
         .insert(UnitActions { actions })
@@ -196,13 +194,14 @@ pub fn spawn_unit(
         .insert(Equipment::default())
         .insert(s.team)
         // Save
-        .insert(SaveGameObjectType)
-        .insert(SaveTransform)
-        .insert(SaveUnit)
-        .insert(SaveInventory)
-        .insert(SaveEquipment)
-        .insert(SaveCollisionGroups)
-        .insert(SaveAnimationState)
+        .insert(SaveComponent::<GameObjectType>::default())
+        .insert(SaveComponent::<Transform>::default())
+        .insert(SaveComponent::<Unit>::default())
+        .insert(SaveComponent::<Buffs>::default())
+        .insert(SaveComponent::<Inventory>::default())
+        .insert(SaveComponent::<Equipment>::default())
+        .insert(SaveComponent::<CollisionGroups>::default())
+        .insert(SaveComponent::<AnimationState>::default())
         // Animation
         .insert(SpriteSheetBundle {
             texture_atlas: texture_atlas_handle,
@@ -257,7 +256,7 @@ pub fn unit_update(
     }
 }
 
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Clone, PartialEq, Reflect)]
 pub struct UnitDieEvent(pub Entity);
 impl Default for UnitDieEvent {
     fn default() -> Self {
@@ -265,16 +264,28 @@ impl Default for UnitDieEvent {
     }
 }
 
+/// Sent with [`UnitDieEvent`], `killer` is the unit that dealt the last damage.
+#[derive(Debug, Clone)]
+pub struct UnitKilledEvent {
+    pub unit: Entity,
+    pub killer: Option<Entity>,
+}
+
 pub fn on_unit_hit(
     mut events: EventReader<HitDamageEvent>,
-    mut unit_q: Query<(&mut Unit,)>,
+    mut unit_q: Query<(&mut Unit, Option<&mut Buffs>)>,
     mut change_events: EventWriter<ChangeActionRequest>,
     mut die_events: EventWriter<UnitDieEvent>,
+    mut killed_events: EventWriter<UnitKilledEvent>,
 ) {
     for ev in events.iter() {
-        if let Ok((mut unit,)) = unit_q.get_mut(ev.victim) {
+        if let Ok((mut unit, buffs)) = unit_q.get_mut(ev.victim) {
             info!("Unit hit: {ev:?}");
-            unit.hp -= ev.damage;
+            let mut damage = unit.damage_taken(ev.damage);
+            if let Some(mut buffs) = buffs {
+                damage = buffs.absorb(damage);
+            }
+            unit.hp -= damage;
 
             let stun = unit.stun.max(ev.hit_stun);
             unit.stun = stun;
@@ -282,6 +293,10 @@ pub fn on_unit_hit(
             if unit.hp <= 0 {
                 if !unit.dead {
                     die_events.send(UnitDieEvent(ev.victim));
+                    killed_events.send(UnitKilledEvent {
+                        unit: ev.victim,
+                        killer: Some(ev.source),
+                    });
 
                     unit.dead = true;
 
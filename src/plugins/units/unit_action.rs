@@ -2,7 +2,7 @@ use bevy::prelude::*;
 
 pub struct UnitActionPlugin;
 impl Plugin for UnitActionPlugin {
-    fn build(&self, app: &mut App) {}
+    fn build(&self, _app: &mut App) {}
 }
 
 #[derive(Debug, Default, Component)]
@@ -2,7 +2,7 @@ use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::plugins::{
-    actions::{setting::TargetSetting, skill_id::SkillId},
+    actions::{setting::TargetSetting, skill_data::SkillSettings, skill_id::SkillId},
     unit_state::{ChangeActionRequest, UnitState},
 };
 
@@ -31,15 +31,19 @@ pub fn planning(
     )>,
     unit_q: Query<(&Unit, &GlobalTransform)>,
     mut change_events: EventWriter<ChangeActionRequest>,
+    settings: Res<SkillSettings>,
 ) {
     for (entity, _p, us, gt, mut uc) in query.iter_mut() {
-        if !us.action_id.setting().cancelable {
+        if !settings.get(&us.action_id).is_none_or(|s| s.cancelable) {
             continue;
         }
         if us.command.as_ref() == Some(uc.as_ref()) {
             continue;
         }
-        let setting = uc.action_id.setting();
+        let setting = match settings.get(&uc.action_id) {
+            Some(x) => x,
+            None => continue,
+        };
         match setting.target_range {
             Some(range) => {
                 match setting.target {
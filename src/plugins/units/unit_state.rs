@@ -2,11 +2,17 @@ use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    plugins::actions::{action::Skill, base::BaseSkill, skill_id::SkillId},
+    plugins::actions::{
+        action::{Skill, SkillRejectReason},
+        base::BaseSkill,
+        skill_data::SkillSettings,
+        skill_id::SkillId,
+    },
     res::GameWorldConfig,
 };
 
 use super::{
+    unit::Unit,
     unit_action::UnitActions,
     unit_command::{planning, UnitCommand},
 };
@@ -21,6 +27,7 @@ impl Plugin for UnitStatePlugin {
             .register_type::<UnitState>()
             .add_system(planning.before(change_action_events))
             .add_event::<ChangeActionRequest>()
+            .add_event::<ChangeActionRejected>()
             .add_event::<StateTransitionRequest>()
             .add_system(change_action_events.before(state_transition))
             .add_event::<UnitActionEnterEvent>()
@@ -35,6 +42,7 @@ impl Plugin for UnitStatePlugin {
                     .before(ActionSystemLabel::Exit),
             )
             .add_event::<UnitActionActiveUpdateEvent>()
+            .add_event::<UnitActionChannelTickEvent>()
             .add_system(
                 unit_state_update
                     .before(PassiveUpdateLabel)
@@ -139,21 +147,23 @@ pub struct UnitState {
     pub action_entity: Option<Entity>,
     pub action_state: ActionState,
     pub action_time: Option<f32>,
+    /// Seconds until the next tick of a channeled skill.
+    #[serde(default)]
+    pub channel_tick: Option<f32>,
 
     pub command: Option<UnitCommand>,
 }
 
-#[derive(Debug, Clone, PartialEq, Copy, Eq, Serialize, Deserialize, Reflect)]
+/// Seconds between ticks of a channeled skill.
+pub const CHANNEL_TICK: f32 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Copy, Eq, Serialize, Deserialize, Reflect, Default)]
 pub enum ActionState {
+    #[default]
     Startup,
     Active,
     Recover,
 }
-impl Default for ActionState {
-    fn default() -> Self {
-        Self::Startup
-    }
-}
 
 #[derive(Debug)]
 pub struct UnitPassiveUpdateEvent {
@@ -164,12 +174,16 @@ fn unit_passive_update(
     config: Res<GameWorldConfig>,
     mut unit_q: Query<(Entity, &UnitState, &UnitActions)>,
     mut events: EventWriter<UnitPassiveUpdateEvent>,
+    settings: Res<SkillSettings>,
 ) {
     if !config.active {
         return;
     }
     for (id, state, ua) in unit_q.iter_mut() {
-        let setting = state.action_id.setting();
+        let setting = match settings.get(&state.action_id) {
+            Some(x) => x,
+            None => continue,
+        };
         match setting.base {
             BaseSkill::Dead => (),
             _ => {
@@ -189,25 +203,37 @@ pub struct UnitActionActiveUpdateEvent {
     pub action_id: SkillId,
 }
 
+/// Sent every [`CHANNEL_TICK`] while a channeled skill is `Active`.
+#[derive(Debug)]
+pub struct UnitActionChannelTickEvent {
+    pub unit: Entity,
+    pub action: Entity,
+    pub action_id: SkillId,
+}
+
 pub fn unit_state_update(
-    mut unit_q: Query<(Entity, &mut UnitState)>,
+    mut unit_q: Query<(Entity, &mut UnitState, Option<&Unit>)>,
     time: Res<Time>,
     config: Res<GameWorldConfig>,
     mut transition_events: EventWriter<StateTransitionRequest>,
     mut update_events: EventWriter<UnitActionActiveUpdateEvent>,
+    mut channel_events: EventWriter<UnitActionChannelTickEvent>,
 ) {
     if !config.active {
         return;
     }
-    for (id, mut state) in unit_q.iter_mut() {
-        let delta = time.delta_seconds();
+    for (id, mut state, unit) in unit_q.iter_mut() {
+        let mut delta = time.delta_seconds();
+        if state.action_id == SkillId::Attack {
+            delta *= unit.map_or(1.0, |u| u.attack_speed.max(0.1));
+        }
         if let Some(time) = state.action_time.as_mut() {
             *time -= delta;
         }
         match state.action_state {
             ActionState::Startup => {
                 // TODO: send startup update event
-                if state.action_time.map_or(false, |t| t <= 0.0) {
+                if state.action_time.is_some_and(|t| t <= 0.0) {
                     transition_events.send(StateTransitionRequest {
                         unit: id,
                         transition: TransitionType::ActionState {
@@ -224,6 +250,17 @@ pub fn unit_state_update(
                         action,
                         action_id: state.action_id,
                     });
+                    if let Some(tick) = state.channel_tick.as_mut() {
+                        *tick -= delta;
+                        if *tick <= 0.0 {
+                            *tick += CHANNEL_TICK;
+                            channel_events.send(UnitActionChannelTickEvent {
+                                unit: id,
+                                action,
+                                action_id: state.action_id,
+                            });
+                        }
+                    }
                 }
                 if let Some(time) = state.action_time {
                     if time <= 0.0 {
@@ -239,7 +276,7 @@ pub fn unit_state_update(
             }
             ActionState::Recover => {
                 // TODO: send recover update event
-                if state.action_time.map_or(false, |t| t <= 0.0) {
+                if state.action_time.is_some_and(|t| t <= 0.0) {
                     transition_events.send(StateTransitionRequest {
                         unit: id,
                         transition: TransitionType::Action(SkillId::Idle, UnitCommand::default()),
@@ -256,12 +293,61 @@ pub struct ChangeActionRequest {
     pub command: UnitCommand,
     pub entity: Entity,
 }
+
+/// A skill could not be used, it is on cooldown or the unit cannot pay for it.
+#[derive(Debug, Clone)]
+pub struct ChangeActionRejected {
+    pub entity: Entity,
+    pub action_id: SkillId,
+    pub reason: SkillRejectReason,
+}
+
 pub fn change_action_events(
     mut events: EventReader<ChangeActionRequest>,
     mut transition_events: EventWriter<StateTransitionRequest>,
+    mut reject_events: EventWriter<ChangeActionRejected>,
+    mut unit_q: Query<(
+        &mut Unit,
+        &UnitState,
+        &UnitActions,
+        Option<&mut UnitCommand>,
+    )>,
+    mut skill_q: Query<&mut Skill>,
+    settings: Res<SkillSettings>,
 ) {
     for event in events.iter() {
         // info!("{event:?}");
+        let mut rejected = false;
+        if let Ok((mut unit, us, ua, command)) = unit_q.get_mut(event.entity) {
+            // Ignored by state_transition, nothing to pay
+            let locked = us.action_id == SkillId::Dead || us.action_id == SkillId::Stun;
+            // A skill without a valid setting has nothing to check, the action still changes
+            let usage = settings.get(&event.action_id).map(|x| &x.usage);
+            for entity in ua.actions.iter() {
+                let (mut skill, usage) = match (skill_q.get_mut(*entity), usage) {
+                    (Ok(x), Some(usage)) if !locked && x.action_id == event.action_id => (x, usage),
+                    _ => continue,
+                };
+                if let Err(reason) = skill.check(&unit, usage) {
+                    reject_events.send(ChangeActionRejected {
+                        entity: event.entity,
+                        action_id: event.action_id,
+                        reason,
+                    });
+                    rejected = true;
+                    // Keep doing the current action instead of asking again
+                    if let Some(mut command) = command {
+                        *command = us.command.clone().unwrap_or_default();
+                    }
+                } else {
+                    skill.spend(&mut unit, usage);
+                }
+                break;
+            }
+        }
+        if rejected {
+            continue;
+        }
         transition_events.send(StateTransitionRequest {
             unit: event.entity,
             transition: TransitionType::Action(event.action_id, event.command.clone()),
@@ -304,6 +390,7 @@ pub fn state_transition(
     mut enter_events: EventWriter<UnitActionEnterEvent>,
     mut unit_q: Query<(&mut UnitState, &UnitActions)>,
     action_q: Query<(Entity, &Skill)>,
+    settings: Res<SkillSettings>,
 ) {
     for ev in events.iter() {
         if let Ok((mut us, ua)) = unit_q.get_mut(ev.unit) {
@@ -312,9 +399,15 @@ pub fn state_transition(
                     if us.action_id == SkillId::Dead || us.action_id == SkillId::Stun {
                         continue;
                     }
+                    let s = match settings.get(action_id) {
+                        Some(x) => x,
+                        None => continue,
+                    };
 
                     us.action_id = *action_id;
                     us.command = Some(command.clone());
+                    // Stun and every other action interrupt a channel
+                    us.channel_tick = None;
                     for entity in ua.actions.iter() {
                         if let Ok((e, action)) = action_q.get(*entity) {
                             if action.action_id == us.action_id {
@@ -329,13 +422,13 @@ pub fn state_transition(
                         action: us.action_entity.unwrap(),
                     });
 
-                    let s = action_id.setting();
                     match s.action_state {
                         ActionState::Startup => {
                             us.action_time = s.startup_time;
                         }
                         ActionState::Active => {
                             us.action_time = s.active_time;
+                            channel(&mut us, &action_q, &settings);
 
                             enter_active_events.send(UnitActionEnterActiveEvent {
                                 unit: ev.unit,
@@ -361,9 +454,13 @@ pub fn state_transition(
                     // Check it is still the current state
                     if *current_action_id == us.action_id && us.action_state != *target_action_state
                     {
+                        let s = match settings.get(current_action_id) {
+                            Some(x) => x,
+                            None => continue,
+                        };
                         us.action_state = *target_action_state;
+                        us.channel_tick = None;
 
-                        let s = us.action_id.setting();
                         match target_action_state {
                             ActionState::Startup => {
                                 // This should not happen
@@ -372,6 +469,7 @@ pub fn state_transition(
                             }
                             ActionState::Active => {
                                 us.action_time = s.active_time;
+                                channel(&mut us, &action_q, &settings);
 
                                 enter_active_events.send(UnitActionEnterActiveEvent {
                                     unit: ev.unit,
@@ -394,6 +492,22 @@ pub fn state_transition(
     }
 }
 
+/// Channeled skills stay `Active` for the duration of their level and start ticking.
+fn channel(us: &mut UnitState, action_q: &Query<(Entity, &Skill)>, settings: &SkillSettings) {
+    let skill = match us.action_entity.and_then(|e| action_q.get(e).ok()) {
+        Some((_, skill)) => skill,
+        None => return,
+    };
+    let base = match settings.get(&skill.action_id) {
+        Some(x) => &x.base,
+        None => return,
+    };
+    if let Some(time) = base.channel_time(skill.level) {
+        us.action_time = Some(time);
+        us.channel_tick = Some(CHANNEL_TICK);
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
 pub enum ActionSystemLabel {
     Enter,
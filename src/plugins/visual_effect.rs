@@ -24,6 +24,8 @@ pub enum VisualEffect {
     Diffusion,
     DeadFinger,
     Heal,
+    Explosion,
+    Fire,
 }
 
 #[derive(Component, Reflect)]
@@ -171,6 +173,45 @@ fn visual_effect_marker(
                 };
                 (texture_atlas_handle, animation_indices)
             }
+            // Tinted waves of the same sheet
+            VisualEffect::Explosion => {
+                let texture_handle = asset_server.load("images/Classic/Preview.png");
+                let texture_atlas = TextureAtlas::from_grid(
+                    texture_handle,
+                    Vec2::new(125.0, 150.0),
+                    6,
+                    5,
+                    None,
+                    None,
+                );
+                let texture_atlas_handle = texture_atlases.add(texture_atlas);
+
+                let animation_indices = AnimationIndices { first: 6, last: 11 };
+                (texture_atlas_handle, animation_indices)
+            }
+            VisualEffect::Fire => {
+                let texture_handle = asset_server.load("images/Classic/Preview.png");
+                let texture_atlas = TextureAtlas::from_grid(
+                    texture_handle,
+                    Vec2::new(125.0, 150.0),
+                    6,
+                    5,
+                    None,
+                    None,
+                );
+                let texture_atlas_handle = texture_atlases.add(texture_atlas);
+
+                let animation_indices = AnimationIndices {
+                    first: 12,
+                    last: 17,
+                };
+                (texture_atlas_handle, animation_indices)
+            }
+        };
+        let color = match marker.visual_effect {
+            VisualEffect::Explosion => Color::ORANGE,
+            VisualEffect::Fire => Color::ORANGE_RED,
+            _ => Color::WHITE,
         };
 
         commands.entity(entity).insert((
@@ -182,6 +223,7 @@ fn visual_effect_marker(
                 texture_atlas: texture_atlas_handle,
                 sprite: TextureAtlasSprite {
                     index: animation_indices.first,
+                    color,
                     custom_size: Some(marker.size),
                     ..Default::default()
                 },
@@ -3,10 +3,7 @@ use bevy_egui::{egui, EguiContext};
 
 use crate::AppState;
 
-pub(crate) fn esc_menu(
-    mut egui_context: ResMut<EguiContext>,
-    mut app_state: ResMut<State<AppState>>,
-) {
+pub fn esc_menu(mut egui_context: ResMut<EguiContext>, mut app_state: ResMut<State<AppState>>) {
     egui::Window::new("Esc Menu").show(egui_context.ctx_mut(), |ui| {
         if ui.button("Option").clicked() {
             // To option
@@ -1,16 +1,73 @@
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContext};
 
-use crate::AppState;
+use crate::plugins::save::slot::{now, DeleteSlot, LoadSlot, NewGame, SaveSlot, SaveSlots};
 
-pub fn main_menu(mut egui_context: ResMut<EguiContext>, mut app_state: ResMut<State<AppState>>) {
+#[derive(Debug, Default)]
+pub struct MainMenu {
+    new_name: String,
+    show_slots: bool,
+    confirm_delete: Option<String>,
+}
+
+pub fn main_menu(
+    mut egui_context: ResMut<EguiContext>,
+    slots: Res<SaveSlots>,
+    mut menu: Local<MainMenu>,
+    mut new_events: EventWriter<NewGame>,
+    mut load_events: EventWriter<LoadSlot>,
+    mut delete_events: EventWriter<DeleteSlot>,
+) {
+    let menu = &mut *menu;
     // egui::Window::new("Hello")
     egui::TopBottomPanel::bottom("main_menu").show(egui_context.ctx_mut(), |ui| {
         ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-            let response = ui.button("Start");
-            response.request_focus();
-            if response.clicked() {
-                app_state.set(AppState::Level).unwrap();
+            let response = ui.add_enabled(slots.latest().is_some(), egui::Button::new("Continue"));
+            if let Some(slot) = slots.latest() {
+                if ui.memory().focus().is_none() {
+                    response.request_focus();
+                }
+                if response.clicked() {
+                    load_events.send(LoadSlot(slot.id.clone()));
+                }
+            }
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut menu.new_name);
+                if ui
+                    .add_enabled(!slots.is_full(), egui::Button::new("New Game"))
+                    .clicked()
+                {
+                    new_events.send(NewGame {
+                        name: menu.new_name.clone(),
+                    });
+                }
+            });
+            if ui
+                .add_enabled(!slots.0.is_empty(), egui::Button::new("Load"))
+                .clicked()
+            {
+                menu.show_slots = !menu.show_slots;
+            }
+            if menu.show_slots {
+                for slot in slots.0.iter() {
+                    ui.horizontal(|ui| {
+                        ui.label(slot_label(slot));
+                        if ui.button("Load").clicked() {
+                            load_events.send(LoadSlot(slot.id.clone()));
+                        }
+                        if menu.confirm_delete.as_ref() == Some(&slot.id) {
+                            if ui.button("Confirm").clicked() {
+                                delete_events.send(DeleteSlot(slot.id.clone()));
+                                menu.confirm_delete = None;
+                            }
+                            if ui.button("Cancel").clicked() {
+                                menu.confirm_delete = None;
+                            }
+                        } else if ui.button("Delete").clicked() {
+                            menu.confirm_delete = Some(slot.id.clone());
+                        }
+                    });
+                }
             }
             if ui.add_enabled(false, egui::Button::new("Option")).clicked() {
                 // To option
@@ -22,3 +79,25 @@ pub fn main_menu(mut egui_context: ResMut<EguiContext>, mut app_state: ResMut<St
         });
     });
 }
+
+fn slot_label(slot: &SaveSlot) -> String {
+    let meta = &slot.meta;
+    let playtime = meta.playtime as u64;
+    let ago = now().saturating_sub(meta.timestamp);
+    format!(
+        "{} | {:02}:{:02}:{:02} | Level {} | Gold {} | {} | saved {}",
+        meta.name,
+        playtime / 3600,
+        playtime / 60 % 60,
+        playtime % 60,
+        meta.level,
+        meta.gold,
+        meta.ldtk_level.as_deref().unwrap_or("-"),
+        match ago {
+            0..=59 => "just now".to_string(),
+            60..=3599 => format!("{} min ago", ago / 60),
+            3600..=86399 => format!("{} h ago", ago / 3600),
+            _ => format!("{} days ago", ago / 86400),
+        }
+    )
+}